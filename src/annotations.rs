@@ -18,6 +18,7 @@ use bstr::ByteSlice;
 use parking_lot::Mutex;
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::graph_query::GraphQuery;
 use crate::quad_tree::QuadTree;
 use crate::{app::SharedState, gui::text::LabelPos};
 use crate::{geometry::*, universe::Node, view::*};
@@ -361,6 +362,50 @@ impl AnnotationLabelSet {
         }
     }
 
+    /// Create a label set from already computed labels, e.g. when
+    /// restoring a session, rather than from the annotation records
+    pub fn from_labels(
+        annotation_name: &str,
+        label_set_name: &str,
+        column: AnnotationColumn,
+        path_id: PathId,
+        path_name: &str,
+        labels: &[(NodeId, String)],
+    ) -> Self {
+        let column_str = match &column {
+            AnnotationColumn::Gff3(c) => c.to_string(),
+            AnnotationColumn::Bed(c) => c.to_string(),
+        };
+
+        let mut label_strings: Vec<String> = Vec::new();
+        let mut label_indices: HashMap<&str, usize> = HashMap::new();
+        let mut node_labels: FxHashMap<NodeId, Vec<usize>> =
+            FxHashMap::default();
+
+        for (node, text) in labels {
+            let ix = *label_indices.entry(text.as_str()).or_insert_with(|| {
+                label_strings.push(text.to_string());
+                label_strings.len() - 1
+            });
+            node_labels.entry(*node).or_default().push(ix);
+        }
+
+        Self {
+            annotation_name: annotation_name.to_string(),
+            label_set_name: label_set_name.to_string(),
+
+            column_str,
+            column,
+
+            path_name: path_name.to_string(),
+            show: Arc::new(true.into()),
+
+            path_id,
+            label_strings,
+            labels: node_labels,
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.label_set_name
     }
@@ -427,8 +472,10 @@ impl Annotations {
     pub fn insert_gff3(&mut self, name: &str, records: Gff3Records) {
         let records = Arc::new(records);
         self.gff3_annotations.insert(name.to_string(), records);
-        self.annot_names
-            .push((name.to_string(), AnnotationFileType::Gff3));
+        if !self.annot_names.iter().any(|(n, _)| n == name) {
+            self.annot_names
+                .push((name.to_string(), AnnotationFileType::Gff3));
+        }
     }
 
     pub fn remove_gff3(&mut self, name: &str) {
@@ -443,8 +490,10 @@ impl Annotations {
     pub fn insert_bed(&mut self, name: &str, records: BedRecords) {
         let records = Arc::new(records);
         self.bed_annotations.insert(name.to_string(), records);
-        self.annot_names
-            .push((name.to_string(), AnnotationFileType::Bed));
+        if !self.annot_names.iter().any(|(n, _)| n == name) {
+            self.annot_names
+                .push((name.to_string(), AnnotationFileType::Bed));
+        }
     }
 
     pub fn remove_bed(&mut self, name: &str) {
@@ -659,6 +708,73 @@ pub fn path_step_range(
     Some(&steps[start..end])
 }
 
+/// Creates a label set from the `column` values of the given
+/// records, with each label placed on the middle step of its record's
+/// range along the path
+pub fn calculate_annotation_set<C>(
+    graph: &GraphQuery,
+    records: &C,
+    record_indices: &[usize],
+    path_id: PathId,
+    path_name: &str,
+    column: &C::ColumnKey,
+    label_set_name: &str,
+) -> Option<AnnotationLabelSet>
+where
+    C: AnnotationCollection + Send + Sync + 'static,
+{
+    log::warn!("checking record_indices.is_empty");
+    if record_indices.is_empty() {
+        return None;
+    }
+
+    let offset = path_name_offset(path_name.as_bytes());
+
+    log::warn!("getting path steps");
+    let steps = graph.path_pos_steps(path_id)?;
+
+    let mut label_strings: Vec<String> =
+        Vec::with_capacity(record_indices.len());
+    let mut label_indices: FxHashMap<NodeId, Vec<usize>> = FxHashMap::default();
+
+    for &record_ix in record_indices.iter() {
+        log::trace!("getting record");
+        let record = records.records().get(record_ix)?;
+
+        if let Some(range) =
+            path_step_range(&steps, offset, record.start(), record.end())
+        {
+            if let Some(value) = record.get_first(column) {
+                if let Some((mid, _, _)) = range.get(range.len() / 2) {
+                    let index = label_strings.len();
+                    let label = format!("{}", value.as_bstr());
+                    label_strings.push(label);
+                    label_indices.entry(mid.id()).or_default().push(index);
+                }
+            }
+        }
+    }
+
+    for labels in label_indices.values_mut() {
+        labels.sort();
+        labels.dedup();
+        labels.shrink_to_fit();
+    }
+
+    label_strings.shrink_to_fit();
+    label_indices.shrink_to_fit();
+
+    Some(AnnotationLabelSet::new(
+        records,
+        path_id,
+        path_name.as_bytes(),
+        column,
+        label_set_name,
+        label_strings,
+        label_indices,
+    ))
+}
+
 pub fn path_step_radius(
     steps: &[(Handle, StepPtr, usize)],
     nodes: &[Node],
@@ -1007,6 +1123,27 @@ pub fn cluster_annotations(
         .collect()
 }
 
+/// The value of a record's column, as hashed by
+/// `record_column_hash_color`, formatted for display
+pub fn record_column_label<R, K>(record: &R, column: &K) -> String
+where
+    R: AnnotationRecord<ColumnKey = K>,
+    K: ColumnKey,
+{
+    if column == &K::start() {
+        record.start().to_string()
+    } else if column == &K::end() {
+        record.end().to_string()
+    } else {
+        let values = record
+            .get_all(column)
+            .into_iter()
+            .map(|v| v.to_str_lossy())
+            .collect::<Vec<_>>();
+        values.join(";")
+    }
+}
+
 pub fn record_column_hash_color<R, K>(
     record: &R,
     column: &K,
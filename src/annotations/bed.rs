@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use bstr::ByteSlice;
 
 use anyhow::Result;
@@ -9,6 +11,7 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct BedRecords {
     file_name: String,
+    file_path: PathBuf,
 
     pub records: Vec<BedRecord>,
 
@@ -70,6 +73,11 @@ impl BedRecords {
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
 
+        let file_path = path
+            .as_ref()
+            .canonicalize()
+            .unwrap_or_else(|_| path.as_ref().to_owned());
+
         let file = File::open(path)?;
 
         let mut reader = BufReader::new(file);
@@ -129,6 +137,7 @@ impl BedRecords {
 
         Ok(Self {
             file_name,
+            file_path,
             records,
             column_keys,
 
@@ -136,6 +145,11 @@ impl BedRecords {
         })
     }
 
+    /// The path the records were parsed from
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
     pub fn has_headers(&self) -> bool {
         !self.headers.is_empty()
     }
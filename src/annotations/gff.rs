@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use bstr::ByteSlice;
 
@@ -13,6 +14,7 @@ use super::{
 #[derive(Debug, Clone, Default)]
 pub struct Gff3Records {
     file_name: String,
+    file_path: PathBuf,
 
     pub records: Vec<Gff3Record>,
 
@@ -259,6 +261,11 @@ impl Gff3Records {
         let file_name = path.as_ref().file_name().unwrap();
         let file_name = file_name.to_str().unwrap().to_string();
 
+        let file_path = path
+            .as_ref()
+            .canonicalize()
+            .unwrap_or_else(|_| path.as_ref().to_owned());
+
         let file = File::open(path)?;
 
         let mut reader = BufReader::new(file);
@@ -301,11 +308,17 @@ impl Gff3Records {
 
         Ok(Self {
             file_name,
+            file_path,
 
             records,
             attribute_keys,
         })
     }
+
+    /// The path the records were parsed from
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
 }
 
 impl Gff3Record {
@@ -1,11 +1,21 @@
 pub mod channels;
+pub mod config;
+pub mod dotplot;
 pub mod mainview;
+pub mod navigation;
+pub mod node_query;
+pub mod path_comparison;
+pub mod path_highlight;
+pub mod path_matrix;
 pub mod selection;
 pub mod settings;
 pub mod shared_state;
+pub mod tour;
+pub mod validation;
 
 pub use channels::*;
 use handlegraph::pathhandlegraph::PathId;
+pub use navigation::*;
 pub use settings::*;
 pub use shared_state::*;
 
@@ -57,6 +67,10 @@ pub enum AppInput {
 }
 
 impl BindableInput for AppInput {
+    fn all_inputs() -> Vec<Self> {
+        vec![AppInput::KeyClearSelection, AppInput::KeyToggleTheme]
+    }
+
     fn default_binds() -> SystemInputBindings<Self> {
         use winit::event::VirtualKeyCode as Key;
         use AppInput as Input;
@@ -485,6 +499,15 @@ impl App {
                 _ => (),
             },
         }
+
+        self.store_selection_bounds();
+    }
+
+    fn store_selection_bounds(&self) {
+        let bounds = self
+            .selected_nodes_bounding_box
+            .map(|(p0, p1)| Rect::new(p0, p1));
+        self.shared_state.selection_bounds.store(bounds);
     }
 
     fn toggle_dark_mode(&self, gui_msg: &Sender<GuiMsg>) {
@@ -511,6 +534,7 @@ impl App {
                         self.selection_changed = true;
                         self.selected_nodes.clear();
                         self.selected_nodes_bounding_box = None;
+                        self.store_selection_bounds();
                     }
                 }
                 AppInput::KeyToggleTheme => {
@@ -526,18 +550,26 @@ impl App {
 #[derive(FromArgs)]
 /// Gfaestus
 pub struct Args {
-    /// the GFA file to load
+    /// the GFA file to load, can be omitted if `--session` is used
     #[argh(positional)]
-    pub gfa: String,
+    pub gfa: Option<String>,
 
-    /// the layout file to use
+    /// the layout file to use, can be omitted if `--session` is used
     #[argh(positional)]
-    pub layout: String,
+    pub layout: Option<String>,
+
+    /// restore a session file (.gfs) saved from a previous run
+    #[argh(option)]
+    pub session: Option<std::path::PathBuf>,
 
     /// load and run a Rhai script file at startup, e.g. for configuration
     #[argh(option)]
     pub run_script: Option<String>,
 
+    /// re-run the `--run-script` file every time it changes
+    #[argh(switch)]
+    pub watch_script: bool,
+
     #[cfg(target_os = "linux")]
     /// force use of X11 window (only applicable in Wayland contexts)
     #[argh(switch)]
@@ -571,6 +603,33 @@ pub struct Args {
         from_str_fn(annotation_files_to_str)
     )]
     pub annotation_files: Vec<std::path::PathBuf>,
+
+    /// path to an overlay file (.gfo) to load at startup, can be used multiple times to load several overlays
+    #[argh(option, long = "overlay-file", from_str_fn(overlay_files_to_str))]
+    pub overlay_files: Vec<std::path::PathBuf>,
+
+    /// render the initial view (or the session's view) to a PNG file and exit
+    #[argh(option)]
+    pub render_png: Option<std::path::PathBuf>,
+
+    /// width in pixels of the `--render-png` image, defaults to the window width
+    #[argh(option)]
+    pub render_width: Option<u32>,
+
+    /// height in pixels of the `--render-png` image, defaults to the window's aspect ratio
+    #[argh(option)]
+    pub render_height: Option<u32>,
+}
+
+fn overlay_files_to_str(input: &str) -> Result<std::path::PathBuf, String> {
+    use std::path::PathBuf;
+    let path = PathBuf::from(input.trim());
+    match path.canonicalize() {
+        Ok(canon) => Ok(canon),
+        Err(err) => {
+            Err(format!("Error when parsing overlay file list: {:?}", err))
+        }
+    }
 }
 
 fn annotation_files_to_str(input: &str) -> Result<std::path::PathBuf, String> {
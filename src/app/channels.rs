@@ -1,18 +1,80 @@
+use std::path::PathBuf;
+
 use crossbeam::channel::{self, Receiver, Sender};
+use handlegraph::pathhandlegraph::PathId;
 use winit::event::VirtualKeyCode;
 
 use crate::app::mainview::MainViewMsg;
+use crate::app::tour::Tour;
 use crate::app::AppMsg;
+use crate::context::CustomContextAction;
 use crate::gui::GuiMsg;
-use crate::overlays::OverlayData;
+use crate::overlays::{OverlayData, OverlayLegend, OverlayNormalization};
 
 pub type BindMsg = (
     VirtualKeyCode,
     Option<Box<dyn Fn() + Send + Sync + 'static>>,
 );
 
+pub enum SessionMsg {
+    Save(PathBuf),
+    Load(PathBuf),
+}
+
+pub enum ExportMsg {
+    /// Export the current view, or the entire graph if
+    /// `whole_graph` is set, as SVG
+    Svg { path: PathBuf, whole_graph: bool },
+    /// Render the current view to a PNG `width` pixels wide, and
+    /// `height` pixels tall, or with the aspect ratio of the window
+    /// if `height` is `None`
+    Png {
+        path: PathBuf,
+        width: u32,
+        height: Option<u32>,
+    },
+}
+
+pub enum TourMsg {
+    /// Play the tour, writing every frame to a numbered PNG in
+    /// `frame_dir` if it's set
+    Play {
+        tour: Tour,
+        frame_dir: Option<PathBuf>,
+    },
+    Stop,
+}
+
+pub enum PathHighlightMsg {
+    /// Highlight the path, using the next color of the palette if
+    /// `color` is `None`
+    Add {
+        path: PathId,
+        color: Option<rgb::RGBA<f32>>,
+    },
+    Remove(PathId),
+    Clear,
+}
+
 pub enum OverlayCreatorMsg {
-    NewOverlay { name: String, data: OverlayData },
+    NewOverlay {
+        name: String,
+        data: OverlayData,
+        legend: Option<OverlayLegend>,
+    },
+    /// Replaces the data of the overlay with the given name in place,
+    /// or creates a new overlay if there is none
+    ReplaceOverlay {
+        name: String,
+        data: OverlayData,
+        legend: Option<OverlayLegend>,
+    },
+    /// Renormalizes the values of a value overlay, without rerunning
+    /// the script that created it
+    SetNormalization {
+        overlay_id: usize,
+        normalization: OverlayNormalization,
+    },
 }
 
 #[derive(Clone)]
@@ -32,6 +94,21 @@ pub struct AppChannels {
     pub new_overlay_tx: Sender<OverlayCreatorMsg>,
     pub new_overlay_rx: Receiver<OverlayCreatorMsg>,
 
+    pub context_action_tx: Sender<CustomContextAction>,
+    pub context_action_rx: Receiver<CustomContextAction>,
+
+    pub session_tx: Sender<SessionMsg>,
+    pub session_rx: Receiver<SessionMsg>,
+
+    pub tour_tx: Sender<TourMsg>,
+    pub tour_rx: Receiver<TourMsg>,
+
+    pub export_tx: Sender<ExportMsg>,
+    pub export_rx: Receiver<ExportMsg>,
+
+    pub path_highlight_tx: Sender<PathHighlightMsg>,
+    pub path_highlight_rx: Receiver<PathHighlightMsg>,
+
     pub modal_tx: Sender<Box<dyn Fn(&mut egui::Ui) + Send + Sync + 'static>>,
     pub modal_rx: Receiver<Box<dyn Fn(&mut egui::Ui) + Send + Sync + 'static>>,
 }
@@ -44,6 +121,13 @@ impl AppChannels {
         let (binds_tx, binds_rx) = channel::unbounded::<BindMsg>();
         let (new_overlay_tx, new_overlay_rx) =
             channel::unbounded::<OverlayCreatorMsg>();
+        let (context_action_tx, context_action_rx) =
+            channel::unbounded::<CustomContextAction>();
+        let (session_tx, session_rx) = channel::unbounded::<SessionMsg>();
+        let (tour_tx, tour_rx) = channel::unbounded::<TourMsg>();
+        let (export_tx, export_rx) = channel::unbounded::<ExportMsg>();
+        let (path_highlight_tx, path_highlight_rx) =
+            channel::unbounded::<PathHighlightMsg>();
 
         let (modal_tx, modal_rx) = channel::unbounded();
 
@@ -63,6 +147,21 @@ impl AppChannels {
             new_overlay_tx,
             new_overlay_rx,
 
+            context_action_tx,
+            context_action_rx,
+
+            session_tx,
+            session_rx,
+
+            tour_tx,
+            tour_rx,
+
+            export_tx,
+            export_rx,
+
+            path_highlight_tx,
+            path_highlight_rx,
+
             modal_tx,
             modal_rx,
         }
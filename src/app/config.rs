@@ -0,0 +1,283 @@
+//! User-level configuration, stored in `$XDG_CONFIG_HOME/gfaestus/`
+//! (or `~/.config/gfaestus/`), containing the `AppSettings` and the
+//! input bindings of each subsystem.
+//!
+//! The config file is tab-separated, with one entry per line:
+//!
+//! ```text
+//! setting <key>   <value>
+//! key     <subsystem> <input> <key name>  <modifiers>
+//! mouse   <subsystem> <input> <button>    <modifiers>
+//! wheel   <subsystem> <input> <invert>    <multiplier>    <modifiers>
+//! wheel   <subsystem> none
+//! ```
+//!
+//! where the subsystem is one of `app`, `main_view`, and `gui`, and
+//! modifiers are written as e.g. `shift+ctrl`, or `none`. A `none`
+//! wheel entry unbinds the subsystem's default wheel binding. The
+//! bindings listed for an input replace that input's default
+//! bindings; inputs that aren't listed keep their defaults, so
+//! inputs added in later versions are bound even with an older
+//! config file.
+
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use rustc_hash::FxHashSet;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::input::binds::{
+    modifiers_to_string, mouse_button_to_string, parse_modifiers,
+    parse_mouse_button, BindableInput, SystemInputBindings, WheelBind,
+};
+use crate::input::{virtual_key_code_map, InputBindings};
+
+use super::AppSettings;
+
+pub const CONFIG_FILE_NAME: &str = "config";
+
+/// The directory containing the gfaestus config, or `None` if
+/// neither `$XDG_CONFIG_HOME` nor `$HOME` are set
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+
+    Some(base.join("gfaestus"))
+}
+
+pub fn config_file_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+fn write_bindings<T: BindableInput, W: Write>(
+    out: &mut W,
+    subsystem: &str,
+    bindings: &SystemInputBindings<T>,
+) -> Result<()> {
+    for (key, mods, input) in bindings.key_binds() {
+        writeln!(
+            out,
+            "key\t{}\t{}\t{:?}\t{}",
+            subsystem,
+            input.name(),
+            key,
+            modifiers_to_string(mods)
+        )?;
+    }
+
+    for (button, mods, input) in bindings.mouse_binds() {
+        writeln!(
+            out,
+            "mouse\t{}\t{}\t{}\t{}",
+            subsystem,
+            input.name(),
+            mouse_button_to_string(button),
+            modifiers_to_string(mods)
+        )?;
+    }
+
+    if let Some(wheel) = bindings.wheel_bind() {
+        writeln!(
+            out,
+            "wheel\t{}\t{}\t{}\t{}\t{}",
+            subsystem,
+            wheel.payload().name(),
+            wheel.invert(),
+            wheel.mult(),
+            modifiers_to_string(wheel.modifiers())
+        )?;
+    } else if T::default_binds().wheel_bind().is_some() {
+        // otherwise the default would be restored when loading
+        writeln!(out, "wheel\t{}\tnone", subsystem)?;
+    }
+
+    Ok(())
+}
+
+/// Write the settings and bindings to `path`, creating the parent
+/// directory if needed
+pub fn save_config<P: AsRef<Path>>(
+    path: P,
+    settings: &AppSettings,
+    bindings: &InputBindings,
+) -> Result<()> {
+    let path = path.as_ref();
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let file = std::fs::File::create(path)?;
+    let mut out = BufWriter::new(file);
+
+    for (key, value) in settings.to_key_values() {
+        writeln!(out, "setting\t{}\t{}", key, value)?;
+    }
+
+    write_bindings(&mut out, "app", &bindings.app.lock())?;
+    write_bindings(&mut out, "main_view", &bindings.main_view.lock())?;
+    write_bindings(&mut out, "gui", &bindings.gui.lock())?;
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Bindings parsed from the config file for one subsystem, starting
+/// from the defaults; these replace the subsystem's bindings once the
+/// whole file is parsed
+struct ParsedBindings<T: BindableInput> {
+    bindings: SystemInputBindings<T>,
+    // the inputs whose default key and mouse bindings have been
+    // replaced by ones in the file
+    overridden: FxHashSet<T>,
+}
+
+impl<T: BindableInput> ParsedBindings<T> {
+    fn new() -> Self {
+        Self {
+            bindings: T::default_binds(),
+            overridden: FxHashSet::default(),
+        }
+    }
+
+    /// The bindings to add the file's bindings for `input` to; the
+    /// first time an input is seen, its default bindings are removed
+    fn get_mut(&mut self, input: T) -> &mut SystemInputBindings<T> {
+        if self.overridden.insert(input) {
+            self.bindings.clear_input(input);
+        }
+        &mut self.bindings
+    }
+
+    fn parse_line(&mut self, kind: &str, fields: &[&str]) -> Result<()> {
+        if kind == "wheel" && fields == ["none"] {
+            self.bindings.set_wheel_bind(None);
+            return Ok(());
+        }
+
+        let input = match T::from_name(fields[0]) {
+            Some(input) => input,
+            None => bail!("Unknown input `{}`", fields[0]),
+        };
+
+        let mods =
+            |ix: usize| match fields.get(ix).and_then(|m| parse_modifiers(m)) {
+                Some(mods) => Ok(mods),
+                None => Err(anyhow::anyhow!("Error parsing modifiers")),
+            };
+
+        match kind {
+            "key" => {
+                let key_name = fields.get(1).copied().unwrap_or_default();
+                let key = match virtual_key_code_map().get(key_name) {
+                    Some(key) => *key,
+                    None => bail!("Unknown key `{}`", key_name),
+                };
+                self.get_mut(input).bind_key(key, mods(2)?, input);
+            }
+            "mouse" => {
+                let button =
+                    match fields.get(1).and_then(|b| parse_mouse_button(b)) {
+                        Some(button) => button,
+                        None => bail!("Error parsing mouse button"),
+                    };
+                self.get_mut(input).bind_mouse(button, mods(2)?, input);
+            }
+            "wheel" => {
+                let invert = fields.get(1).and_then(|v| v.parse().ok());
+                let mult = fields.get(2).and_then(|v| v.parse().ok());
+
+                if let (Some(invert), Some(mult)) = (invert, mult) {
+                    let wheel = WheelBind::with_modifiers(
+                        invert,
+                        mult,
+                        input,
+                        mods(3)?,
+                    );
+                    self.bindings.set_wheel_bind(Some(wheel));
+                } else {
+                    bail!("Error parsing wheel binding");
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    fn apply(self, target: &Arc<Mutex<SystemInputBindings<T>>>) {
+        *target.lock() = self.bindings;
+    }
+}
+
+/// Load the config at `path`, updating the settings and bindings.
+/// Lines that can't be parsed are skipped with a warning
+pub fn load_config<P: AsRef<Path>>(
+    path: P,
+    settings: &AppSettings,
+    bindings: &InputBindings,
+) -> Result<()> {
+    let path = path.as_ref();
+
+    let file = std::fs::File::open(path)?;
+    let reader = BufReader::new(file);
+
+    let mut app = ParsedBindings::new();
+    let mut main_view = ParsedBindings::new();
+    let mut gui = ParsedBindings::new();
+
+    for (line_ix, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_no = line_ix + 1;
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = line.split('\t').collect::<Vec<_>>();
+
+        let result = match fields.as_slice() {
+            ["setting", key, value] => settings.set_from_str(key, value),
+            [kind @ "key", subsystem, rest @ ..]
+            | [kind @ "mouse", subsystem, rest @ ..]
+            | [kind @ "wheel", subsystem, rest @ ..]
+                if !rest.is_empty() =>
+            {
+                match *subsystem {
+                    "app" => app.parse_line(kind, rest),
+                    "main_view" => main_view.parse_line(kind, rest),
+                    "gui" => gui.parse_line(kind, rest),
+                    _ => Err(anyhow::anyhow!(
+                        "Unknown subsystem `{}`",
+                        subsystem
+                    )),
+                }
+            }
+            _ => Err(anyhow::anyhow!("Unknown config entry")),
+        };
+
+        if let Err(err) = result {
+            warn!(
+                "Skipping line {} of config file {:?}: {}",
+                line_no, path, err
+            );
+        }
+    }
+
+    app.apply(&bindings.app);
+    main_view.apply(&bindings.main_view);
+    gui.apply(&bindings.gui);
+
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use handlegraph::{
+    handle::{Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::graph_query::GraphQuery;
+
+/// The nodes are traversed in the same orientation by both paths
+pub const DOT_FORWARD: u8 = 1;
+/// The nodes are traversed in opposite orientations
+pub const DOT_INVERTED: u8 = 2;
+
+/// The largest number of dots created for a single node. A node
+/// visited `m` times by one path and `n` times by the other has `m * n`
+/// pairs of steps, which are evenly subsampled down to this many
+pub const MAX_DOTS_PER_NODE: usize = 4096;
+
+/// A node shared by the two paths of a dotplot, at one pair of their
+/// steps on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dot {
+    pub pos_a: usize,
+    pub pos_b: usize,
+    pub len: usize,
+    pub node: NodeId,
+    /// The paths traverse the node in opposite orientations
+    pub inverted: bool,
+}
+
+/// The positions of the nodes shared by two paths, in the path
+/// coordinates of each
+#[derive(Debug, Clone)]
+pub struct Dotplot {
+    pub path_a: (PathId, String),
+    pub path_b: (PathId, String),
+
+    pub len_a: usize,
+    pub len_b: usize,
+
+    pub dots: Vec<Dot>,
+
+    /// The number of nodes with more than `MAX_DOTS_PER_NODE` pairs
+    /// of steps, which only some of the pairs are plotted for
+    pub subsampled_nodes: usize,
+}
+
+impl Dotplot {
+    pub fn new(
+        graph_query: &GraphQuery,
+        path_a: PathId,
+        path_b: PathId,
+    ) -> Option<Self> {
+        let graph = graph_query.graph();
+
+        let name_a = graph.get_path_name_vec(path_a)?;
+        let name_b = graph.get_path_name_vec(path_b)?;
+
+        let steps_a = graph_query.path_pos_steps(path_a)?;
+        let steps_b = graph_query.path_pos_steps(path_b)?;
+
+        let len_a = graph.path_bases_len(path_a)?;
+        let len_b = graph.path_bases_len(path_b)?;
+
+        let mut a_positions: FxHashMap<NodeId, Vec<(usize, bool)>> =
+            FxHashMap::default();
+        let mut b_positions: FxHashMap<NodeId, Vec<(usize, bool)>> =
+            FxHashMap::default();
+
+        for (handle, _, pos) in steps_a {
+            a_positions
+                .entry(handle.id())
+                .or_default()
+                .push((pos, handle.is_reverse()));
+        }
+
+        for (handle, _, pos) in steps_b {
+            b_positions
+                .entry(handle.id())
+                .or_default()
+                .push((pos, handle.is_reverse()));
+        }
+
+        let mut dots = Vec::new();
+        let mut subsampled_nodes = 0;
+
+        for (&id, positions_a) in a_positions.iter() {
+            let positions_b = match b_positions.get(&id) {
+                Some(positions) => positions,
+                None => continue,
+            };
+
+            let len = graph.node_len(Handle::pack(id, false));
+
+            // every pair of steps if there are few enough, otherwise
+            // every `stride`th pair, to keep repeats from blowing up
+            let pairs = positions_a.len() * positions_b.len();
+            let stride = (pairs + MAX_DOTS_PER_NODE - 1) / MAX_DOTS_PER_NODE;
+
+            if stride > 1 {
+                subsampled_nodes += 1;
+            }
+
+            for ix in (0..pairs).step_by(stride) {
+                let (pos_a, rev_a) = positions_a[ix / positions_b.len()];
+                let (pos_b, rev_b) = positions_b[ix % positions_b.len()];
+
+                dots.push(Dot {
+                    pos_a,
+                    pos_b,
+                    len,
+                    node: id,
+                    inverted: rev_a != rev_b,
+                });
+            }
+        }
+
+        if subsampled_nodes > 0 {
+            warn!(
+                "Dotplot: {} nodes have more than {} pairs of steps, \
+                 only some of them are plotted",
+                subsampled_nodes, MAX_DOTS_PER_NODE
+            );
+        }
+
+        Some(Self {
+            path_a: (path_a, name_a.to_str_lossy().into_owned()),
+            path_b: (path_b, name_b.to_str_lossy().into_owned()),
+            len_a,
+            len_b,
+            dots,
+            subsampled_nodes,
+        })
+    }
+
+    /// Downsamples the dots to a `res` by `res` grid, with path A
+    /// along the columns and path B along the rows, returning the
+    /// DOT_FORWARD and DOT_INVERTED flags of each cell, row-major
+    pub fn grid(&self, res: usize) -> Vec<u8> {
+        let mut cells = vec![0u8; res * res];
+
+        if res == 0 || self.len_a == 0 || self.len_b == 0 {
+            return cells;
+        }
+
+        let bp_per_col = self.len_a as f64 / res as f64;
+        let bp_per_row = self.len_b as f64 / res as f64;
+
+        let cell = |pos: f64, bp_per_cell: f64| {
+            ((pos / bp_per_cell) as usize).min(res - 1)
+        };
+
+        for dot in self.dots.iter() {
+            let flag = if dot.inverted {
+                DOT_INVERTED
+            } else {
+                DOT_FORWARD
+            };
+
+            // sample the diagonal of the node often enough to touch
+            // every cell it crosses
+            let cells_spanned = (dot.len as f64 / bp_per_col)
+                .max(dot.len as f64 / bp_per_row)
+                .ceil() as usize;
+            let samples = cells_spanned.max(1);
+
+            let last = dot.len.saturating_sub(1) as f64;
+
+            for s in 0..=samples {
+                let t = last * s as f64 / samples as f64;
+
+                let a = dot.pos_a as f64 + t;
+                let b = if dot.inverted {
+                    dot.pos_b as f64 + last - t
+                } else {
+                    dot.pos_b as f64 + t
+                };
+
+                let col = cell(a, bp_per_col);
+                let row = cell(b, bp_per_row);
+
+                cells[row * res + col] |= flag;
+            }
+        }
+
+        cells
+    }
+
+    /// The nodes with a dot in the given ranges of the two paths
+    pub fn nodes_in(
+        &self,
+        range_a: (usize, usize),
+        range_b: (usize, usize),
+    ) -> FxHashSet<NodeId> {
+        let overlaps =
+            |pos: usize, len: usize, (start, end): (usize, usize)| {
+                pos < end && pos + len > start
+            };
+
+        self.dots
+            .iter()
+            .filter(|dot| {
+                overlaps(dot.pos_a, dot.len, range_a)
+                    && overlaps(dot.pos_b, dot.len, range_b)
+            })
+            .map(|dot| dot.node)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graph_query::test_graph;
+
+    fn fwd(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), false)
+    }
+
+    fn rev(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), true)
+    }
+
+    fn dotplot(path_a: &[Handle], path_b: &[Handle]) -> Dotplot {
+        let graph = test_graph(
+            &[(1, "ACGT")],
+            &[(fwd(1), fwd(1))],
+            &[("a", path_a), ("b", path_b)],
+        );
+
+        let path_a = graph.graph().get_path_id(b"a").unwrap();
+        let path_b = graph.graph().get_path_id(b"b").unwrap();
+
+        Dotplot::new(&graph, path_a, path_b).unwrap()
+    }
+
+    #[test]
+    fn repeated_node_has_a_dot_per_pair_of_steps() {
+        let plot = dotplot(&[fwd(1), fwd(1), fwd(1)], &[rev(1), rev(1)]);
+
+        assert_eq!(plot.subsampled_nodes, 0);
+        assert_eq!(plot.dots.len(), 6);
+        assert!(plot.dots.iter().all(|dot| dot.inverted && dot.len == 4));
+
+        let mut positions = plot
+            .dots
+            .iter()
+            .map(|dot| (dot.pos_a, dot.pos_b))
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
+        assert_eq!(
+            positions,
+            vec![(0, 0), (0, 4), (4, 0), (4, 4), (8, 0), (8, 4)]
+        );
+    }
+
+    #[test]
+    fn repeated_node_pairs_are_subsampled_by_stride() {
+        // 65 * 65 pairs, just over the limit, so every other pair
+        let steps = vec![fwd(1); 65];
+        let plot = dotplot(&steps, &steps);
+
+        assert_eq!(plot.subsampled_nodes, 1);
+        assert_eq!(plot.dots.len(), (65 * 65 + 1) / 2);
+        assert!(plot.dots.len() <= MAX_DOTS_PER_NODE);
+
+        // the first and last pairs are both kept
+        let first = plot.dots.first().unwrap();
+        let last = plot.dots.last().unwrap();
+        assert_eq!((first.pos_a, first.pos_b), (0, 0));
+        assert_eq!((last.pos_a, last.pos_b), (64 * 4, 64 * 4));
+    }
+
+    fn grid_plot(dots: Vec<Dot>) -> Dotplot {
+        Dotplot {
+            path_a: (PathId(0), "a".to_string()),
+            path_b: (PathId(1), "b".to_string()),
+            len_a: 40,
+            len_b: 40,
+            dots,
+            subsampled_nodes: 0,
+        }
+    }
+
+    fn dot(inverted: bool) -> Dot {
+        Dot {
+            pos_a: 0,
+            pos_b: 0,
+            len: 40,
+            node: NodeId::from(1),
+            inverted,
+        }
+    }
+
+    #[test]
+    fn forward_dots_fill_the_diagonal() {
+        let cells = grid_plot(vec![dot(false)]).grid(4);
+
+        for row in 0..4 {
+            for col in 0..4 {
+                let expected = if row == col { DOT_FORWARD } else { 0 };
+                assert_eq!(cells[row * 4 + col], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn inverted_dots_fill_the_antidiagonal() {
+        let cells = grid_plot(vec![dot(true)]).grid(4);
+
+        // the node runs from the bottom left to the top right
+        assert_eq!(cells[3 * 4], DOT_INVERTED);
+        assert_eq!(cells[3], DOT_INVERTED);
+
+        assert_eq!(cells[0], 0);
+        assert_eq!(cells[3 * 4 + 3], 0);
+
+        for row in 0..4 {
+            for col in 0..4 {
+                let flags = cells[row * 4 + col];
+                assert!(flags == 0 || flags == DOT_INVERTED);
+
+                // only cells on or next to the antidiagonal are set
+                if flags != 0 {
+                    assert!(((row + col) as isize - 3).abs() <= 1);
+                }
+            }
+        }
+
+        let both = grid_plot(vec![dot(false), dot(true)]).grid(4);
+        assert_eq!(both[4 + 1], DOT_FORWARD | DOT_INVERTED);
+    }
+}
@@ -60,6 +60,10 @@ pub struct MainView {
 #[derive(Debug, Clone, Copy)]
 pub enum MainViewMsg {
     GotoView(View),
+    /// Go to the previous view in the navigation history
+    GoBack,
+    /// Go to the next view in the navigation history
+    GoForward,
 }
 
 impl MainView {
@@ -129,20 +133,37 @@ impl MainView {
         &self.channels.main_view_rx
     }
 
+    fn animate_to_view(&self, view: View) {
+        use std::time::Duration;
+
+        let anim_def = AnimationDef {
+            kind: AnimationKind::Absolute,
+            order: AnimationOrder::Transform {
+                center: view.center,
+                scale: view.scale,
+            },
+            duration: Duration::from_millis(500),
+        };
+        self.anim_handler.send_anim_def(anim_def);
+    }
+
     pub fn apply_msg(&self, msg: MainViewMsg) {
+        let mut history = self.shared_state.view_history.lock();
+
         match msg {
             MainViewMsg::GotoView(view) => {
-                use std::time::Duration;
-
-                let anim_def = AnimationDef {
-                    kind: AnimationKind::Absolute,
-                    order: AnimationOrder::Transform {
-                        center: view.center,
-                        scale: view.scale,
-                    },
-                    duration: Duration::from_millis(500),
-                };
-                self.anim_handler.send_anim_def(anim_def);
+                history.push(self.view());
+                self.animate_to_view(view);
+            }
+            MainViewMsg::GoBack => {
+                if let Some(view) = history.go_back(self.view()) {
+                    self.animate_to_view(view);
+                }
+            }
+            MainViewMsg::GoForward => {
+                if let Some(view) = history.go_forward(self.view()) {
+                    self.animate_to_view(view);
+                }
             }
         }
     }
@@ -215,29 +236,35 @@ impl MainView {
     ) -> Result<()> {
         let view = self.shared_state.view();
 
-        let node_width = {
-            let min = self.node_width.min_node_width();
-            let max = self.node_width.max_node_width();
-
-            let min_scale = self.node_width.min_node_scale();
-            let max_scale = self.node_width.max_node_scale();
-
-            let norm_scale = (view.scale - min_scale) / (max_scale - min_scale);
+        let node_width = self.node_width.width_at_scale(view.scale);
 
-            let easing_val =
-                EasingExpoOut::value_at_normalized_time(norm_scale as f64)
-                    as f32;
-
-            let mut width = min + easing_val * (max - min);
-
-            if view.scale > max_scale {
-                width *= view.scale / (min_scale - max_scale);
-            } else if view.scale < min_scale {
-                width = min
-            }
-            width
-        };
+        self.draw_nodes_with_view(
+            cmd_buf,
+            render_pass,
+            framebuffers,
+            screen_dims,
+            view,
+            node_width,
+            offset,
+            overlay_id,
+            color_scheme,
+        )
+    }
 
+    /// Draw the nodes as seen from `view` rather than the current
+    /// view, e.g. when rendering the current view in tiles
+    pub fn draw_nodes_with_view(
+        &mut self,
+        cmd_buf: vk::CommandBuffer,
+        render_pass: vk::RenderPass,
+        framebuffers: &Framebuffers,
+        screen_dims: [f32; 2],
+        view: View,
+        node_width: f32,
+        offset: Point,
+        overlay_id: Option<usize>,
+        color_scheme: &GradientTexture,
+    ) -> Result<()> {
         let background_color = if self.shared_state.dark_mode.load() {
             self.settings.background_color_dark().load()
         } else {
@@ -367,6 +394,20 @@ impl MainView {
                             self.reset_view();
                         }
                     }
+                    In::KeyViewBack => {
+                        if pressed {
+                            self.main_view_msg_tx()
+                                .send(MainViewMsg::GoBack)
+                                .unwrap();
+                        }
+                    }
+                    In::KeyViewForward => {
+                        if pressed {
+                            self.main_view_msg_tx()
+                                .send(MainViewMsg::GoForward)
+                                .unwrap();
+                        }
+                    }
                     _ => (),
                 }
             }
@@ -479,16 +520,35 @@ pub enum MainViewInput {
     KeyPanDown,
     KeyPanLeft,
     KeyResetView,
+    KeyViewBack,
+    KeyViewForward,
     WheelZoom,
 }
 
 impl BindableInput for MainViewInput {
+    fn all_inputs() -> Vec<Self> {
+        use MainViewInput as Input;
+        vec![
+            Input::ButtonMousePan,
+            Input::ButtonSelect,
+            Input::ButtonRectangleSelect,
+            Input::KeyPanUp,
+            Input::KeyPanRight,
+            Input::KeyPanDown,
+            Input::KeyPanLeft,
+            Input::KeyResetView,
+            Input::KeyViewBack,
+            Input::KeyViewForward,
+            Input::WheelZoom,
+        ]
+    }
+
     fn default_binds() -> SystemInputBindings<Self> {
         use winit::event;
         use winit::event::VirtualKeyCode as Key;
         use MainViewInput as Input;
 
-        let key_binds: FxHashMap<Key, Vec<KeyBind<Input>>> = [
+        let mut key_binds: FxHashMap<Key, Vec<KeyBind<Input>>> = [
             (Key::Up, Input::KeyPanUp),
             (Key::Down, Input::KeyPanDown),
             (Key::Left, Input::KeyPanLeft),
@@ -501,6 +561,17 @@ impl BindableInput for MainViewInput {
         .collect::<FxHashMap<_, _>>();
 
         let shift_mod = winit::event::ModifiersState::SHIFT;
+        let alt_mod = winit::event::ModifiersState::ALT;
+
+        // browser-style history navigation with alt+left/right
+        key_binds
+            .entry(Key::Left)
+            .or_default()
+            .push(KeyBind::with_modifiers(Input::KeyViewBack, alt_mod));
+        key_binds
+            .entry(Key::Right)
+            .or_default()
+            .push(KeyBind::with_modifiers(Input::KeyViewForward, alt_mod));
 
         let mouse_binds: FxHashMap<
             event::MouseButton,
@@ -0,0 +1,69 @@
+use crate::view::View;
+
+/// The maximum number of views kept in the back history
+const HISTORY_LEN: usize = 100;
+
+/// Browser-style back/forward history over the views that were
+/// visited using `MainViewMsg::GotoView`
+#[derive(Debug, Default, Clone)]
+pub struct ViewHistory {
+    back: Vec<View>,
+    forward: Vec<View>,
+}
+
+impl ViewHistory {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Record that the view is leaving `from`; this clears the
+    /// forward history
+    pub fn push(&mut self, from: View) {
+        if self.back.last() != Some(&from) {
+            self.back.push(from);
+        }
+
+        if self.back.len() > HISTORY_LEN {
+            self.back.remove(0);
+        }
+
+        self.forward.clear();
+    }
+
+    /// Returns the view to go back to, if any, storing `current` in
+    /// the forward history
+    pub fn go_back(&mut self, current: View) -> Option<View> {
+        let view = self.back.pop()?;
+        self.forward.push(current);
+        Some(view)
+    }
+
+    /// Returns the view to go forward to, if any, storing `current`
+    /// in the back history
+    pub fn go_forward(&mut self, current: View) -> Option<View> {
+        let view = self.forward.pop()?;
+        self.back.push(current);
+        Some(view)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub name: String,
+    pub note: String,
+    pub view: View,
+}
+
+impl Bookmark {
+    pub fn new(name: &str, note: &str, view: View) -> Self {
+        Self {
+            name: name.to_string(),
+            note: note.to_string(),
+            view,
+        }
+    }
+}
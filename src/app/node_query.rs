@@ -0,0 +1,498 @@
+use handlegraph::{
+    handle::{Direction, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use anyhow::{anyhow, bail, Result};
+
+use std::io::Write;
+
+use crate::graph_query::GraphQuery;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeField {
+    Id,
+    Length,
+    Degree,
+    /// The number of path steps on the node
+    Coverage,
+    /// The number of distinct paths visiting the node
+    PathCount,
+}
+
+impl NodeField {
+    pub const ALL: [NodeField; 5] = [
+        NodeField::Id,
+        NodeField::Length,
+        NodeField::Degree,
+        NodeField::Coverage,
+        NodeField::PathCount,
+    ];
+
+    /// The name used for the field in query expressions
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeField::Id => "id",
+            NodeField::Length => "len",
+            NodeField::Degree => "degree",
+            NodeField::Coverage => "coverage",
+            NodeField::PathCount => "paths",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "id" | "node" => Some(NodeField::Id),
+            "len" | "length" => Some(NodeField::Length),
+            "degree" => Some(NodeField::Degree),
+            "coverage" | "depth" => Some(NodeField::Coverage),
+            "paths" => Some(NodeField::PathCount),
+            _ => None,
+        }
+    }
+}
+
+/// The values of each `NodeField` for every node in the graph,
+/// indexed by node ID minus one
+#[derive(Debug, Clone)]
+pub struct NodeColumns {
+    lengths: Vec<usize>,
+    degrees: Vec<usize>,
+    coverage: Vec<usize>,
+    path_counts: Vec<usize>,
+}
+
+impl NodeColumns {
+    pub fn new(graph_query: &GraphQuery) -> Self {
+        let graph = graph_query.graph();
+
+        let mut handles = graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let node_count = handles.len();
+
+        let mut lengths = Vec::with_capacity(node_count);
+        let mut degrees = Vec::with_capacity(node_count);
+        let mut coverage = Vec::with_capacity(node_count);
+        let mut path_counts = Vec::with_capacity(node_count);
+
+        let mut paths: Vec<PathId> = Vec::new();
+
+        for handle in handles {
+            lengths.push(graph.node_len(handle));
+
+            degrees.push(
+                graph.degree(handle, Direction::Left)
+                    + graph.degree(handle, Direction::Right),
+            );
+
+            paths.clear();
+
+            if let Some(steps) = graph.steps_on_handle(handle) {
+                paths.extend(steps.map(|(path, _)| path));
+            }
+
+            coverage.push(paths.len());
+
+            paths.sort();
+            paths.dedup();
+
+            path_counts.push(paths.len());
+        }
+
+        Self {
+            lengths,
+            degrees,
+            coverage,
+            path_counts,
+        }
+    }
+
+    pub fn value(&self, field: NodeField, node: NodeId) -> usize {
+        let ix = (node.0 - 1) as usize;
+
+        match field {
+            NodeField::Id => node.0 as usize,
+            NodeField::Length => self.lengths[ix],
+            NodeField::Degree => self.degrees[ix],
+            NodeField::Coverage => self.coverage[ix],
+            NodeField::PathCount => self.path_counts[ix],
+        }
+    }
+
+    /// Sorts the nodes by the field, ties broken by node ID
+    pub fn sort(
+        &self,
+        nodes: &mut [NodeId],
+        field: NodeField,
+        descending: bool,
+    ) {
+        nodes.sort_by(|&a, &b| {
+            let ord = self.value(field, a).cmp(&self.value(field, b));
+            let ord = if descending { ord.reverse() } else { ord };
+            ord.then(a.cmp(&b))
+        });
+    }
+
+    /// Writes the nodes and their fields as a TSV with a header row
+    pub fn write_tsv<W: Write>(
+        &self,
+        nodes: &[NodeId],
+        mut out: W,
+    ) -> std::io::Result<()> {
+        let header = NodeField::ALL
+            .iter()
+            .map(|field| field.name())
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", header.join("\t"))?;
+
+        for &node in nodes {
+            let row = NodeField::ALL
+                .iter()
+                .map(|&field| self.value(field, node).to_string())
+                .collect::<Vec<_>>();
+            writeln!(out, "{}", row.join("\t"))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn apply(&self, lhs: usize, rhs: usize) -> bool {
+        match self {
+            CmpOp::Lt => lhs < rhs,
+            CmpOp::Le => lhs <= rhs,
+            CmpOp::Gt => lhs > rhs,
+            CmpOp::Ge => lhs >= rhs,
+            CmpOp::Eq => lhs == rhs,
+            CmpOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Field(NodeField),
+    Number(usize),
+    Cmp(CmpOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+
+    let mut tokens = Vec::new();
+    let mut ix = 0;
+
+    while ix < chars.len() {
+        let c = chars[ix];
+        let next = chars.get(ix + 1).copied();
+
+        if c.is_whitespace() {
+            ix += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let start = ix;
+            while ix < chars.len() && chars[ix].is_ascii_digit() {
+                ix += 1;
+            }
+            let number = chars[start..ix].iter().collect::<String>();
+            tokens.push(Token::Number(number.parse()?));
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() {
+            let start = ix;
+            while ix < chars.len()
+                && (chars[ix].is_ascii_alphanumeric() || chars[ix] == '_')
+            {
+                ix += 1;
+            }
+            let word = chars[start..ix].iter().collect::<String>();
+
+            let token = match word.as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                _ => Token::Field(
+                    NodeField::parse(&word)
+                        .ok_or(anyhow!("Unknown field `{}`", word))?,
+                ),
+            };
+            tokens.push(token);
+            continue;
+        }
+
+        let (token, len) = match (c, next) {
+            ('<', Some('=')) => (Token::Cmp(CmpOp::Le), 2),
+            ('>', Some('=')) => (Token::Cmp(CmpOp::Ge), 2),
+            ('=', Some('=')) => (Token::Cmp(CmpOp::Eq), 2),
+            ('!', Some('=')) => (Token::Cmp(CmpOp::Ne), 2),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('<', _) => (Token::Cmp(CmpOp::Lt), 1),
+            ('>', _) => (Token::Cmp(CmpOp::Gt), 1),
+            ('=', _) => (Token::Cmp(CmpOp::Eq), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            _ => bail!("Unexpected character `{}`", c),
+        };
+
+        tokens.push(token);
+        ix += len;
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Cmp(NodeField, CmpOp, usize),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Result<Expr> {
+        let mut expr = self.and_expr()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let rhs = self.and_expr()?;
+            expr = Expr::Or(Box::new(expr), Box::new(rhs));
+        }
+
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let rhs = self.term()?;
+            expr = Expr::And(Box::new(expr), Box::new(rhs));
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.term()?))),
+            Some(Token::Open) => {
+                let expr = self.or_expr()?;
+                if self.next() != Some(Token::Close) {
+                    bail!("Expected `)`");
+                }
+                Ok(expr)
+            }
+            Some(Token::Field(field)) => {
+                let op = match self.next() {
+                    Some(Token::Cmp(op)) => op,
+                    _ => {
+                        bail!("Expected a comparison after `{}`", field.name())
+                    }
+                };
+                let value = match self.next() {
+                    Some(Token::Number(value)) => value,
+                    _ => bail!("Expected a number after the comparison"),
+                };
+                Ok(Expr::Cmp(field, op, value))
+            }
+            Some(token) => bail!("Unexpected {:?}", token),
+            None => bail!("Unexpected end of query"),
+        }
+    }
+}
+
+/// A filter on node fields, parsed from expressions like
+/// `len > 1000 && coverage < 3`. Comparisons can be combined with
+/// `&&`, `||`, `!` (or `and`, `or`, `not`) and parentheses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeQuery {
+    expr: Expr,
+}
+
+impl NodeQuery {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+
+        let mut parser = Parser { tokens, pos: 0 };
+
+        let expr = parser.or_expr()?;
+
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {:?}", token);
+        }
+
+        Ok(Self { expr })
+    }
+
+    pub fn matches(&self, columns: &NodeColumns, node: NodeId) -> bool {
+        Self::eval(&self.expr, columns, node)
+    }
+
+    fn eval(expr: &Expr, columns: &NodeColumns, node: NodeId) -> bool {
+        match expr {
+            Expr::Cmp(field, op, value) => {
+                op.apply(columns.value(*field, node), *value)
+            }
+            Expr::And(a, b) => {
+                Self::eval(a, columns, node) && Self::eval(b, columns, node)
+            }
+            Expr::Or(a, b) => {
+                Self::eval(a, columns, node) || Self::eval(b, columns, node)
+            }
+            Expr::Not(a) => !Self::eval(a, columns, node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Expr {
+        NodeQuery::parse(input).unwrap().expr
+    }
+
+    fn cmp(field: NodeField, op: CmpOp, value: usize) -> Box<Expr> {
+        Box::new(Expr::Cmp(field, op, value))
+    }
+
+    fn parse_error(input: &str) -> String {
+        NodeQuery::parse(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expected = Expr::Or(
+            cmp(NodeField::Id, CmpOp::Eq, 1),
+            Box::new(Expr::And(
+                cmp(NodeField::Length, CmpOp::Gt, 10),
+                cmp(NodeField::Degree, CmpOp::Lt, 3),
+            )),
+        );
+
+        assert_eq!(parse("id == 1 || len > 10 && degree < 3"), expected);
+        assert_eq!(parse("id == 1 or len > 10 and degree < 3"), expected);
+    }
+
+    #[test]
+    fn parentheses_group() {
+        let expected = Expr::And(
+            Box::new(Expr::Or(
+                cmp(NodeField::Id, CmpOp::Eq, 1),
+                cmp(NodeField::Length, CmpOp::Gt, 10),
+            )),
+            cmp(NodeField::Degree, CmpOp::Lt, 3),
+        );
+
+        assert_eq!(parse("(id == 1 || len > 10) && degree < 3"), expected);
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term() {
+        let expected = Expr::And(
+            Box::new(Expr::Not(cmp(NodeField::PathCount, CmpOp::Ge, 2))),
+            cmp(NodeField::Coverage, CmpOp::Ne, 0),
+        );
+
+        assert_eq!(parse("!paths >= 2 && coverage != 0"), expected);
+        assert_eq!(parse("not paths >= 2 and coverage != 0"), expected);
+
+        assert_eq!(
+            parse("!(len <= 5 || len > 100)"),
+            Expr::Not(Box::new(Expr::Or(
+                cmp(NodeField::Length, CmpOp::Le, 5),
+                cmp(NodeField::Length, CmpOp::Gt, 100),
+            )))
+        );
+    }
+
+    #[test]
+    fn field_and_comparison_aliases() {
+        assert_eq!(parse("length > 5"), parse("len > 5"));
+        assert_eq!(parse("depth > 5"), parse("coverage > 5"));
+        assert_eq!(parse("node = 5"), parse("id == 5"));
+        assert_eq!(parse_error("sizes > 1"), "Unknown field `sizes`");
+        assert_eq!(parse_error("((len > 5)"), "Expected `)`");
+    }
+
+    #[test]
+    fn invalid_queries_are_errors() {
+        assert_eq!(parse_error("size > 5"), "Unknown field `size`");
+        assert_eq!(
+            parse_error("len >"),
+            "Expected a number after the comparison"
+        );
+        assert_eq!(
+            parse_error("len > degree"),
+            "Expected a number after the comparison"
+        );
+        assert_eq!(parse_error("len > 5 6"), "Unexpected Number(6)");
+        assert_eq!(parse_error("len > 5 )"), "Unexpected Close");
+        assert_eq!(parse_error("(len > 5"), "Expected `)`");
+        assert_eq!(parse_error("(len > 5 || id < 3"), "Expected `)`");
+        assert_eq!(parse_error("len 5"), "Expected a comparison after `len`");
+        assert_eq!(parse_error(""), "Unexpected end of query");
+    }
+
+    #[test]
+    fn queries_match_node_fields() {
+        let columns = NodeColumns {
+            lengths: vec![1, 50, 200],
+            degrees: vec![2, 4, 1],
+            coverage: vec![0, 3, 6],
+            path_counts: vec![0, 2, 3],
+        };
+
+        let matching = |query: &str| {
+            let query = NodeQuery::parse(query).unwrap();
+            (1..=3u64)
+                .filter(|&id| query.matches(&columns, NodeId::from(id)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching("len > 10"), vec![2, 3]);
+        assert_eq!(matching("len > 10 && degree < 2"), vec![3]);
+        assert_eq!(matching("id == 1 || len > 10 && degree < 2"), vec![1, 3]);
+        assert_eq!(matching("!(coverage >= 3)"), vec![1]);
+    }
+}
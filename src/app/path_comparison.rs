@@ -0,0 +1,506 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use handlegraph::{
+    handle::{Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use anyhow::{bail, Result};
+use bstr::ByteSlice;
+use rustc_hash::FxHashMap;
+
+use crate::app::path_highlight::path_highlight_color;
+use crate::graph_query::GraphQuery;
+use crate::overlays::{hash_node_color, OverlayData, OverlayLegend};
+
+/// The most paths that can be compared at once, one bit of the
+/// coverage masks per path
+pub const MAX_COMPARED_PATHS: usize = 64;
+
+const UNCOVERED_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+    r: 0.3,
+    g: 0.3,
+    b: 0.3,
+    a: 0.3,
+};
+
+const SHARED_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+    r: 0.95,
+    g: 0.75,
+    b: 0.2,
+    a: 1.0,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    /// Covered by all the compared paths
+    Shared,
+    /// Covered only by the path of the segment
+    Private,
+    /// Covered by some, but not all, of the other paths
+    Partial,
+}
+
+impl SegmentKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SegmentKind::Shared => "shared",
+            SegmentKind::Private => "private",
+            SegmentKind::Partial => "partial",
+        }
+    }
+}
+
+/// A range of consecutive steps of a path whose nodes are all of the
+/// same kind, in path coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathSegment {
+    /// The index of the path among the compared paths
+    pub path_ix: usize,
+    pub start: usize,
+    pub end: usize,
+    pub kind: SegmentKind,
+}
+
+/// The Jaccard similarity of two sets of nodes, counted in nodes
+/// and in bases
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Jaccard {
+    pub nodes: f64,
+    pub bases: f64,
+}
+
+/// The nodes shared by, and private to, a set of paths
+#[derive(Debug, Clone)]
+pub struct PathComparison {
+    pub paths: Vec<(PathId, String)>,
+
+    /// The similarity of all the compared paths, i.e. the nodes
+    /// covered by all of them over the nodes covered by any of them
+    pub jaccard: Jaccard,
+
+    // the similarity of each pair of paths, indexed by
+    // `i * paths.len() + j` with `i < j`
+    pairwise: Vec<Jaccard>,
+
+    /// The segments of each path, in path order
+    pub segments: Vec<PathSegment>,
+
+    // the paths covering each node, as bitmasks of path indices
+    coverage: FxHashMap<NodeId, u64>,
+}
+
+impl PathComparison {
+    pub fn new(graph_query: &GraphQuery, paths: &[PathId]) -> Result<Self> {
+        let graph = graph_query.graph();
+
+        if paths.len() > MAX_COMPARED_PATHS {
+            bail!(
+                "Can't compare {} paths, at most {} can be compared at once",
+                paths.len(),
+                MAX_COMPARED_PATHS
+            );
+        }
+
+        let paths = paths
+            .iter()
+            .filter_map(|&path| {
+                let name = graph.get_path_name_vec(path)?;
+                Some((path, name.to_str_lossy().into_owned()))
+            })
+            .collect::<Vec<_>>();
+
+        let path_bits = paths
+            .iter()
+            .enumerate()
+            .map(|(ix, (path, _))| (*path, 1u64 << ix))
+            .collect::<FxHashMap<_, _>>();
+
+        let all_mask = path_bits.values().fold(0u64, |acc, bit| acc | bit);
+
+        let path_steps = paths
+            .iter()
+            .map(|(path, _)| {
+                graph_query.path_pos_steps(*path).unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut coverage: FxHashMap<NodeId, u64> = FxHashMap::default();
+
+        for (handle, _, _) in path_steps.iter().flatten() {
+            let id = handle.id();
+
+            if coverage.contains_key(&id) {
+                continue;
+            }
+
+            let mask = graph
+                .steps_on_handle(*handle)
+                .map(|steps| {
+                    steps.fold(0u64, |acc, (path, _)| {
+                        acc | path_bits.get(&path).copied().unwrap_or(0)
+                    })
+                })
+                .unwrap_or(0);
+
+            coverage.insert(id, mask);
+        }
+
+        let path_count = paths.len();
+
+        let mut intersection = (0usize, 0usize);
+        let mut union = (0usize, 0usize);
+
+        let mut pair_inter = vec![(0usize, 0usize); path_count * path_count];
+        let mut pair_union = vec![(0usize, 0usize); path_count * path_count];
+
+        for (&id, &mask) in coverage.iter() {
+            let len = graph.node_len(Handle::pack(id, false));
+
+            union.0 += 1;
+            union.1 += len;
+
+            if mask == all_mask {
+                intersection.0 += 1;
+                intersection.1 += len;
+            }
+
+            for i in 0..path_count {
+                for j in (i + 1)..path_count {
+                    let a = mask & (1 << i) != 0;
+                    let b = mask & (1 << j) != 0;
+
+                    let ix = i * path_count + j;
+
+                    if a && b {
+                        pair_inter[ix].0 += 1;
+                        pair_inter[ix].1 += len;
+                    }
+
+                    if a || b {
+                        pair_union[ix].0 += 1;
+                        pair_union[ix].1 += len;
+                    }
+                }
+            }
+        }
+
+        let all = jaccard(intersection, union);
+
+        let pairwise = pair_inter
+            .into_iter()
+            .zip(pair_union.into_iter())
+            .map(|(inter, union)| jaccard(inter, union))
+            .collect();
+
+        let mut segments: Vec<PathSegment> = Vec::new();
+
+        for (path_ix, steps) in path_steps.iter().enumerate() {
+            let bit = 1u64 << path_ix;
+
+            let mut current: Option<PathSegment> = None;
+
+            for (handle, _, pos) in steps.iter() {
+                let mask = coverage.get(&handle.id()).copied().unwrap_or(bit);
+
+                let kind = if mask == all_mask {
+                    SegmentKind::Shared
+                } else if mask == bit {
+                    SegmentKind::Private
+                } else {
+                    SegmentKind::Partial
+                };
+
+                let end = pos + graph.node_len(*handle);
+
+                if let Some(segment) =
+                    current.as_mut().filter(|segment| segment.kind == kind)
+                {
+                    segment.end = end;
+                    continue;
+                }
+
+                segments.extend(current.take());
+                current = Some(PathSegment {
+                    path_ix,
+                    start: *pos,
+                    end,
+                    kind,
+                });
+            }
+
+            segments.extend(current);
+        }
+
+        Ok(Self {
+            paths,
+            jaccard: all,
+            pairwise,
+            segments,
+            coverage,
+        })
+    }
+
+    /// The similarity of the `i`th and `j`th compared paths
+    pub fn pair(&self, i: usize, j: usize) -> Jaccard {
+        if i == j {
+            return Jaccard {
+                nodes: 1.0,
+                bases: 1.0,
+            };
+        }
+
+        let (i, j) = (i.min(j), i.max(j));
+        self.pairwise[i * self.paths.len() + j]
+    }
+
+    /// The segments where the paths diverge, i.e. that aren't shared
+    /// by all the compared paths
+    pub fn divergent_segments(
+        &self,
+    ) -> impl Iterator<Item = &PathSegment> + '_ {
+        self.segments
+            .iter()
+            .filter(|segment| segment.kind != SegmentKind::Shared)
+    }
+
+    /// Writes the divergent segments as BED records, one per line,
+    /// with the kind of the segment as the name
+    pub fn write_divergent_bed<W: Write>(&self, mut out: W) -> Result<()> {
+        for segment in self.divergent_segments() {
+            let (_, path_name) = &self.paths[segment.path_ix];
+
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                path_name,
+                segment.start,
+                segment.end,
+                segment.kind.name()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// An overlay coloring each node by the set of compared paths
+    /// that cover it
+    pub fn overlay(
+        &self,
+        node_count: usize,
+    ) -> (OverlayData, Option<OverlayLegend>) {
+        let mut colors = vec![UNCOVERED_COLOR; node_count];
+
+        let mut categories = vec![(UNCOVERED_COLOR, "None".to_string())];
+
+        let mut masks: Vec<u64> = Vec::new();
+
+        for (&id, &mask) in self.coverage.iter() {
+            let ix = (id.0 - 1) as usize;
+
+            if let Some(color) = colors.get_mut(ix) {
+                *color = self.mask_color(mask);
+            }
+
+            if mask != 0 && !masks.contains(&mask) {
+                masks.push(mask);
+            }
+        }
+
+        categories.extend(
+            masks
+                .into_iter()
+                .map(|mask| (self.mask_color(mask), self.mask_label(mask))),
+        );
+
+        let legend = OverlayLegend::categories(categories);
+
+        (OverlayData::RGB(colors), Some(legend))
+    }
+
+    fn mask_color(&self, mask: u64) -> rgb::RGBA<f32> {
+        let all_mask = (0..self.paths.len()).fold(0u64, |acc, i| acc | 1 << i);
+
+        if mask == 0 {
+            UNCOVERED_COLOR
+        } else if mask == all_mask {
+            SHARED_COLOR
+        } else if mask.count_ones() == 1 {
+            path_highlight_color(mask.trailing_zeros() as usize)
+        } else {
+            let mut hasher = DefaultHasher::default();
+            mask.hash(&mut hasher);
+
+            let (r, g, b) = hash_node_color(hasher.finish());
+            rgb::RGBA::new(r, g, b, 1.0)
+        }
+    }
+
+    fn mask_label(&self, mask: u64) -> String {
+        if mask.count_ones() as usize == self.paths.len() {
+            return "All paths".to_string();
+        }
+
+        self.paths
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| mask & (1 << ix) != 0)
+            .map(|(_, (_, name))| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn jaccard(inter: (usize, usize), union: (usize, usize)) -> Jaccard {
+    let ratio = |a: usize, b: usize| {
+        if b == 0 {
+            0.0
+        } else {
+            a as f64 / b as f64
+        }
+    };
+
+    Jaccard {
+        nodes: ratio(inter.0, union.0),
+        bases: ratio(inter.1, union.1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graph_query::test_graph;
+
+    fn fwd(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), false)
+    }
+
+    // a and b share nodes 1, 3, and 5, and take different nodes, 2
+    // and 4, between 1 and 3; c only covers 1 and 2
+    fn graph() -> GraphQuery {
+        test_graph(
+            &[(1, "AAAA"), (2, "CC"), (3, "GGG"), (4, "T"), (5, "AC")],
+            &[
+                (fwd(1), fwd(2)),
+                (fwd(1), fwd(4)),
+                (fwd(2), fwd(3)),
+                (fwd(4), fwd(3)),
+                (fwd(3), fwd(5)),
+            ],
+            &[
+                ("a", &[fwd(1), fwd(2), fwd(3), fwd(5)]),
+                ("b", &[fwd(1), fwd(4), fwd(3), fwd(5)]),
+                ("c", &[fwd(1), fwd(2)]),
+            ],
+        )
+    }
+
+    fn path_ids(graph: &GraphQuery, names: &[&str]) -> Vec<PathId> {
+        names
+            .iter()
+            .map(|name| graph.graph().get_path_id(name.as_bytes()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn shared_and_private_segments() {
+        let graph = graph();
+        let paths = path_ids(&graph, &["a", "b"]);
+
+        let comparison = PathComparison::new(&graph, &paths).unwrap();
+
+        let segment = |path_ix, start, end, kind| PathSegment {
+            path_ix,
+            start,
+            end,
+            kind,
+        };
+
+        assert_eq!(
+            comparison.segments,
+            vec![
+                segment(0, 0, 4, SegmentKind::Shared),
+                segment(0, 4, 6, SegmentKind::Private),
+                segment(0, 6, 11, SegmentKind::Shared),
+                segment(1, 0, 4, SegmentKind::Shared),
+                segment(1, 4, 5, SegmentKind::Private),
+                segment(1, 5, 10, SegmentKind::Shared),
+            ]
+        );
+
+        // 3 of the 5 nodes, and 9 of the 12 bases, are shared
+        assert_eq!(comparison.jaccard.nodes, 0.6);
+        assert_eq!(comparison.jaccard.bases, 0.75);
+    }
+
+    #[test]
+    fn divergent_bed_is_zero_based_and_half_open() {
+        let graph = graph();
+        let paths = path_ids(&graph, &["a", "b"]);
+
+        let comparison = PathComparison::new(&graph, &paths).unwrap();
+
+        let mut bed = Vec::new();
+        comparison.write_divergent_bed(&mut bed).unwrap();
+
+        assert_eq!(
+            bed.to_str().unwrap(),
+            "a\t4\t6\tprivate\nb\t4\t5\tprivate\n"
+        );
+    }
+
+    #[test]
+    fn partial_segments_and_pairwise_similarity() {
+        let graph = graph();
+        let paths = path_ids(&graph, &["a", "b", "c"]);
+
+        let comparison = PathComparison::new(&graph, &paths).unwrap();
+
+        let kinds = comparison
+            .segments
+            .iter()
+            .filter(|segment| segment.path_ix == 0)
+            .map(|segment| (segment.start, segment.end, segment.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![(0, 4, SegmentKind::Shared), (4, 11, SegmentKind::Partial)]
+        );
+
+        assert_eq!(comparison.pair(0, 1).nodes, 0.6);
+        assert_eq!(comparison.pair(0, 2).nodes, 0.5);
+        assert_eq!(comparison.pair(1, 2).nodes, 0.2);
+
+        for i in 0..3 {
+            assert_eq!(comparison.pair(i, i).nodes, 1.0);
+
+            for j in 0..3 {
+                assert_eq!(comparison.pair(i, j), comparison.pair(j, i));
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_paths_is_an_error() {
+        let names = (0..=MAX_COMPARED_PATHS)
+            .map(|ix| format!("path{}", ix))
+            .collect::<Vec<_>>();
+
+        let steps = [fwd(1)];
+        let paths = names
+            .iter()
+            .map(|name| (name.as_str(), &steps[..]))
+            .collect::<Vec<_>>();
+
+        let graph = test_graph(&[(1, "A")], &[], &paths);
+        let path_ids = graph.graph().path_ids().collect::<Vec<_>>();
+
+        assert!(PathComparison::new(&graph, &path_ids).is_err());
+        assert!(PathComparison::new(&graph, &path_ids[1..]).is_ok());
+    }
+}
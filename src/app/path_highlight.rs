@@ -0,0 +1,377 @@
+use handlegraph::handle::Handle;
+
+use std::ops::Range;
+
+use crate::geometry::{Point, Rect};
+use crate::universe::Node;
+
+/// The number of segments in each chunk of a path polyline, each of
+/// which has its own bounding box so that the chunks outside the view
+/// can be skipped
+pub const POLYLINE_CHUNK_LEN: usize = 256;
+
+/// The color of the `ix`th highlighted path, cycling through a
+/// categorical palette
+pub fn path_highlight_color(ix: usize) -> rgb::RGBA<f32> {
+    let colors = &colorous::TABLEAU10;
+    let color = colors[ix % colors.len()];
+
+    rgb::RGBA::new(
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+        1.0,
+    )
+}
+
+/// The perpendicular offset of the line of the `lane`th of `lanes`
+/// highlighted paths, centering the lanes on the node centers
+pub fn lane_offset(lane: usize, lanes: usize, spacing: f32) -> f32 {
+    let center = (lanes.max(1) - 1) as f32 / 2.0;
+    (lane as f32 - center) * spacing
+}
+
+/// A path as a polyline through the centers of its nodes, in step
+/// order, in world coordinates
+#[derive(Debug, Default, Clone)]
+pub struct PathPolyline {
+    pub points: Vec<Point>,
+
+    // the unit normal of the polyline at each point, averaged over
+    // the two adjacent segments, used to offset the line so that
+    // several paths through the same nodes remain distinguishable
+    pub normals: Vec<Point>,
+
+    // the bounding box of the `ix`th chunk of the polyline, i.e. of
+    // the points `ix * POLYLINE_CHUNK_LEN..=(ix + 1) * POLYLINE_CHUNK_LEN`
+    pub chunk_bounds: Vec<Rect>,
+}
+
+impl PathPolyline {
+    /// Builds the polyline of the path with the given steps; steps
+    /// on nodes missing from `nodes` are skipped, as are repeated
+    /// visits of the same node in consecutive steps
+    pub fn from_steps(
+        nodes: &[Node],
+        steps: impl IntoIterator<Item = Handle>,
+    ) -> Self {
+        let mut points: Vec<Point> = Vec::new();
+
+        for handle in steps {
+            let ix = (handle.id().0 - 1) as usize;
+
+            if let Some(node) = nodes.get(ix) {
+                let center = node.center();
+
+                if points.last() != Some(&center) {
+                    points.push(center);
+                }
+            }
+        }
+
+        let normals = polyline_normals(&points);
+        let chunk_bounds = chunk_bounds(&points);
+
+        Self {
+            points,
+            normals,
+            chunk_bounds,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The points in `range` of the polyline, moved `offset` world
+    /// units along the normals
+    pub fn offset_points(
+        &self,
+        range: Range<usize>,
+        offset: f32,
+    ) -> impl Iterator<Item = Point> + '_ {
+        self.points[range.clone()]
+            .iter()
+            .zip(self.normals[range].iter())
+            .map(move |(&p, &n)| p + n * offset)
+    }
+
+    /// The ranges of points of the chunks of the polyline that may
+    /// overlap `rect` when offset by `offset`, with adjacent chunks
+    /// merged into one range
+    pub fn visible_ranges(&self, rect: Rect, offset: f32) -> Vec<Range<usize>> {
+        let margin = Point::new(offset.abs(), offset.abs());
+        let rect = Rect::new(rect.min() - margin, rect.max() + margin);
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        for (ix, bounds) in self.chunk_bounds.iter().enumerate() {
+            if !bounds.intersects(rect) {
+                continue;
+            }
+
+            let start = ix * POLYLINE_CHUNK_LEN;
+            let end =
+                ((ix + 1) * POLYLINE_CHUNK_LEN + 1).min(self.points.len());
+
+            match ranges.last_mut() {
+                Some(last) if start < last.end => last.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+
+        ranges
+    }
+}
+
+fn chunk_bounds(points: &[Point]) -> Vec<Rect> {
+    (0..points.len())
+        .step_by(POLYLINE_CHUNK_LEN)
+        .map(|start| {
+            let end = (start + POLYLINE_CHUNK_LEN).min(points.len() - 1);
+
+            points[start..=end]
+                .iter()
+                .fold(Rect::nowhere(), |rect, &p| rect.union(Rect::new(p, p)))
+        })
+        .collect()
+}
+
+fn polyline_normals(points: &[Point]) -> Vec<Point> {
+    let mut normals = Vec::with_capacity(points.len());
+
+    let mut last_normal = Point::new(0.0, 1.0);
+
+    for ix in 0..points.len() {
+        let prev = points[ix.saturating_sub(1)];
+        let next = points[(ix + 1).min(points.len() - 1)];
+
+        let dir = next - prev;
+        let len = dir.length();
+
+        if len > std::f32::EPSILON {
+            last_normal = Point::new(-dir.y / len, dir.x / len);
+        }
+
+        normals.push(last_normal);
+    }
+
+    normals
+}
+
+/// Places arrow heads along a polyline, in the direction of the
+/// line, one every `spacing` units of length, starting half a
+/// spacing from the start. Each arrow head is a triangle with its
+/// tip first
+pub fn arrow_heads(
+    points: &[Point],
+    spacing: f32,
+    size: f32,
+) -> Vec<[Point; 3]> {
+    let mut arrows = Vec::new();
+
+    if spacing <= 0.0 {
+        return arrows;
+    }
+
+    let mut next_arrow = spacing / 2.0;
+    let mut travelled = 0.0;
+
+    for segment in points.windows(2) {
+        let (p0, p1) = (segment[0], segment[1]);
+
+        let len = p0.dist(p1);
+
+        if len <= std::f32::EPSILON {
+            continue;
+        }
+
+        let dir = (p1 - p0) / len;
+        let normal = Point::new(-dir.y, dir.x);
+
+        while next_arrow <= travelled + len {
+            let tip = p0 + dir * (next_arrow - travelled);
+            let base = tip - dir * size;
+
+            arrows.push([
+                tip,
+                base + normal * (size / 2.0),
+                base - normal * (size / 2.0),
+            ]);
+
+            next_arrow += spacing;
+        }
+
+        travelled += len;
+    }
+
+    arrows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::handle::NodeId;
+
+    fn node_at(x: f32, y: f32) -> Node {
+        Node {
+            p0: Point::new(x - 1.0, y),
+            p1: Point::new(x + 1.0, y),
+        }
+    }
+
+    fn handle(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), false)
+    }
+
+    fn approx_eq(a: Point, b: Point) -> bool {
+        a.dist(b) < 1.0e-5
+    }
+
+    #[test]
+    fn from_steps_skips_missing_and_repeated_nodes() {
+        let nodes = vec![node_at(0.0, 0.0), node_at(10.0, 0.0)];
+
+        // node 3 has no layout, and node 2 is visited twice in a row
+        let steps = vec![handle(1), handle(3), handle(2), handle(2), handle(1)];
+
+        let polyline = PathPolyline::from_steps(&nodes, steps);
+
+        assert_eq!(
+            polyline.points,
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(10.0, 0.0),
+                Point::new(0.0, 0.0),
+            ]
+        );
+        assert_eq!(polyline.normals.len(), polyline.points.len());
+        assert_eq!(polyline.chunk_bounds.len(), 1);
+    }
+
+    #[test]
+    fn from_steps_without_nodes_is_empty() {
+        let polyline = PathPolyline::from_steps(&[], vec![handle(1)]);
+        assert!(polyline.is_empty());
+        assert!(polyline.chunk_bounds.is_empty());
+    }
+
+    #[test]
+    fn normals_are_perpendicular_unit_vectors() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+        ];
+
+        let normals = polyline_normals(&points);
+
+        assert!(approx_eq(normals[0], Point::new(0.0, 1.0)));
+        assert!(approx_eq(normals[2], Point::new(-1.0, 0.0)));
+
+        // the middle normal is averaged over both segments
+        let diag = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(approx_eq(normals[1], Point::new(-diag, diag)));
+
+        for n in normals {
+            assert!((n.length() - 1.0).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn normals_of_repeated_points_reuse_the_last_normal() {
+        let points = vec![Point::new(1.0, 1.0)];
+        assert_eq!(polyline_normals(&points), vec![Point::new(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn lanes_are_centered() {
+        assert_eq!(lane_offset(0, 1, 4.0), 0.0);
+        assert_eq!(lane_offset(0, 0, 4.0), 0.0);
+
+        assert_eq!(lane_offset(0, 2, 4.0), -2.0);
+        assert_eq!(lane_offset(1, 2, 4.0), 2.0);
+
+        assert_eq!(lane_offset(0, 3, 4.0), -4.0);
+        assert_eq!(lane_offset(1, 3, 4.0), 0.0);
+        assert_eq!(lane_offset(2, 3, 4.0), 4.0);
+    }
+
+    #[test]
+    fn arrow_heads_are_evenly_spaced() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(50.0, 0.0),
+            // a zero-length segment doesn't affect the spacing
+            Point::new(50.0, 0.0),
+            Point::new(50.0, 50.0),
+        ];
+
+        let arrows = arrow_heads(&points, 20.0, 4.0);
+
+        // tips at 10, 30, 50, 70, 90 units along the line
+        let tips = arrows.iter().map(|a| a[0]).collect::<Vec<_>>();
+        let expected = vec![
+            Point::new(10.0, 0.0),
+            Point::new(30.0, 0.0),
+            Point::new(50.0, 0.0),
+            Point::new(50.0, 20.0),
+            Point::new(50.0, 40.0),
+        ];
+
+        assert_eq!(tips.len(), expected.len());
+        for (tip, expected) in tips.into_iter().zip(expected) {
+            assert!(approx_eq(tip, expected));
+        }
+
+        // the first arrow points along the x axis
+        let [tip, left, right] = arrows[0];
+        assert!(approx_eq(left, Point::new(6.0, 2.0)));
+        assert!(approx_eq(right, Point::new(6.0, -2.0)));
+        assert!(approx_eq(tip, Point::new(10.0, 0.0)));
+    }
+
+    #[test]
+    fn arrow_heads_need_a_positive_spacing() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0)];
+        assert!(arrow_heads(&points, 0.0, 4.0).is_empty());
+    }
+
+    #[test]
+    fn visible_ranges_skip_chunks_outside_the_rect() {
+        let len = POLYLINE_CHUNK_LEN * 3 + 1;
+        let nodes = (0..len)
+            .map(|ix| node_at(ix as f32, 0.0))
+            .collect::<Vec<_>>();
+        let steps = (1..=len as u64).map(handle).collect::<Vec<_>>();
+
+        let polyline = PathPolyline::from_steps(&nodes, steps);
+        assert_eq!(polyline.chunk_bounds.len(), 4);
+
+        let chunk = POLYLINE_CHUNK_LEN as f32;
+
+        // only the middle chunk
+        let rect = Rect::new(
+            Point::new(chunk + 10.0, -1.0),
+            Point::new(chunk + 20.0, 1.0),
+        );
+        assert_eq!(
+            polyline.visible_ranges(rect, 0.0),
+            vec![POLYLINE_CHUNK_LEN..2 * POLYLINE_CHUNK_LEN + 1]
+        );
+
+        // adjacent chunks are merged
+        let rect =
+            Rect::new(Point::new(10.0, -1.0), Point::new(chunk + 10.0, 1.0));
+        assert_eq!(
+            polyline.visible_ranges(rect, 0.0),
+            vec![0..2 * POLYLINE_CHUNK_LEN + 1]
+        );
+
+        // the offset moves the line into the rect
+        let rect = Rect::new(Point::new(10.0, 5.0), Point::new(20.0, 6.0));
+        assert!(polyline.visible_ranges(rect, 0.0).is_empty());
+        assert_eq!(polyline.visible_ranges(rect, 5.0).len(), 1);
+    }
+}
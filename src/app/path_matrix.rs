@@ -0,0 +1,325 @@
+use handlegraph::{handle::NodeId, handlegraph::*, pathhandlegraph::*};
+
+use bstr::ByteSlice;
+use rayon::prelude::*;
+
+use crate::graph_query::GraphQuery;
+
+/// The path visits the node in its forward orientation
+pub const STEP_FORWARD: u8 = 1;
+/// The path visits the node in reverse
+pub const STEP_REVERSE: u8 = 2;
+
+/// The nodes visited by a path, with the orientations they're
+/// visited in
+#[derive(Debug, Clone)]
+pub struct PathMatrixRow {
+    pub path: PathId,
+    pub name: String,
+
+    // sorted by node index, i.e. the index of the node ID in the
+    // sorted node IDs of the matrix, with the STEP_FORWARD and STEP_REVERSE
+    // flags of all the steps on the node
+    nodes: Vec<(usize, u8)>,
+}
+
+impl PathMatrixRow {
+    /// The orientation flags of the steps of the path on the node
+    /// with index `node_ix`, or 0 if the path doesn't visit the node
+    pub fn orientation(&self, node_ix: usize) -> u8 {
+        self.nodes
+            .binary_search_by_key(&node_ix, |(ix, _)| *ix)
+            .map(|i| self.nodes[i].1)
+            .unwrap_or(0)
+    }
+}
+
+/// A one-dimensional linearization of the graph, with the nodes laid
+/// out end to end in ID order, and one row per path showing which
+/// nodes the path covers
+#[derive(Debug, Clone)]
+pub struct PathMatrix {
+    // the IDs of the nodes, sorted, so that node indices can be
+    // mapped to and from IDs even if there are gaps between them
+    node_ids: Vec<NodeId>,
+
+    // the start position of each node, in base pairs, followed by the
+    // total length of the nodes
+    node_offsets: Vec<usize>,
+
+    pub rows: Vec<PathMatrixRow>,
+}
+
+impl PathMatrix {
+    pub fn new(
+        rayon_pool: &rayon::ThreadPool,
+        graph_query: &GraphQuery,
+    ) -> Self {
+        let graph = graph_query.graph();
+
+        let mut handles = graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let mut node_ids = Vec::with_capacity(handles.len());
+        let mut node_offsets = Vec::with_capacity(handles.len() + 1);
+
+        let mut offset = 0;
+        for handle in handles {
+            node_ids.push(handle.id());
+            node_offsets.push(offset);
+            offset += graph.node_len(handle);
+        }
+        node_offsets.push(offset);
+
+        let mut paths = graph.path_ids().collect::<Vec<_>>();
+        paths.sort_by_key(|path| path.0);
+
+        let rows = rayon_pool.install(|| {
+            paths
+                .into_par_iter()
+                .filter_map(|path| {
+                    let name = graph.get_path_name_vec(path)?;
+                    let steps = graph.path_steps(path)?;
+
+                    let mut nodes = steps
+                        .filter_map(|step| {
+                            let handle = step.handle();
+                            let ix =
+                                node_ids.binary_search(&handle.id()).ok()?;
+
+                            if handle.is_reverse() {
+                                Some((ix, STEP_REVERSE))
+                            } else {
+                                Some((ix, STEP_FORWARD))
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    nodes.sort_unstable();
+
+                    // merge the orientations of repeated visits
+                    nodes.dedup_by(|(ix, flags), (prev_ix, prev_flags)| {
+                        if ix == prev_ix {
+                            *prev_flags |= *flags;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+
+                    Some(PathMatrixRow {
+                        path,
+                        name: name.to_str_lossy().into_owned(),
+                        nodes,
+                    })
+                })
+                .collect()
+        });
+
+        Self {
+            node_ids,
+            node_offsets,
+            rows,
+        }
+    }
+
+    /// The total length of the nodes, in base pairs
+    pub fn total_len(&self) -> usize {
+        self.node_offsets.last().copied().unwrap_or(0)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_offsets.len().saturating_sub(1)
+    }
+
+    /// The node at the given position of the linearization
+    pub fn node_at(&self, pos: usize) -> Option<NodeId> {
+        if pos >= self.total_len() {
+            return None;
+        }
+
+        let ix = self.node_offsets.partition_point(|&o| o <= pos) - 1;
+        Some(self.node_ids[ix])
+    }
+
+    /// The index of the node with the given ID, in the order the
+    /// nodes are laid out
+    pub fn node_ix(&self, node: NodeId) -> Option<usize> {
+        self.node_ids.binary_search(&node).ok()
+    }
+
+    /// The start and end positions of the node with index `node_ix`
+    pub fn node_range(&self, node_ix: usize) -> (usize, usize) {
+        (self.node_offsets[node_ix], self.node_offsets[node_ix + 1])
+    }
+
+    /// The range of `bins` equally sized bins spanning `start..end`
+    /// covered by the node with index `node_ix`, if any
+    pub fn node_bins(
+        &self,
+        node_ix: usize,
+        start: f64,
+        end: f64,
+        bins: usize,
+    ) -> Option<(usize, usize)> {
+        if node_ix >= self.node_count() || end <= start || bins == 0 {
+            return None;
+        }
+
+        let bp_per_bin = (end - start) / bins as f64;
+
+        let (n0, n1) = self.node_range(node_ix);
+
+        if (n1 as f64) <= start || (n0 as f64) >= end {
+            return None;
+        }
+
+        let b0 = ((n0 as f64 - start) / bp_per_bin).floor().max(0.0) as usize;
+        let b1 = ((n1 as f64 - start) / bp_per_bin).ceil() as usize;
+
+        let b0 = b0.min(bins - 1);
+        let b1 = b1.min(bins).max(b0 + 1);
+
+        Some((b0, b1))
+    }
+
+    /// Splits `start..end` into `bins` equally sized bins, and
+    /// returns the combined orientation flags of the nodes the path
+    /// of `row` covers in each bin
+    pub fn bin_row(
+        &self,
+        row: usize,
+        start: f64,
+        end: f64,
+        bins: usize,
+    ) -> Vec<u8> {
+        let mut result = vec![0u8; bins];
+
+        let row = if let Some(row) = self.rows.get(row) {
+            row
+        } else {
+            return result;
+        };
+
+        let first = row.nodes.partition_point(|(ix, _)| {
+            (self.node_offsets[ix + 1] as f64) <= start
+        });
+
+        for &(ix, flags) in row.nodes[first..].iter() {
+            if self.node_offsets[ix] as f64 >= end {
+                break;
+            }
+
+            if let Some((b0, b1)) = self.node_bins(ix, start, end, bins) {
+                for bin in result[b0..b1].iter_mut() {
+                    *bin |= flags;
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::handle::Handle;
+
+    use crate::graph_query::test_graph;
+
+    const BOTH: u8 = STEP_FORWARD | STEP_REVERSE;
+
+    fn fwd(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), false)
+    }
+
+    fn rev(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), true)
+    }
+
+    // the nodes are laid out at 0..10, 10..15, and 15..30, with gaps
+    // between their IDs
+    fn matrix() -> PathMatrix {
+        let graph = test_graph(
+            &[(3, "ACGTACGTAC"), (7, "ACGTA"), (10, "ACGTACGTACGTACG")],
+            &[],
+            &[("p", &[fwd(3), rev(10)]), ("q", &[fwd(7), rev(7), fwd(10)])],
+        );
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+
+        PathMatrix::new(&pool, &graph)
+    }
+
+    #[test]
+    fn nodes_are_indexed_in_id_order() {
+        let matrix = matrix();
+
+        assert_eq!(matrix.node_count(), 3);
+        assert_eq!(matrix.total_len(), 30);
+
+        assert_eq!(matrix.node_ix(NodeId::from(7)), Some(1));
+        assert_eq!(matrix.node_ix(NodeId::from(4)), None);
+
+        assert_eq!(matrix.node_at(0), Some(NodeId::from(3)));
+        assert_eq!(matrix.node_at(10), Some(NodeId::from(7)));
+        assert_eq!(matrix.node_at(29), Some(NodeId::from(10)));
+        assert_eq!(matrix.node_at(30), None);
+
+        assert_eq!(matrix.node_range(2), (15, 30));
+
+        assert_eq!(matrix.rows[0].orientation(0), STEP_FORWARD);
+        assert_eq!(matrix.rows[0].orientation(1), 0);
+        assert_eq!(matrix.rows[0].orientation(2), STEP_REVERSE);
+        assert_eq!(matrix.rows[1].orientation(1), BOTH);
+    }
+
+    #[test]
+    fn node_bins_at_bin_edges() {
+        let matrix = matrix();
+
+        // 10 bp per bin, the first two nodes end on bin edges
+        assert_eq!(matrix.node_bins(0, 0.0, 30.0, 3), Some((0, 1)));
+        assert_eq!(matrix.node_bins(1, 0.0, 30.0, 3), Some((1, 2)));
+        assert_eq!(matrix.node_bins(2, 0.0, 30.0, 3), Some((1, 3)));
+
+        // 5 bp per bin, the first node straddles the start and the
+        // last the end
+        assert_eq!(matrix.node_bins(0, 5.0, 20.0, 3), Some((0, 1)));
+        assert_eq!(matrix.node_bins(1, 5.0, 20.0, 3), Some((1, 2)));
+        assert_eq!(matrix.node_bins(2, 5.0, 20.0, 3), Some((2, 3)));
+
+        // nodes ending at the start or starting at the end are outside
+        assert_eq!(matrix.node_bins(0, 10.0, 20.0, 2), None);
+        assert_eq!(matrix.node_bins(2, 0.0, 15.0, 3), None);
+
+        assert_eq!(matrix.node_bins(3, 0.0, 30.0, 3), None);
+        assert_eq!(matrix.node_bins(0, 0.0, 30.0, 0), None);
+        assert_eq!(matrix.node_bins(0, 10.0, 10.0, 3), None);
+    }
+
+    #[test]
+    fn bin_row_flags() {
+        let matrix = matrix();
+
+        let f = STEP_FORWARD;
+        let r = STEP_REVERSE;
+
+        assert_eq!(matrix.bin_row(0, 0.0, 30.0, 3), vec![f, r, r]);
+        assert_eq!(matrix.bin_row(1, 0.0, 30.0, 3), vec![0, BOTH, f]);
+
+        assert_eq!(matrix.bin_row(0, 5.0, 20.0, 3), vec![f, 0, r]);
+        assert_eq!(matrix.bin_row(1, 5.0, 20.0, 3), vec![0, BOTH, f]);
+
+        // only the middle node is in range
+        assert_eq!(matrix.bin_row(0, 10.0, 15.0, 2), vec![0, 0]);
+        assert_eq!(matrix.bin_row(1, 10.0, 15.0, 2), vec![BOTH, BOTH]);
+
+        assert_eq!(matrix.bin_row(2, 0.0, 30.0, 3), vec![0, 0, 0]);
+    }
+}
@@ -1,6 +1,8 @@
+use anyhow::{bail, Result};
 use crossbeam::atomic::AtomicCell;
 use std::sync::Arc;
 
+use crate::app::mainview::view::{EasingExpoOut, EasingFunction};
 use crate::vulkan::draw_system::edges::EdgesUBO;
 
 #[derive(Debug, Clone)]
@@ -56,6 +58,101 @@ impl AppSettings {
     pub fn background_color_dark(&self) -> &Arc<AtomicCell<rgb::RGB<f32>>> {
         &self.background_color_dark
     }
+
+    /// The current settings as key-value pairs, using the same keys
+    /// as `set_from_str`; colors and lists are whitespace-separated
+    pub fn to_key_values(&self) -> Vec<(&'static str, String)> {
+        let rgb_str = |c: rgb::RGB<f32>| format!("{} {} {}", c.r, c.g, c.b);
+
+        let node_width = &self.node_width;
+        let edges = self.edge_renderer.load();
+
+        let tess_levels = edges
+            .tess_levels
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        vec![
+            ("min_node_width", node_width.min_node_width().to_string()),
+            ("max_node_width", node_width.max_node_width().to_string()),
+            ("min_node_scale", node_width.min_node_scale().to_string()),
+            ("max_node_scale", node_width.max_node_scale().to_string()),
+            ("edge_color", rgb_str(edges.edge_color)),
+            ("edge_width", edges.edge_width.to_string()),
+            ("tess_levels", tess_levels),
+            ("curve_offset", edges.curve_offset.to_string()),
+            ("label_radius", self.label_radius.load().to_string()),
+            (
+                "background_color_light",
+                rgb_str(self.background_color_light.load()),
+            ),
+            (
+                "background_color_dark",
+                rgb_str(self.background_color_dark.load()),
+            ),
+        ]
+    }
+
+    /// Set the setting `key` from its string representation, as
+    /// produced by `to_key_values`
+    pub fn set_from_str(&self, key: &str, value: &str) -> Result<()> {
+        let floats = || -> Result<Vec<f32>> {
+            let vals = value
+                .split_whitespace()
+                .map(|v| v.parse::<f32>())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(vals)
+        };
+
+        let float = || -> Result<f32> {
+            match floats()?.as_slice() {
+                [v] => Ok(*v),
+                _ => bail!("Expected a number for `{}`, got `{}`", key, value),
+            }
+        };
+
+        let rgb = || -> Result<rgb::RGB<f32>> {
+            match floats()?.as_slice() {
+                [r, g, b] => Ok(rgb::RGB::new(*r, *g, *b)),
+                _ => bail!("Expected an RGB color for `{}`", key),
+            }
+        };
+
+        let node_width = &self.node_width;
+
+        match key {
+            "min_node_width" => node_width.set_min_node_width(float()?),
+            "max_node_width" => node_width.set_max_node_width(float()?),
+            "min_node_scale" => node_width.set_min_node_scale(float()?),
+            "max_node_scale" => node_width.set_max_node_scale(float()?),
+            "edge_color" | "edge_width" | "tess_levels" | "curve_offset" => {
+                let mut edges = self.edge_renderer.load();
+                match key {
+                    "edge_color" => edges.edge_color = rgb()?,
+                    "edge_width" => edges.edge_width = float()?,
+                    "curve_offset" => edges.curve_offset = float()?,
+                    _ => {
+                        let levels = floats()?;
+                        if levels.len() != edges.tess_levels.len() {
+                            bail!("Expected 5 tessellation levels");
+                        }
+                        edges.tess_levels.copy_from_slice(&levels);
+                    }
+                }
+                self.edge_renderer.store(edges);
+            }
+            "label_radius" => self.label_radius.store(float()?),
+            "background_color_light" => {
+                self.background_color_light.store(rgb()?)
+            }
+            "background_color_dark" => self.background_color_dark.store(rgb()?),
+            _ => bail!("Unknown setting `{}`", key),
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -83,6 +180,30 @@ impl NodeWidth {
         self.max_node_scale.load()
     }
 
+    /// The width of the nodes, in world units, when drawn at the
+    /// view scale `scale`
+    pub fn width_at_scale(&self, scale: f32) -> f32 {
+        let min = self.min_node_width();
+        let max = self.max_node_width();
+
+        let min_scale = self.min_node_scale();
+        let max_scale = self.max_node_scale();
+
+        let norm_scale = (scale - min_scale) / (max_scale - min_scale);
+
+        let easing_val =
+            EasingExpoOut::value_at_normalized_time(norm_scale as f64) as f32;
+
+        let mut width = min + easing_val * (max - min);
+
+        if scale > max_scale {
+            width *= scale / (min_scale - max_scale);
+        } else if scale < min_scale {
+            width = min
+        }
+        width
+    }
+
     pub fn set_min_node_width(&self, width: f32) {
         self.min_node_width.store(width);
     }
@@ -2,8 +2,16 @@ use std::sync::Arc;
 
 use crossbeam::atomic::AtomicCell;
 use handlegraph::handle::NodeId;
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
 
-use crate::{geometry::*, gui::GuiFocusState};
+use crate::{
+    geometry::*,
+    gui::GuiFocusState,
+    overlays::{OverlayData, OverlayLegend, OverlayNormalization},
+};
+
+use super::navigation::{Bookmark, ViewHistory};
 use crate::{view::*, vulkan::texture::GradientName};
 
 #[derive(Clone)]
@@ -13,8 +21,14 @@ pub struct SharedState {
 
     pub view: Arc<AtomicCell<View>>,
 
+    pub view_history: Arc<Mutex<ViewHistory>>,
+    pub bookmarks: Arc<Mutex<Vec<Bookmark>>>,
+
     pub hover_node: Arc<AtomicCell<Option<NodeId>>>,
 
+    // mirrors `App::selected_nodes_bounding_box`, for use by the GUI
+    pub selection_bounds: Arc<AtomicCell<Option<Rect>>>,
+
     pub mouse_rect: MouseRect,
 
     pub overlay_state: OverlayState,
@@ -26,6 +40,9 @@ pub struct SharedState {
     pub dark_mode: Arc<AtomicCell<bool>>,
 
     pub show_modal: Arc<AtomicCell<bool>>,
+
+    // mirrors `GfaestusVk::supports_frame_capture`
+    pub frame_capture_supported: Arc<AtomicCell<bool>>,
 }
 
 impl SharedState {
@@ -36,8 +53,13 @@ impl SharedState {
 
             view: Arc::new(View::default().into()),
 
+            view_history: Arc::new(Mutex::new(ViewHistory::default())),
+            bookmarks: Arc::new(Mutex::new(Vec::new())),
+
             hover_node: Arc::new(None.into()),
 
+            selection_bounds: Arc::new(None.into()),
+
             mouse_rect: MouseRect::default(),
 
             overlay_state: OverlayState::default(),
@@ -47,6 +69,8 @@ impl SharedState {
             edges_enabled: Arc::new(true.into()),
             dark_mode: Arc::new(false.into()),
             show_modal: Arc::new(false.into()),
+
+            frame_capture_supported: Arc::new(false.into()),
         }
     }
 
@@ -62,10 +86,22 @@ impl SharedState {
         self.view.load()
     }
 
+    pub fn view_history(&self) -> &Arc<Mutex<ViewHistory>> {
+        &self.view_history
+    }
+
+    pub fn bookmarks(&self) -> &Arc<Mutex<Vec<Bookmark>>> {
+        &self.bookmarks
+    }
+
     pub fn hover_node(&self) -> Option<NodeId> {
         self.hover_node.load()
     }
 
+    pub fn selection_bounds(&self) -> Option<Rect> {
+        self.selection_bounds.load()
+    }
+
     pub fn overlay_state(&self) -> &OverlayState {
         &self.overlay_state
     }
@@ -164,6 +200,14 @@ pub struct OverlayState {
     pub current_overlay: Arc<AtomicCell<Option<usize>>>,
 
     gradient: Arc<AtomicCell<GradientName>>,
+
+    // CPU-side copies of the data uploaded to each overlay, so they
+    // can be saved to disk
+    overlay_data: Arc<Mutex<FxHashMap<usize, Arc<OverlayData>>>>,
+
+    overlay_legends: Arc<Mutex<FxHashMap<usize, OverlayLegend>>>,
+
+    overlay_normalizations: Arc<Mutex<FxHashMap<usize, OverlayNormalization>>>,
 }
 
 impl OverlayState {
@@ -182,6 +226,96 @@ impl OverlayState {
     pub fn set_gradient(&self, gradient: GradientName) {
         self.gradient.store(gradient);
     }
+
+    pub fn overlay_data(&self, overlay_id: usize) -> Option<Arc<OverlayData>> {
+        self.overlay_data.lock().get(&overlay_id).cloned()
+    }
+
+    pub fn set_overlay_data(&self, overlay_id: usize, data: Arc<OverlayData>) {
+        self.overlay_data.lock().insert(overlay_id, data);
+    }
+
+    pub fn overlay_legend(&self, overlay_id: usize) -> Option<OverlayLegend> {
+        self.overlay_legends.lock().get(&overlay_id).cloned()
+    }
+
+    /// Sets the legend of an overlay. If both the old and new legends
+    /// are ranges, and the new one doesn't have units, the old units
+    /// are kept, so that they survive rerunning the overlay script
+    pub fn set_overlay_legend(
+        &self,
+        overlay_id: usize,
+        legend: Option<OverlayLegend>,
+    ) {
+        let mut legends = self.overlay_legends.lock();
+
+        match legend {
+            Some(mut legend) => {
+                if let (
+                    Some(OverlayLegend::Range { units: old, .. }),
+                    OverlayLegend::Range { units, .. },
+                ) = (legends.get(&overlay_id), &mut legend)
+                {
+                    if units.is_empty() {
+                        *units = old.clone();
+                    }
+                }
+                legends.insert(overlay_id, legend);
+            }
+            None => {
+                legends.remove(&overlay_id);
+            }
+        }
+    }
+
+    pub fn overlay_normalization(
+        &self,
+        overlay_id: usize,
+    ) -> OverlayNormalization {
+        self.overlay_normalizations
+            .lock()
+            .get(&overlay_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_overlay_normalization(
+        &self,
+        overlay_id: usize,
+        normalization: OverlayNormalization,
+    ) {
+        self.overlay_normalizations
+            .lock()
+            .insert(overlay_id, normalization);
+    }
+
+    /// The data of an overlay as it's shown, i.e. with the overlay's
+    /// normalization applied
+    pub fn normalized_overlay_data(
+        &self,
+        overlay_id: usize,
+    ) -> Option<Arc<OverlayData>> {
+        let data = self.overlay_data(overlay_id)?;
+        let legend = self.overlay_legend(overlay_id);
+
+        let normalization = self.overlay_normalization(overlay_id);
+
+        let normalized = match normalization.apply(&data, legend.as_ref()) {
+            std::borrow::Cow::Borrowed(_) => None,
+            std::borrow::Cow::Owned(normalized) => Some(Arc::new(normalized)),
+        };
+
+        Some(normalized.unwrap_or(data))
+    }
+
+    pub fn set_overlay_units(&self, overlay_id: usize, new_units: &str) {
+        let mut legends = self.overlay_legends.lock();
+        if let Some(OverlayLegend::Range { units, .. }) =
+            legends.get_mut(&overlay_id)
+        {
+            *units = new_units.to_string();
+        }
+    }
 }
 
 impl std::default::Default for OverlayState {
@@ -193,6 +327,9 @@ impl std::default::Default for OverlayState {
         Self {
             current_overlay,
             gradient,
+            overlay_data: Arc::new(Mutex::new(FxHashMap::default())),
+            overlay_legends: Arc::new(Mutex::new(FxHashMap::default())),
+            overlay_normalizations: Arc::new(Mutex::new(FxHashMap::default())),
         }
     }
 }
@@ -0,0 +1,348 @@
+//! Camera tours: timed sequences of views, overlay switches,
+//! selections, and label set visibility changes, that can be played
+//! back while optionally writing every rendered frame to a PNG.
+//!
+//! Tours can be built in the console (see `Tour` in the Rhai API), or
+//! loaded from a tab-separated file with one step per line:
+//!
+//! ```text
+//! view    <x> <y> <scale> <duration ms>
+//! wait    <duration ms>
+//! overlay <overlay name>
+//! select  <node id>,<node id>,...
+//! clear_selection
+//! labels  <label set name>    <true|false>
+//! ```
+
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use handlegraph::handle::NodeId;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::geometry::Point;
+use crate::view::View;
+
+use super::mainview::view::{EasingCirc, EasingFunction, ViewLerp};
+
+pub const TOUR_FILE_EXT: &str = "tour";
+
+/// The rate at which the tour advances when exporting frames,
+/// independent of how long each frame takes to render
+pub const TOUR_EXPORT_FPS: u32 = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TourStep {
+    /// Animate from the current view to `view` over `duration`
+    View {
+        view: View,
+        duration: Duration,
+    },
+    Wait(Duration),
+    Overlay(String),
+    Select(Vec<NodeId>),
+    ClearSelection,
+    Labels {
+        name: String,
+        visible: bool,
+    },
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Tour {
+    pub steps: Vec<TourStep>,
+}
+
+impl Tour {
+    pub fn push(&mut self, step: TourStep) {
+        self.steps.push(step);
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                TourStep::View { duration, .. } => *duration,
+                TourStep::Wait(duration) => *duration,
+                _ => Duration::from_millis(0),
+            })
+            .sum()
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut out = BufWriter::new(file);
+
+        for step in self.steps.iter() {
+            match step {
+                TourStep::View { view, duration } => writeln!(
+                    out,
+                    "view\t{}\t{}\t{}\t{}",
+                    view.center.x,
+                    view.center.y,
+                    view.scale,
+                    duration.as_millis()
+                )?,
+                TourStep::Wait(duration) => {
+                    writeln!(out, "wait\t{}", duration.as_millis())?
+                }
+                TourStep::Overlay(name) => writeln!(out, "overlay\t{}", name)?,
+                TourStep::Select(nodes) => {
+                    let nodes = nodes
+                        .iter()
+                        .map(|n| n.0.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    writeln!(out, "select\t{}", nodes)?
+                }
+                TourStep::ClearSelection => writeln!(out, "clear_selection")?,
+                TourStep::Labels { name, visible } => {
+                    writeln!(out, "labels\t{}\t{}", name, visible)?
+                }
+            }
+        }
+
+        out.flush()?;
+
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        let reader = BufReader::new(file);
+
+        let mut tour = Tour::default();
+
+        for (line_ix, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_no = line_ix + 1;
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let fields = trimmed.split('\t').collect::<Vec<_>>();
+
+            macro_rules! parse {
+                ($ix:literal, $ty:ty) => {
+                    match fields.get($ix).and_then(|f| f.parse::<$ty>().ok()) {
+                        Some(val) => val,
+                        None => bail!(
+                            "Error parsing field {} on line {}",
+                            $ix,
+                            line_no
+                        ),
+                    }
+                };
+            }
+
+            macro_rules! field {
+                ($ix:literal) => {
+                    match fields.get($ix) {
+                        Some(val) => val.to_string(),
+                        None => {
+                            bail!("Missing field {} on line {}", $ix, line_no)
+                        }
+                    }
+                };
+            }
+
+            let step = match fields[0] {
+                "view" => {
+                    let center = Point::new(parse!(1, f32), parse!(2, f32));
+                    let scale = parse!(3, f32);
+                    let duration = Duration::from_millis(parse!(4, u64));
+
+                    TourStep::View {
+                        view: View { center, scale },
+                        duration,
+                    }
+                }
+                "wait" => TourStep::Wait(Duration::from_millis(parse!(1, u64))),
+                "overlay" => TourStep::Overlay(field!(1)),
+                "select" => {
+                    let nodes = field!(1)
+                        .split(',')
+                        .map(|id| id.trim().parse::<u64>().map(NodeId::from))
+                        .collect::<std::result::Result<Vec<_>, _>>()?;
+                    TourStep::Select(nodes)
+                }
+                "clear_selection" => TourStep::ClearSelection,
+                "labels" => TourStep::Labels {
+                    name: field!(1),
+                    visible: parse!(2, bool),
+                },
+                other => {
+                    warn!(
+                        "Ignoring unknown tour step `{}` on line {}",
+                        other, line_no
+                    );
+                    continue;
+                }
+            };
+
+            tour.push(step);
+        }
+
+        Ok(tour)
+    }
+}
+
+/// The result of advancing a `TourPlayer` by one frame
+#[derive(Debug, Default)]
+pub struct TourFrame {
+    /// The view to use for this frame
+    pub view: Option<View>,
+    /// The steps other than views and waits that were reached since
+    /// the previous frame, in order
+    pub actions: Vec<TourStep>,
+    /// The PNG file this frame should be written to, if frames are
+    /// being exported
+    pub frame_path: Option<PathBuf>,
+}
+
+pub struct TourPlayer {
+    tour: Tour,
+
+    step_ix: usize,
+    // the tour time at which the current step started
+    step_start: Duration,
+    // the view at the start of the current view step
+    step_from: Option<View>,
+    last_view: View,
+
+    time: Duration,
+    last_update: Option<Instant>,
+
+    frame_dir: Option<PathBuf>,
+    frame: usize,
+}
+
+impl TourPlayer {
+    /// Start playing `tour` from `current_view`; if `frame_dir` is
+    /// set, every frame of the tour is written there as a numbered
+    /// PNG
+    pub fn new(
+        tour: Tour,
+        current_view: View,
+        frame_dir: Option<PathBuf>,
+    ) -> Result<Self> {
+        if let Some(dir) = frame_dir.as_ref() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        Ok(Self {
+            tour,
+
+            step_ix: 0,
+            step_start: Duration::from_millis(0),
+            step_from: None,
+            last_view: current_view,
+
+            time: Duration::from_millis(0),
+            last_update: None,
+
+            frame_dir,
+            frame: 0,
+        })
+    }
+
+    pub fn is_exporting(&self) -> bool {
+        self.frame_dir.is_some()
+    }
+
+    /// Keep playing the tour without writing any more frames
+    pub fn stop_exporting(&mut self) {
+        self.frame_dir = None;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step_ix >= self.tour.steps.len()
+    }
+
+    /// The fraction of the tour that has been played
+    pub fn progress(&self) -> f32 {
+        let total = self.tour.duration().as_secs_f32();
+
+        if total > 0.0 {
+            (self.time.as_secs_f32() / total).min(1.0)
+        } else if self.is_done() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Advance the tour to the next frame. When exporting frames,
+    /// the tour time advances by a fixed step per frame, otherwise
+    /// it follows the wall clock
+    pub fn advance(&mut self) -> TourFrame {
+        let now = Instant::now();
+
+        if self.is_exporting() {
+            if self.frame > 0 {
+                self.time += Duration::from_secs(1) / TOUR_EXPORT_FPS;
+            }
+        } else if let Some(last) = self.last_update {
+            self.time += now - last;
+        }
+        self.last_update = Some(now);
+
+        let mut result = TourFrame::default();
+
+        while let Some(step) = self.tour.steps.get(self.step_ix).cloned() {
+            let elapsed = self.time.saturating_sub(self.step_start);
+
+            match step {
+                TourStep::View { view, duration } => {
+                    let from = *self.step_from.get_or_insert(self.last_view);
+
+                    if elapsed >= duration {
+                        result.view = Some(view);
+                        self.last_view = view;
+                        self.next_step(duration);
+                    } else {
+                        let t = elapsed.as_secs_f64() / duration.as_secs_f64();
+                        let t = EasingCirc::value_at_normalized_time(t);
+                        let view = ViewLerp::new(from, view).lerp(t);
+
+                        result.view = Some(view);
+                        self.last_view = view;
+                        break;
+                    }
+                }
+                TourStep::Wait(duration) => {
+                    if elapsed >= duration {
+                        self.next_step(duration);
+                    } else {
+                        break;
+                    }
+                }
+                other => {
+                    result.actions.push(other);
+                    self.next_step(Duration::from_millis(0));
+                }
+            }
+        }
+
+        if let Some(dir) = self.frame_dir.as_ref() {
+            result.frame_path =
+                Some(dir.join(format!("frame_{:06}.png", self.frame)));
+        }
+
+        self.frame += 1;
+
+        result
+    }
+
+    fn next_step(&mut self, step_duration: Duration) {
+        self.step_ix += 1;
+        self.step_start += step_duration;
+        self.step_from = None;
+    }
+}
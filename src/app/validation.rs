@@ -0,0 +1,255 @@
+use handlegraph::{
+    handle::{Direction, Edge, Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+use rustc_hash::FxHashMap;
+
+use crate::graph_query::GraphQuery;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    MissingEdge,
+    DuplicateEdge,
+    OrphanNode,
+    EmptyNode,
+    MissingLayout,
+}
+
+impl IssueKind {
+    pub const ALL: [IssueKind; 5] = [
+        IssueKind::MissingEdge,
+        IssueKind::DuplicateEdge,
+        IssueKind::OrphanNode,
+        IssueKind::EmptyNode,
+        IssueKind::MissingLayout,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueKind::MissingEdge => "Path steps without an edge",
+            IssueKind::DuplicateEdge => "Duplicate edges",
+            IssueKind::OrphanNode => "Orphan nodes",
+            IssueKind::EmptyNode => "Zero-length segments",
+            IssueKind::MissingLayout => "Nodes missing from the layout",
+        }
+    }
+}
+
+/// A problem found in a loaded graph or its layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    /// Two consecutive steps of a path with no edge connecting their
+    /// handles; `pos` is the base position of the first step
+    MissingEdge {
+        path: PathId,
+        pos: usize,
+        from: Handle,
+        to: Handle,
+    },
+    /// An edge that is stored more than once
+    DuplicateEdge { edge: Edge, count: usize },
+    /// A node with no edges, not visited by any path
+    OrphanNode(NodeId),
+    /// A node with an empty sequence
+    EmptyNode(NodeId),
+    /// A node with no rows in the layout file
+    MissingLayout(NodeId),
+}
+
+impl Issue {
+    pub fn kind(&self) -> IssueKind {
+        match self {
+            Issue::MissingEdge { .. } => IssueKind::MissingEdge,
+            Issue::DuplicateEdge { .. } => IssueKind::DuplicateEdge,
+            Issue::OrphanNode(_) => IssueKind::OrphanNode,
+            Issue::EmptyNode(_) => IssueKind::EmptyNode,
+            Issue::MissingLayout(_) => IssueKind::MissingLayout,
+        }
+    }
+
+    /// The nodes involved in the issue
+    pub fn nodes(&self) -> Vec<NodeId> {
+        match *self {
+            Issue::MissingEdge { from, to, .. } => vec![from.id(), to.id()],
+            Issue::DuplicateEdge {
+                edge: Edge(from, to),
+                ..
+            } => vec![from.id(), to.id()],
+            Issue::OrphanNode(id)
+            | Issue::EmptyNode(id)
+            | Issue::MissingLayout(id) => vec![id],
+        }
+    }
+}
+
+fn handle_str(handle: Handle) -> String {
+    let orient = if handle.is_reverse() { "-" } else { "+" };
+    format!("{}{}", handle.id().0, orient)
+}
+
+/// The problems found by a validation pass over a graph, in the
+/// order of `IssueKind::ALL`
+#[derive(Debug, Default, Clone)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+    path_names: FxHashMap<PathId, String>,
+}
+
+impl ValidationReport {
+    /// Validates the graph; `missing_layout` are the nodes that had
+    /// no rows in the layout file
+    pub fn new(graph_query: &GraphQuery, missing_layout: &[NodeId]) -> Self {
+        let graph = graph_query.graph();
+
+        let mut issues = Vec::new();
+        let mut path_names = FxHashMap::default();
+
+        let mut path_ids = graph.path_ids().collect::<Vec<_>>();
+        path_ids.sort();
+
+        for path in path_ids {
+            if let Some(name) = graph.get_path_name_vec(path) {
+                path_names.insert(path, name.to_str_lossy().into_owned());
+            }
+
+            let steps = if let Some(steps) = graph_query.path_pos_steps(path) {
+                steps
+            } else {
+                continue;
+            };
+
+            for window in steps.windows(2) {
+                let (from, _, pos) = window[0];
+                let (to, _, _) = window[1];
+
+                if !graph.has_edge(from, to) {
+                    issues.push(Issue::MissingEdge {
+                        path,
+                        pos,
+                        from,
+                        to,
+                    });
+                }
+            }
+        }
+
+        // an edge and its reverse are the same edge
+        let mut edge_counts: FxHashMap<Edge, usize> = FxHashMap::default();
+
+        for Edge(a, b) in graph.edges() {
+            let edge = Edge(a, b).min(Edge(b.flip(), a.flip()));
+            *edge_counts.entry(edge).or_default() += 1;
+        }
+
+        let mut duplicates = edge_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .collect::<Vec<_>>();
+        duplicates.sort();
+
+        issues.extend(
+            duplicates
+                .into_iter()
+                .map(|(edge, count)| Issue::DuplicateEdge { edge, count }),
+        );
+
+        let mut handles = graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let mut empty_nodes = Vec::new();
+
+        for &handle in handles.iter() {
+            let degree = graph.degree(handle, Direction::Left)
+                + graph.degree(handle, Direction::Right);
+
+            if degree == 0 {
+                let on_path = graph
+                    .steps_on_handle(handle)
+                    .map(|mut steps| steps.next().is_some())
+                    .unwrap_or(false);
+
+                if !on_path {
+                    issues.push(Issue::OrphanNode(handle.id()));
+                }
+            }
+
+            if graph.node_len(handle) == 0 {
+                empty_nodes.push(Issue::EmptyNode(handle.id()));
+            }
+        }
+
+        issues.extend(empty_nodes);
+
+        issues
+            .extend(missing_layout.iter().map(|&id| Issue::MissingLayout(id)));
+
+        Self { issues, path_names }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn issues_of(&self, kind: IssueKind) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(move |issue| issue.kind() == kind)
+    }
+
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues_of(kind).count()
+    }
+
+    pub fn describe(&self, issue: &Issue) -> String {
+        match *issue {
+            Issue::MissingEdge {
+                path,
+                pos,
+                from,
+                to,
+            } => {
+                let name = self
+                    .path_names
+                    .get(&path)
+                    .map(|name| name.as_str())
+                    .unwrap_or("<unnamed>");
+                format!(
+                    "{} at {}: {} -> {}",
+                    name,
+                    pos,
+                    handle_str(from),
+                    handle_str(to)
+                )
+            }
+            Issue::DuplicateEdge {
+                edge: Edge(from, to),
+                count,
+            } => format!(
+                "{} -> {} ({} copies)",
+                handle_str(from),
+                handle_str(to),
+                count
+            ),
+            Issue::OrphanNode(id)
+            | Issue::EmptyNode(id)
+            | Issue::MissingLayout(id) => format!("Node {}", id.0),
+        }
+    }
+
+    /// A one line summary of the issue counts, for the log
+    pub fn summary(&self) -> String {
+        IssueKind::ALL
+            .iter()
+            .filter_map(|&kind| {
+                let count = self.count(kind);
+                if count > 0 {
+                    Some(format!("{}: {}", kind.name(), count))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
@@ -0,0 +1,108 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use argh::FromArgs;
+
+use flexi_logger::{Logger, LoggerHandle};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use gfaestus::graph_query::GraphQuery;
+use gfaestus::script::headless::HeadlessRunner;
+use gfaestus::universe::Universe;
+
+#[derive(FromArgs)]
+/// Run a gfaestus Rhai script without a GUI, writing any overlays,
+/// selections, and label sets it creates to files
+pub struct Args {
+    /// the GFA file to load
+    #[argh(positional)]
+    gfa: String,
+
+    /// the Rhai script to run
+    #[argh(positional)]
+    script: String,
+
+    /// the layout TSV to load, needed for node positions
+    #[argh(option)]
+    layout: Option<String>,
+
+    /// directory the output files are written to, defaults to the
+    /// current directory
+    #[argh(option, default = "PathBuf::from(\".\")")]
+    out_dir: PathBuf,
+
+    /// number of threads used when computing overlays, defaults to
+    /// the number of CPUs
+    #[argh(option)]
+    threads: Option<usize>,
+
+    /// suppress log messages
+    #[argh(switch, short = 'q')]
+    quiet: bool,
+
+    /// whether to print debug messages
+    #[argh(switch, short = 'd')]
+    debug: bool,
+}
+
+fn set_up_logger(args: &Args) -> Result<LoggerHandle> {
+    let spec = match (args.debug, args.quiet) {
+        (true, _) => "debug",
+        (_, true) => "",
+        _ => "info",
+    };
+
+    let logger = Logger::try_with_env_or_str(spec)?.start()?;
+
+    Ok(logger)
+}
+
+fn run(args: &Args) -> Result<()> {
+    let graph_query = Arc::new(GraphQuery::load_gfa(&args.gfa)?);
+    info!(
+        "loaded {} with {} nodes",
+        args.gfa,
+        graph_query.node_count()
+    );
+
+    let universe = if let Some(layout) = &args.layout {
+        let universe =
+            Universe::from_laid_out_graph(graph_query.graph(), layout)?;
+        Some(universe)
+    } else {
+        None
+    };
+
+    let threads = args.threads.unwrap_or_else(num_cpus::get);
+    let rayon_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?;
+
+    std::fs::create_dir_all(&args.out_dir)?;
+
+    let runner = HeadlessRunner::new(
+        graph_query,
+        universe,
+        Arc::new(rayon_pool),
+        args.out_dir.clone(),
+    );
+
+    info!("running {}", args.script);
+    runner.run_file(&args.script)?;
+
+    Ok(())
+}
+
+fn main() {
+    let args: Args = argh::from_env();
+
+    let _logger = set_up_logger(&args).unwrap();
+
+    if let Err(err) = run(&args) {
+        error!("Error running {}", args.script);
+        error!("{:?}", err);
+        std::process::exit(1);
+    }
+}
@@ -23,6 +23,8 @@ use rustc_hash::FxHashSet;
 use crate::{
     app::{selection::NodeSelection, App, AppChannels, AppMsg, SharedState},
     geometry::{Point, Rect},
+    graph_query::fasta_string,
+    gui::GuiMsg,
     reactor::{ModalError, ModalHandler, ModalSuccess, Reactor},
 };
 
@@ -36,16 +38,49 @@ pub enum ContextEntry {
     },
 }
 
-// TODO this should be handled dynamically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContextKind {
+    Node,
+    Path,
+    Selection,
+}
+
+impl ContextKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "node" => Some(ContextKind::Node),
+            "path" => Some(ContextKind::Path),
+            "selection" => Some(ContextKind::Selection),
+            _ => None,
+        }
+    }
+}
+
+/// A context menu action that's registered at runtime, e.g. from a
+/// Rhai script. The callback is run on the reactor thread pool with
+/// the context entry the menu was opened on.
+#[derive(Clone)]
+pub struct CustomContextAction {
+    pub name: String,
+    pub kind: ContextKind,
+    pub callback: Arc<dyn Fn(ContextEntry) + Send + Sync + 'static>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ContextAction {
     CopyNodeId,
     CopyNodeSeq,
     CopyPathName,
+    CopyPathFasta,
     CopySubgraphGfa,
+    /// Prompts for a path, and copies the sequences of the selected
+    /// nodes along it
+    CopySelectionFasta,
     // CopySelection,
     // CopyPathNames,
     PanToNode,
+    /// Index into the custom actions registered with the context menu
+    Custom(usize),
 }
 
 #[derive(Debug, Default, Clone)]
@@ -62,6 +97,17 @@ impl Contexts {
     fn is_not_empty(&self) -> bool {
         self.node.is_some() || self.path.is_some() || self.has_selection
     }
+
+    fn entry(&self, kind: ContextKind) -> Option<ContextEntry> {
+        match kind {
+            ContextKind::Node => self.node.map(ContextEntry::Node),
+            ContextKind::Path => self.path.map(ContextEntry::Path),
+            ContextKind::Selection => self
+                .selection_nodes
+                .clone()
+                .map(|nodes| ContextEntry::Selection { nodes }),
+        }
+    }
 }
 
 pub struct ContextMenu {
@@ -73,6 +119,8 @@ pub struct ContextMenu {
     position: Arc<AtomicCell<Point>>,
 
     contexts: Contexts,
+
+    custom_actions: Vec<CustomContextAction>,
 }
 
 impl ContextMenu {
@@ -94,6 +142,7 @@ impl ContextMenu {
             shared_state,
             position: Arc::new(Point::ZERO.into()),
             contexts: Default::default(),
+            custom_actions: Vec::new(),
         }
     }
 
@@ -106,6 +155,8 @@ impl ContextMenu {
     }
 
     pub fn recv_contexts(&mut self) {
+        self.recv_custom_actions();
+
         self.contexts = Default::default();
 
         // TODO add combining step, maybe?
@@ -121,6 +172,23 @@ impl ContextMenu {
         }
     }
 
+    /// Adds the actions registered using the
+    /// `AppChannels::context_action_tx` channel; an action replaces
+    /// any existing action with the same name and context kind
+    fn recv_custom_actions(&mut self) {
+        while let Ok(action) = self.channels.context_action_rx.try_recv() {
+            if let Some(existing) = self
+                .custom_actions
+                .iter_mut()
+                .find(|a| a.name == action.name && a.kind == action.kind)
+            {
+                *existing = action;
+            } else {
+                self.custom_actions.push(action);
+            }
+        }
+    }
+
     fn process(
         &self,
         reactor: &Reactor,
@@ -156,6 +224,27 @@ impl ContextMenu {
                     }
                 }
             }
+            ContextAction::CopyPathFasta => {
+                if let Some(path) = contexts.path {
+                    // spelling a whole path can take a while, so it's
+                    // done off the GUI thread
+                    let graph_query = reactor.graph_query.clone();
+                    let gui_tx = self.channels.gui_tx.clone();
+
+                    reactor
+                        .spawn_forget(async move {
+                            if let Some(record) =
+                                graph_query.path_sequence(path, None)
+                            {
+                                let contents = fasta_string(&[record]);
+                                gui_tx
+                                    .send(GuiMsg::SetClipboard(contents))
+                                    .unwrap();
+                            }
+                        })
+                        .unwrap();
+                }
+            }
             ContextAction::CopySubgraphGfa => {
                 if let Some(nodes) = &contexts.selection_nodes {
                     let mut nodes = nodes.iter().copied().collect::<Vec<_>>();
@@ -193,6 +282,85 @@ impl ContextMenu {
                     log::warn!("selection has {} nodes", nodes.len());
                 }
             }
+            ContextAction::CopySelectionFasta => {
+                let nodes = if let Some(nodes) = &contexts.selection_nodes {
+                    nodes.clone()
+                } else {
+                    return;
+                };
+
+                let (result_tx, mut result_rx) =
+                    futures::channel::mpsc::channel::<Option<String>>(1);
+
+                let first_run = AtomicCell::new(true);
+
+                let callback = move |text: &mut String, ui: &mut egui::Ui| {
+                    ui.label("Enter path name");
+                    let text_box = ui.text_edit_singleline(text);
+
+                    if first_run.fetch_and(false) {
+                        text_box.request_focus();
+                    }
+
+                    if text_box.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter)
+                    {
+                        return Ok(ModalSuccess::Success);
+                    }
+
+                    Err(ModalError::Continue)
+                };
+
+                let prepared = ModalHandler::prepare_callback(
+                    &self.shared_state.show_modal,
+                    String::new(),
+                    callback,
+                    result_tx,
+                );
+
+                self.channels.modal_tx.send(prepared).unwrap();
+
+                let graph_query = reactor.graph_query.clone();
+                let gui_tx = self.channels.gui_tx.clone();
+
+                reactor
+                    .spawn_forget(async move {
+                        let name = if let Some(name) =
+                            result_rx.next().await.flatten()
+                        {
+                            name
+                        } else {
+                            return;
+                        };
+
+                        let path = graph_query
+                            .graph
+                            .get_path_id(name.trim().as_bytes());
+
+                        let records = path.and_then(|path| {
+                            graph_query.path_selection_sequences(path, &nodes)
+                        });
+
+                        match records {
+                            Some(records) if records.is_empty() => {
+                                log::warn!(
+                                    "Path {} doesn't visit the selection",
+                                    name
+                                );
+                            }
+                            Some(records) => {
+                                let contents = fasta_string(&records);
+                                gui_tx
+                                    .send(GuiMsg::SetClipboard(contents))
+                                    .unwrap();
+                            }
+                            None => {
+                                log::warn!("Path {} not found", name);
+                            }
+                        }
+                    })
+                    .unwrap();
+            }
             ContextAction::PanToNode => {
                 let (result_tx, mut result_rx) =
                     futures::channel::mpsc::channel::<Option<String>>(1);
@@ -243,6 +411,31 @@ impl ContextMenu {
                     })
                     .unwrap();
             }
+            ContextAction::Custom(ix) => {
+                if let Some(action) = self.custom_actions.get(ix) {
+                    if let Some(entry) = contexts.entry(action.kind) {
+                        let callback = action.callback.clone();
+                        reactor
+                            .spawn_forget(async move {
+                                callback(entry);
+                            })
+                            .unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    fn custom_action_buttons(
+        &self,
+        ui: &mut egui::Ui,
+        kind: ContextKind,
+        process: &mut impl FnMut(ContextAction),
+    ) {
+        for (ix, action) in self.custom_actions.iter().enumerate() {
+            if action.kind == kind && ui.button(&action.name).clicked() {
+                process(ContextAction::Custom(ix));
+            }
         }
     }
 
@@ -280,12 +473,26 @@ impl ContextMenu {
                                     {
                                         process(ContextAction::CopyNodeSeq);
                                     }
+                                    self.custom_action_buttons(
+                                        ui,
+                                        ContextKind::Node,
+                                        &mut process,
+                                    );
                                 }
 
                                 if let Some(_path) = self.contexts.path {
                                     if ui.button("Copy path name").clicked() {
                                         process(ContextAction::CopyPathName);
                                     }
+                                    if ui.button("Copy path as FASTA").clicked()
+                                    {
+                                        process(ContextAction::CopyPathFasta);
+                                    }
+                                    self.custom_action_buttons(
+                                        ui,
+                                        ContextKind::Path,
+                                        &mut process,
+                                    );
                                 }
 
                                 if self.contexts.has_selection {
@@ -295,6 +502,19 @@ impl ContextMenu {
                                     {
                                         process(ContextAction::CopySubgraphGfa);
                                     }
+                                    if ui
+                                        .button("Copy selection as FASTA")
+                                        .clicked()
+                                    {
+                                        process(
+                                            ContextAction::CopySelectionFasta,
+                                        );
+                                    }
+                                    self.custom_action_buttons(
+                                        ui,
+                                        ContextKind::Selection,
+                                        &mut process,
+                                    );
                                 }
 
                                 if ui.button("Pan to node").clicked() {
@@ -0,0 +1,7 @@
+pub mod png;
+pub mod svg;
+
+pub use self::png::*;
+pub use svg::*;
+
+pub const PNG_FILE_EXT: &str = "png";
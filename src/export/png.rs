@@ -0,0 +1,275 @@
+use std::path::Path;
+
+use anyhow::Result;
+use ash::version::DeviceV1_0;
+use ash::{vk, Device};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::app::{mainview::MainView, App};
+use crate::geometry::Point;
+use crate::view::View;
+use crate::vulkan::capture::CapturedFrame;
+use crate::vulkan::draw_system::edges::EdgeRenderer;
+use crate::vulkan::draw_system::selection::{
+    SelectionOutlineBlurPipeline, SelectionOutlineEdgePipeline,
+};
+use crate::vulkan::offscreen::OffscreenTarget;
+use crate::vulkan::texture::Gradients;
+use crate::vulkan::GfaestusVk;
+
+use super::PNG_FILE_EXT;
+
+/// Render the current view to a PNG of `width` x `height` pixels,
+/// covering the same width of the world as the window. The image is
+/// split into tiles no larger than the device supports, which are
+/// drawn offscreen with the same pipelines as the main view, except
+/// the GUI
+pub fn export_png(
+    path: &Path,
+    width: u32,
+    height: Option<u32>,
+    gfaestus: &GfaestusVk,
+    app: &App,
+    main_view: &mut MainView,
+    edge_renderer: &mut Option<EdgeRenderer>,
+    selection_edge: &mut SelectionOutlineEdgePipeline,
+    selection_blur: &mut SelectionOutlineBlurPipeline,
+    gradients: &Gradients,
+) -> Result<()> {
+    let path = if path.extension().is_none() {
+        path.with_extension(PNG_FILE_EXT)
+    } else {
+        path.to_owned()
+    };
+
+    let screen_dims = app.dims();
+    let view = main_view.view();
+
+    let width = width.max(1);
+    let height = height
+        .unwrap_or_else(|| {
+            (width as f32 * screen_dims.height / screen_dims.width) as u32
+        })
+        .max(1);
+
+    let scale = view.scale * screen_dims.width / width as f32;
+
+    let tile_size = gfaestus.max_offscreen_size();
+    let tile_width = width.min(tile_size);
+    let tile_height = height.min(tile_size);
+
+    let columns = (width + tile_width - 1) / tile_width;
+    let rows = (height + tile_height - 1) / tile_height;
+
+    info!(
+        "Rendering {}x{} image in {} tiles of {}x{}",
+        width,
+        height,
+        columns * rows,
+        tile_width,
+        tile_height
+    );
+
+    let mut target = OffscreenTarget::new(gfaestus, tile_width, tile_height)?;
+
+    let device = gfaestus.vk_context().device();
+
+    selection_edge
+        .write_descriptor_set(device, target.node_attachments.mask_resolve);
+    selection_blur
+        .write_descriptor_set(device, target.offscreen_attachment.color);
+
+    // nodes keep their width in world units from the window, and
+    // the edges their width relative to the nodes
+    let node_width = app.settings.node_width().width_at_scale(view.scale);
+
+    let edge_ubo = app.settings.edge_renderer().load();
+    let mut tile_edge_ubo = edge_ubo;
+    tile_edge_ubo.edge_width *= view.scale / scale;
+
+    for er in edge_renderer.iter_mut() {
+        er.write_ubo(&tile_edge_ubo)?;
+    }
+
+    let edges_enabled = app.shared_state().edges_enabled();
+
+    let overlay_state = app.shared_state().overlay_state();
+    let overlay = overlay_state.current_overlay();
+    let gradient = gradients.gradient(overlay_state.gradient()).unwrap();
+
+    let passes = &gfaestus.render_passes;
+
+    let tile_dims = target.dims();
+    let node_id_image = target.node_attachments.id_resolve.image;
+    let offscreen_image = target.offscreen_attachment.color.image;
+
+    let top_left =
+        view.center - Point::new(width as f32, height as f32) * (scale / 2.0);
+
+    let mut image = CapturedFrame::new(width, height);
+
+    let mut result = Ok(());
+
+    'tiles: for row in 0..rows {
+        for col in 0..columns {
+            let x = col * tile_width;
+            let y = row * tile_height;
+
+            let tile_center = Point::new(
+                x as f32 + tile_dims.width / 2.0,
+                y as f32 + tile_dims.height / 2.0,
+            );
+
+            let tile_view = View {
+                center: top_left + tile_center * scale,
+                scale,
+            };
+
+            let main_view = &mut *main_view;
+            let edge_renderer = &*edge_renderer;
+            let selection_edge = &*selection_edge;
+            let selection_blur = &*selection_blur;
+
+            let tile = gfaestus.draw_offscreen(
+                &mut target,
+                |device, cmd_buf, framebuffers| {
+                    attachment_barrier(
+                        device,
+                        cmd_buf,
+                        offscreen_image,
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                        vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    );
+
+                    main_view.draw_nodes_with_view(
+                        cmd_buf,
+                        passes.nodes,
+                        framebuffers,
+                        tile_dims.into(),
+                        tile_view,
+                        node_width,
+                        Point::ZERO,
+                        overlay,
+                        gradient,
+                    )?;
+
+                    if edges_enabled {
+                        for er in edge_renderer.iter() {
+                            er.draw(
+                                cmd_buf,
+                                tile_edge_ubo.edge_width,
+                                &main_view.node_draw_system.vertices,
+                                passes.edges,
+                                framebuffers,
+                                tile_dims.into(),
+                                2.0,
+                                tile_view,
+                                Point::ZERO,
+                            )?;
+                        }
+                    }
+
+                    attachment_barrier(
+                        device,
+                        cmd_buf,
+                        node_id_image,
+                        vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    );
+
+                    selection_edge.draw(
+                        device,
+                        cmd_buf,
+                        passes.selection_edge_detect,
+                        framebuffers,
+                        tile_dims.into(),
+                    )?;
+
+                    attachment_barrier(
+                        device,
+                        cmd_buf,
+                        offscreen_image,
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    );
+
+                    selection_blur.draw(
+                        device,
+                        cmd_buf,
+                        passes.selection_blur,
+                        framebuffers,
+                        tile_dims.into(),
+                    )?;
+
+                    Ok(())
+                },
+            );
+
+            match tile {
+                Ok(tile) => image.copy_from(&tile, x, y),
+                Err(err) => {
+                    result = Err(err);
+                    break 'tiles;
+                }
+            }
+        }
+    }
+
+    for er in edge_renderer.iter_mut() {
+        er.write_ubo(&edge_ubo)?;
+    }
+
+    selection_edge
+        .write_descriptor_set(device, gfaestus.node_attachments.mask_resolve);
+    selection_blur
+        .write_descriptor_set(device, gfaestus.offscreen_attachment.color);
+
+    target.destroy(device);
+
+    result?;
+
+    image.write_png(&path)?;
+
+    Ok(())
+}
+
+/// Records a barrier between the render passes that draw the main
+/// view, like the ones recorded when drawing to the swapchain
+fn attachment_barrier(
+    device: &Device,
+    cmd_buf: vk::CommandBuffer,
+    image: vk::Image,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) {
+    let barrier = vk::ImageMemoryBarrier::builder()
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_access_mask(vk::AccessFlags::SHADER_READ)
+        .old_layout(old_layout)
+        .new_layout(new_layout)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .build();
+
+    unsafe {
+        device.cmd_pipeline_barrier(
+            cmd_buf,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::BY_REGION,
+            &[],
+            &[],
+            &[barrier],
+        );
+    }
+}
@@ -0,0 +1,752 @@
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use handlegraph::{
+    handle::{Edge, NodeId},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+};
+use rustc_hash::FxHashMap;
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::app::{mainview::MainView, App};
+use crate::geometry::{Point, Rect};
+use crate::overlays::{OverlayData, OverlayLegend};
+use crate::universe::{FlatLayout, GraphLayout};
+use crate::view::{ScreenDims, View};
+use crate::vulkan::draw_system::edges::EdgesUBO;
+use crate::vulkan::texture::GradientName;
+
+pub const SVG_FILE_EXT: &str = "svg";
+
+/// The length of the longest side, in pixels, of an export of the
+/// entire graph
+pub const WHOLE_GRAPH_SVG_SIZE: f32 = 4096.0;
+
+/// The number of color stops used to draw a gradient in the legend
+const LEGEND_STOPS: usize = 16;
+
+/// The largest number of categories listed in the legend of a
+/// categorical overlay
+const LEGEND_MAX_CATEGORIES: usize = 24;
+
+const LABEL_FONT_SIZE: f32 = 12.0;
+
+/// The factor in `curve_modulation` in `shaders/edges/edges.tese` and
+/// `quads.tese`, which offset the line between the endpoints of an
+/// edge by `EDGE_CURVE_MODULATION * t * (1 - t)` times the endpoint
+/// difference rotated by 90 degrees. The shaders don't read
+/// `EdgesUBO::curve_offset`, so neither does the export
+const EDGE_CURVE_MODULATION: f32 = 0.8;
+
+/// How the nodes are colored in an SVG export
+#[derive(Debug, Clone)]
+pub enum SvgNodeColors {
+    Uniform(rgb::RGB<f32>),
+    Overlay {
+        name: String,
+        data: Arc<OverlayData>,
+        gradient: GradientName,
+        legend: Option<OverlayLegend>,
+    },
+}
+
+/// Exports the nodes, edges, and labels within a region of the
+/// world as SVG, with nodes drawn as stroked lines, and edges as the
+/// same curves the edge renderer draws.
+///
+/// Output coordinates are in pixels, with `scale` world units per
+/// pixel, the same as `View::scale`.
+#[derive(Debug, Clone)]
+pub struct SvgExport {
+    pub region: Rect,
+    pub scale: f32,
+
+    /// The node width in world units
+    pub node_width: f32,
+    pub node_colors: SvgNodeColors,
+
+    /// The edge color and width; edges are not drawn if `None`
+    pub edges: Option<EdgesUBO>,
+
+    pub background: rgb::RGB<f32>,
+    pub text_color: rgb::RGB<f32>,
+
+    /// Labels and their positions in world coordinates; labels at
+    /// the same position are stacked
+    pub labels: Vec<(Point, String)>,
+
+    /// Draw a legend for the node colors
+    pub legend: bool,
+}
+
+impl SvgExport {
+    pub fn new(region: Rect, scale: f32) -> Self {
+        Self {
+            region,
+            scale,
+
+            node_width: 5.0,
+            node_colors: SvgNodeColors::Uniform(rgb::RGB::new(0.2, 0.2, 0.2)),
+
+            edges: Some(EdgesUBO::default()),
+
+            background: rgb::RGB::new(1.0, 1.0, 1.0),
+            text_color: rgb::RGB::new(0.0, 0.0, 0.0),
+
+            labels: Vec::new(),
+
+            legend: true,
+        }
+    }
+
+    /// Export the part of the world visible in `view`
+    pub fn from_view<Dims: Into<ScreenDims>>(view: View, dims: Dims) -> Self {
+        let dims = dims.into();
+
+        let region = Rect::new(
+            view.screen_point_to_world(dims, Point::ZERO),
+            view.screen_point_to_world(
+                dims,
+                Point::new(dims.width, dims.height),
+            ),
+        );
+
+        Self::new(region, view.scale)
+    }
+
+    /// Export the entire layout, scaled so that its longest side is
+    /// `WHOLE_GRAPH_SVG_SIZE` pixels
+    pub fn from_layout<L: GraphLayout>(layout: &L, node_width: f32) -> Self {
+        let (top_left, bottom_right) = layout.bounding_box();
+
+        let pad = Point::new(node_width, node_width);
+        let region = Rect::new(top_left - pad, bottom_right + pad);
+
+        let longest = region.width().max(region.height()).max(1.0);
+
+        let mut export = Self::new(region, longest / WHOLE_GRAPH_SVG_SIZE);
+        export.node_width = node_width;
+        export
+    }
+
+    fn to_svg(&self, world: Point) -> Point {
+        (world - self.region.min()) / self.scale
+    }
+
+    pub fn write_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        layout: &FlatLayout,
+        graph: &PackedGraph,
+    ) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut out = BufWriter::new(file);
+
+        self.write(&mut out, layout, graph)?;
+        out.flush()?;
+
+        Ok(())
+    }
+
+    pub fn write<W: Write>(
+        &self,
+        mut out: W,
+        layout: &FlatLayout,
+        graph: &PackedGraph,
+    ) -> Result<()> {
+        let width = (self.region.width() / self.scale).ceil().max(1.0);
+        let height = (self.region.height() / self.scale).ceil().max(1.0);
+
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        )?;
+
+        writeln!(
+            out,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            svg_color(self.background)
+        )?;
+
+        if let Some(edges) = self.edges {
+            self.write_edges(&mut out, layout, graph, &edges)?;
+        }
+
+        self.write_nodes(&mut out, layout)?;
+
+        if !self.labels.is_empty() {
+            self.write_labels(&mut out)?;
+        }
+
+        if self.legend {
+            self.write_legend(&mut out)?;
+        }
+
+        writeln!(out, "</svg>")?;
+
+        Ok(())
+    }
+
+    fn node_color(&self, node_ix: usize) -> (rgb::RGB<f32>, f32) {
+        match &self.node_colors {
+            SvgNodeColors::Uniform(color) => (*color, 1.0),
+            SvgNodeColors::Overlay { data, gradient, .. } => {
+                match data.as_ref() {
+                    OverlayData::RGB(colors) => colors
+                        .get(node_ix)
+                        .map(|c| (rgb::RGB::new(c.r, c.g, c.b), c.a))
+                        .unwrap_or((rgb::RGB::new(0.0, 0.0, 0.0), 1.0)),
+                    OverlayData::Value(values) => {
+                        let value = values.get(node_ix).copied().unwrap_or(0.0);
+                        (gradient_color(*gradient, value), 1.0)
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_nodes<W: Write>(
+        &self,
+        out: &mut W,
+        layout: &FlatLayout,
+    ) -> Result<()> {
+        let margin = Point::new(self.node_width, self.node_width);
+        let visible =
+            Rect::new(self.region.min() - margin, self.region.max() + margin);
+
+        writeln!(
+            out,
+            r#"<g id="nodes" stroke-width="{:.2}" stroke-linecap="butt">"#,
+            self.node_width / self.scale
+        )?;
+
+        for (ix, node) in layout.nodes().iter().enumerate() {
+            if !visible.intersects(Rect::new(node.p0, node.p1)) {
+                continue;
+            }
+
+            let p0 = self.to_svg(node.p0);
+            let p1 = self.to_svg(node.p1);
+
+            let (color, alpha) = self.node_color(ix);
+
+            write!(
+                out,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}""#,
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                svg_color(color)
+            )?;
+
+            if alpha < 1.0 {
+                write!(out, r#" stroke-opacity="{:.3}""#, alpha)?;
+            }
+
+            writeln!(out, "/>")?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        Ok(())
+    }
+
+    fn write_edges<W: Write>(
+        &self,
+        out: &mut W,
+        layout: &FlatLayout,
+        graph: &PackedGraph,
+        edges: &EdgesUBO,
+    ) -> Result<()> {
+        let nodes = layout.nodes();
+
+        let node_end = |id: NodeId, right: bool| -> Option<Point> {
+            let node = nodes.get((id.0 - 1) as usize)?;
+            Some(if right { node.p1 } else { node.p0 })
+        };
+
+        writeln!(
+            out,
+            r#"<g id="edges" fill="none" stroke="{}" stroke-width="{:.2}">"#,
+            svg_color(edges.edge_color),
+            edges.edge_width
+        )?;
+
+        for Edge(left, right) in graph.edges() {
+            // edges between components aren't drawn by the renderer
+            let left_comp = layout.node_component(left.id());
+            let right_comp = layout.node_component(right.id());
+
+            if left_comp != right_comp {
+                continue;
+            }
+
+            // same endpoints as `EdgeIndices`
+            let p = node_end(left.id(), !left.is_reverse());
+            let q = node_end(right.id(), right.is_reverse());
+
+            let (p, q) = match (p, q) {
+                (Some(p), Some(q)) => (p, q),
+                _ => continue,
+            };
+
+            let ctrl = edge_control_point(p, q);
+
+            let bounds = Rect::new(p, q).union(Rect::new(ctrl, ctrl));
+
+            if !self.region.intersects(bounds) {
+                continue;
+            }
+
+            let p = self.to_svg(p);
+            let q = self.to_svg(q);
+            let ctrl = self.to_svg(ctrl);
+
+            writeln!(
+                out,
+                r#"<path d="M{:.2},{:.2} Q{:.2},{:.2} {:.2},{:.2}"/>"#,
+                p.x, p.y, ctrl.x, ctrl.y, q.x, q.y
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        Ok(())
+    }
+
+    fn write_labels<W: Write>(&self, out: &mut W) -> Result<()> {
+        writeln!(
+            out,
+            r#"<g id="labels" font-family="sans-serif" font-size="{}" text-anchor="middle" fill="{}">"#,
+            LABEL_FONT_SIZE,
+            svg_color(self.text_color)
+        )?;
+
+        let mut stacked: FxHashMap<(u32, u32), usize> = FxHashMap::default();
+
+        for (point, text) in self.labels.iter() {
+            if !self.region.contains(*point) {
+                continue;
+            }
+
+            let count = stacked
+                .entry((point.x.to_bits(), point.y.to_bits()))
+                .or_default();
+
+            let pos = self.to_svg(*point);
+            let y = pos.y - (*count as f32 * (LABEL_FONT_SIZE + 2.0));
+
+            *count += 1;
+
+            writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}">{}</text>"#,
+                pos.x,
+                y,
+                escape_xml(text)
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        Ok(())
+    }
+
+    fn write_legend<W: Write>(&self, out: &mut W) -> Result<()> {
+        let (name, gradient, legend) = match &self.node_colors {
+            SvgNodeColors::Uniform(_) => return Ok(()),
+            SvgNodeColors::Overlay {
+                name,
+                data,
+                gradient,
+                legend,
+            } => {
+                let gradient = match data.as_ref() {
+                    OverlayData::Value(_) => Some(*gradient),
+                    OverlayData::RGB(_) => None,
+                };
+                (name, gradient, legend.as_ref())
+            }
+        };
+
+        let categories = match (gradient, legend) {
+            (None, Some(OverlayLegend::Categories(categories))) => {
+                categories.as_slice()
+            }
+            _ => &[][..],
+        };
+
+        let shown_categories = categories.len().min(LEGEND_MAX_CATEGORIES);
+        let hidden_categories = categories.len() - shown_categories;
+
+        let (x, y) = (16.0, 16.0);
+        let (bar_width, bar_height) = (160.0, 12.0);
+
+        let row_height = LABEL_FONT_SIZE + 4.0;
+
+        let box_height = if gradient.is_some() {
+            58.0
+        } else {
+            let rows = shown_categories + (hidden_categories > 0) as usize;
+            28.0 + rows as f32 * row_height
+        };
+
+        writeln!(
+            out,
+            r#"<g id="legend" font-family="sans-serif" font-size="{}" fill="{}">"#,
+            LABEL_FONT_SIZE,
+            svg_color(self.text_color)
+        )?;
+
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.8"/>"#,
+            x - 8.0,
+            y - 8.0,
+            bar_width + 16.0,
+            box_height,
+            svg_color(self.background)
+        )?;
+
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}">{}</text>"#,
+            x,
+            y + LABEL_FONT_SIZE,
+            escape_xml(name)
+        )?;
+
+        if let Some(gradient) = gradient {
+            writeln!(out, r#"<defs><linearGradient id="legend_gradient">"#)?;
+
+            for i in 0..LEGEND_STOPS {
+                let t = i as f32 / (LEGEND_STOPS - 1) as f32;
+                writeln!(
+                    out,
+                    r#"<stop offset="{:.3}" stop-color="{}"/>"#,
+                    t,
+                    svg_color(gradient_color(gradient, t))
+                )?;
+            }
+
+            writeln!(out, "</linearGradient></defs>")?;
+
+            let bar_y = y + LABEL_FONT_SIZE + 4.0;
+
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#legend_gradient)"/>"#,
+                x, bar_y, bar_width, bar_height
+            )?;
+
+            let text_y = bar_y + bar_height + LABEL_FONT_SIZE;
+
+            let (min, max) = match legend {
+                Some(OverlayLegend::Range { min, max, units }) => (
+                    OverlayLegend::format_value(*min, units),
+                    OverlayLegend::format_value(*max, units),
+                ),
+                _ => ("0".to_string(), "1".to_string()),
+            };
+
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                x,
+                text_y,
+                escape_xml(&min)
+            )?;
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                x + bar_width,
+                text_y,
+                escape_xml(&max)
+            )?;
+        }
+
+        let mut row_y = y + LABEL_FONT_SIZE + 4.0;
+
+        for (color, value) in categories.iter().take(shown_categories) {
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x,
+                row_y + 2.0,
+                bar_height,
+                bar_height,
+                svg_color(rgb::RGB::new(color.r, color.g, color.b))
+            )?;
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                x + bar_height + 6.0,
+                row_y + LABEL_FONT_SIZE,
+                escape_xml(value)
+            )?;
+            row_y += row_height;
+        }
+
+        if hidden_categories > 0 {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}">and {} more</text>"#,
+                x,
+                row_y + LABEL_FONT_SIZE,
+                hidden_categories
+            )?;
+        }
+
+        writeln!(out, "</g>")?;
+
+        Ok(())
+    }
+}
+
+/// Exports the current view, or the entire graph, to an SVG file at
+/// `path`, with the node colors, edges, and labels shown in the
+/// viewer
+pub fn export_svg(
+    path: &Path,
+    whole_graph: bool,
+    app: &App,
+    main_view: &MainView,
+    layout: &FlatLayout,
+    graph: &PackedGraph,
+) -> Result<()> {
+    let path = if path.extension().is_none() {
+        path.with_extension(SVG_FILE_EXT)
+    } else {
+        path.to_owned()
+    };
+
+    let shared_state = app.shared_state();
+    let settings = &app.settings;
+
+    let view = main_view.view();
+    let node_width = settings.node_width().width_at_scale(view.scale);
+
+    let mut export = if whole_graph {
+        SvgExport::from_layout(layout, node_width)
+    } else {
+        let mut export = SvgExport::from_view(view, shared_state.screen_dims());
+        export.node_width = node_width;
+        export
+    };
+
+    let dark_mode = shared_state.dark_mode().load();
+
+    if dark_mode {
+        export.background = settings.background_color_dark().load();
+        export.text_color = rgb::RGB::new(1.0, 1.0, 1.0);
+        export.node_colors =
+            SvgNodeColors::Uniform(rgb::RGB::new(0.8, 0.8, 0.8));
+    } else {
+        export.background = settings.background_color_light().load();
+    }
+
+    if shared_state.edges_enabled() {
+        export.edges = Some(settings.edge_renderer().load());
+    } else {
+        export.edges = None;
+    }
+
+    let overlay_state = shared_state.overlay_state();
+
+    if let Some(overlay_id) = overlay_state.current_overlay() {
+        let pipelines = &main_view.node_draw_system.pipelines;
+
+        let name = pipelines
+            .overlay_names()
+            .into_iter()
+            .find(|(id, _, _)| *id == overlay_id)
+            .map(|(_, _, name)| name.to_string());
+
+        if let (Some(name), Some(data)) =
+            (name, overlay_state.normalized_overlay_data(overlay_id))
+        {
+            let normalization = overlay_state.overlay_normalization(overlay_id);
+            let legend = overlay_state
+                .overlay_legend(overlay_id)
+                .map(|legend| legend.normalized(normalization));
+
+            export.node_colors = SvgNodeColors::Overlay {
+                name,
+                data,
+                gradient: overlay_state.gradient(),
+                legend,
+            };
+        }
+    }
+
+    let nodes = layout.nodes();
+
+    for label_set in app.annotations().visible_label_sets() {
+        let label_strings = label_set.label_strings();
+
+        for (node_id, label_indices) in label_set.labels() {
+            if let Some(node) = nodes.get((node_id.0 - 1) as usize) {
+                let pos = node.center();
+
+                for &ix in label_indices {
+                    export.labels.push((pos, label_strings[ix].clone()));
+                }
+            }
+        }
+    }
+
+    export.write_file(&path, layout, graph)?;
+
+    Ok(())
+}
+
+/// The color a value overlay maps `value` to; values are expected to
+/// be normalized to `0.0..=1.0`
+fn gradient_color(gradient: GradientName, value: f32) -> rgb::RGB<f32> {
+    let value = if value.is_finite() {
+        value.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    let color = gradient.gradient().eval_continuous(value as f64);
+
+    rgb::RGB::new(
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+    )
+}
+
+/// The control point of the quadratic bezier that follows the curve
+/// the edge shaders draw from `p` to `q`
+fn edge_control_point(p: Point, q: Point) -> Point {
+    let diff = q - p;
+    let normal = Point::new(-diff.y, diff.x);
+
+    // the bezier is offset from the line by `2 * t * (1 - t)` times
+    // the distance from the midpoint to the control point
+    p + (diff / 2.0) + normal * (EDGE_CURVE_MODULATION / 2.0)
+}
+
+fn svg_color(color: rgb::RGB<f32>) -> String {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: Point, b: Point) -> bool {
+        a.dist(b) < 1.0e-5
+    }
+
+    #[test]
+    fn xml_special_characters_are_escaped() {
+        assert_eq!(escape_xml("chr1:100-200"), "chr1:100-200");
+        assert_eq!(
+            escape_xml(r#"<a & 'b' "c">"#),
+            "&lt;a &amp; &apos;b&apos; &quot;c&quot;&gt;"
+        );
+        assert_eq!(escape_xml("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn colors_are_clamped_and_rounded() {
+        assert_eq!(svg_color(rgb::RGB::new(0.0, 0.0, 0.0)), "#000000");
+        assert_eq!(svg_color(rgb::RGB::new(1.0, 0.5, 0.0)), "#ff8000");
+        assert_eq!(svg_color(rgb::RGB::new(2.0, -1.0, 0.2)), "#ff0033");
+    }
+
+    #[test]
+    fn labels_at_the_same_point_are_stacked() {
+        let region = Rect::new(Point::ZERO, Point::new(100.0, 100.0));
+        let mut export = SvgExport::new(region, 1.0);
+
+        export.labels = vec![
+            (Point::new(10.0, 50.0), "a".to_string()),
+            (Point::new(10.0, 50.0), "b".to_string()),
+            (Point::new(20.0, 50.0), "c".to_string()),
+            (Point::new(10.0, 50.0), "d".to_string()),
+            (Point::new(200.0, 50.0), "outside".to_string()),
+        ];
+
+        let mut out = Vec::new();
+        export.write_labels(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let texts = out
+            .lines()
+            .filter(|line| line.starts_with("<text"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            texts,
+            vec![
+                r#"<text x="10.00" y="50.00">a</text>"#,
+                r#"<text x="10.00" y="36.00">b</text>"#,
+                r#"<text x="20.00" y="50.00">c</text>"#,
+                r#"<text x="10.00" y="22.00">d</text>"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn edge_control_point_matches_the_shader_curve() {
+        let p = Point::new(0.0, 0.0);
+        let q = Point::new(10.0, 0.0);
+
+        let ctrl = edge_control_point(p, q);
+        assert!(approx_eq(ctrl, Point::new(5.0, 4.0)));
+
+        // the bezier and the shader agree at every point of the curve
+        let normal = Point::new(0.0, 10.0);
+
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+
+            let bezier = p * ((1.0 - t) * (1.0 - t))
+                + ctrl * (2.0 * t * (1.0 - t))
+                + q * (t * t);
+
+            let shader = p * (1.0 - t)
+                + q * t
+                + normal * (EDGE_CURVE_MODULATION * t * (1.0 - t));
+
+            assert!(approx_eq(bezier, shader));
+        }
+    }
+}
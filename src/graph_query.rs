@@ -21,6 +21,12 @@ use anyhow::Result;
 
 use crate::asynchronous::AsyncResult;
 
+pub mod sequence;
+pub mod stats;
+
+pub use sequence::{fasta_string, write_fasta, SequenceRecord};
+pub use stats::{GraphStatistics, Histogram, NodeStat};
+
 pub struct GraphQueryWorker {
     graph_query: Arc<GraphQuery>,
     thread_pool: ThreadPool,
@@ -337,3 +343,34 @@ pub enum GraphQueryResp {
     //     right: Vec<NodeId>,
     // },
 }
+
+/// Builds a small graph for tests, from node IDs and sequences,
+/// edges, and paths given as their steps
+#[cfg(test)]
+pub(crate) fn test_graph(
+    nodes: &[(u64, &str)],
+    edges: &[(Handle, Handle)],
+    paths: &[(&str, &[Handle])],
+) -> GraphQuery {
+    use handlegraph::handle::Edge;
+
+    let mut graph = PackedGraph::new();
+
+    for &(id, seq) in nodes {
+        graph.create_handle(seq.as_bytes(), id);
+    }
+
+    for &(from, to) in edges {
+        graph.create_edge(Edge(from, to));
+    }
+
+    for &(name, steps) in paths {
+        let path = graph.create_path(name.as_bytes(), false).unwrap();
+        for &handle in steps {
+            graph.path_append_step(path, handle);
+        }
+    }
+
+    let path_positions = PathPositionMap::index_paths(&graph);
+    GraphQuery::new(graph, path_positions)
+}
@@ -0,0 +1,296 @@
+use handlegraph::{
+    handle::{Handle, NodeId},
+    handlegraph::*,
+    packedgraph::paths::StepPtr,
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+use rustc_hash::FxHashSet;
+
+use std::io::Write;
+
+use super::GraphQuery;
+
+/// The number of bases on each sequence line of FASTA output
+pub const FASTA_LINE_WIDTH: usize = 60;
+
+/// A named sequence, e.g. a path or a range along a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceRecord {
+    pub name: String,
+    pub sequence: Vec<u8>,
+}
+
+impl SequenceRecord {
+    pub fn write_fasta<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        writeln!(out, ">{}", self.name)?;
+
+        for line in self.sequence.chunks(FASTA_LINE_WIDTH) {
+            out.write_all(line)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn write_fasta<W: Write>(
+    records: &[SequenceRecord],
+    mut out: W,
+) -> std::io::Result<()> {
+    for record in records {
+        record.write_fasta(&mut out)?;
+    }
+    Ok(())
+}
+
+pub fn fasta_string(records: &[SequenceRecord]) -> String {
+    let mut out = Vec::new();
+    // writing to a Vec can't fail
+    write_fasta(records, &mut out).unwrap();
+    out.to_str_lossy().into_owned()
+}
+
+/// Names a range of a path like `name:start-end`, with a one-based,
+/// inclusive start and end
+fn region_name(path_name: &str, start: usize, end: usize) -> String {
+    format!("{}:{}-{}", path_name, start + 1, end)
+}
+
+impl GraphQuery {
+    /// The sequence spelled by the path, with the steps on reverse
+    /// handles reverse complemented. If `range` is given, only the
+    /// bases in `start..end` along the path are included
+    pub fn path_sequence(
+        &self,
+        path: PathId,
+        range: Option<(usize, usize)>,
+    ) -> Option<SequenceRecord> {
+        let name = self.graph.get_path_name_vec(path)?;
+        let name = name.to_str_lossy();
+
+        let steps = self.path_pos_steps(path)?;
+        let path_len = self.graph.path_bases_len(path)?;
+
+        if let Some((start, end)) = range {
+            let end = end.min(path_len);
+            let start = start.min(end);
+
+            let sequence = self.spell_range(&steps, start, end);
+
+            Some(SequenceRecord {
+                name: region_name(&name, start, end),
+                sequence,
+            })
+        } else {
+            let sequence = self.spell_range(&steps, 0, path_len);
+
+            Some(SequenceRecord {
+                name: name.into_owned(),
+                sequence,
+            })
+        }
+    }
+
+    /// The sequences of the runs of consecutive path steps on the
+    /// given nodes, in path order, one record per run
+    pub fn path_selection_sequences(
+        &self,
+        path: PathId,
+        nodes: &FxHashSet<NodeId>,
+    ) -> Option<Vec<SequenceRecord>> {
+        let name = self.graph.get_path_name_vec(path)?;
+        let name = name.to_str_lossy();
+
+        let steps = self.path_pos_steps(path)?;
+        let path_len = self.graph.path_bases_len(path)?;
+
+        // the base ranges of the runs of selected steps
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        let mut run_start: Option<usize> = None;
+
+        for &(handle, _, pos) in steps.iter() {
+            let selected = nodes.contains(&handle.id());
+
+            match (selected, run_start) {
+                (true, None) => run_start = Some(pos),
+                (false, Some(start)) => {
+                    ranges.push((start, pos));
+                    run_start = None;
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(start) = run_start {
+            ranges.push((start, path_len));
+        }
+
+        let records = ranges
+            .into_iter()
+            .map(|(start, end)| SequenceRecord {
+                name: region_name(&name, start, end),
+                sequence: self.spell_range(&steps, start, end),
+            })
+            .collect();
+
+        Some(records)
+    }
+
+    fn spell_range(
+        &self,
+        steps: &[(Handle, StepPtr, usize)],
+        start: usize,
+        end: usize,
+    ) -> Vec<u8> {
+        let mut sequence = Vec::with_capacity(end.saturating_sub(start));
+
+        // the last step starting at or before `start`
+        let first = match steps.binary_search_by_key(&start, |(_, _, p)| *p) {
+            Ok(ix) => ix,
+            Err(ix) => ix.saturating_sub(1),
+        };
+
+        for &(handle, _, pos) in steps[first..].iter() {
+            if pos >= end {
+                break;
+            }
+
+            let node_seq = self.graph.sequence_vec(handle);
+
+            let from = start.saturating_sub(pos).min(node_seq.len());
+            let to = (end - pos).min(node_seq.len());
+
+            sequence.extend_from_slice(&node_seq[from..to]);
+        }
+
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graph_query::test_graph;
+
+    fn fwd(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), false)
+    }
+
+    fn rev(id: u64) -> Handle {
+        Handle::pack(NodeId::from(id), true)
+    }
+
+    // the path spells ACGT + TCC (GGA reverse complemented) + TTTAC
+    fn graph() -> (GraphQuery, PathId) {
+        let graph = test_graph(
+            &[(1, "ACGT"), (2, "GGA"), (3, "TTTAC")],
+            &[(fwd(1), rev(2)), (rev(2), fwd(3))],
+            &[("p", &[fwd(1), rev(2), fwd(3)])],
+        );
+        let path = graph.graph().get_path_id(b"p").unwrap();
+        (graph, path)
+    }
+
+    fn sequence(
+        graph: &GraphQuery,
+        path: PathId,
+        range: Option<(usize, usize)>,
+    ) -> (String, String) {
+        let record = graph.path_sequence(path, range).unwrap();
+        (record.name, record.sequence.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn whole_path_is_spelled_with_reverse_steps_complemented() {
+        let (graph, path) = graph();
+
+        assert_eq!(
+            sequence(&graph, path, None),
+            ("p".to_string(), "ACGTTCCTTTAC".to_string())
+        );
+    }
+
+    #[test]
+    fn ranges_can_start_and_end_mid_node() {
+        let (graph, path) = graph();
+
+        let seq = |start, end| sequence(&graph, path, Some((start, end)));
+
+        assert_eq!(seq(2, 9), ("p:3-9".to_string(), "GTTCCTT".to_string()));
+        assert_eq!(seq(4, 7), ("p:5-7".to_string(), "TCC".to_string()));
+        assert_eq!(seq(5, 6), ("p:6-6".to_string(), "C".to_string()));
+        assert_eq!(
+            seq(0, 12),
+            ("p:1-12".to_string(), "ACGTTCCTTTAC".to_string())
+        );
+
+        // the end is clamped to the path length
+        assert_eq!(seq(10, 100), ("p:11-12".to_string(), "AC".to_string()));
+    }
+
+    #[test]
+    fn empty_ranges_have_no_sequence() {
+        let (graph, path) = graph();
+
+        let seq = |start, end| sequence(&graph, path, Some((start, end))).1;
+
+        assert_eq!(seq(5, 5), "");
+        assert_eq!(seq(0, 0), "");
+        assert_eq!(seq(12, 12), "");
+        assert_eq!(seq(8, 4), "");
+    }
+
+    #[test]
+    fn selection_runs_are_separate_records() {
+        let (graph, path) = graph();
+
+        let records = |nodes: &[u64]| {
+            let nodes = nodes.iter().map(|&n| NodeId::from(n)).collect();
+            graph
+                .path_selection_sequences(path, &nodes)
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.name, r.sequence.to_str().unwrap().to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            records(&[2]),
+            vec![("p:5-7".to_string(), "TCC".to_string())]
+        );
+        assert_eq!(
+            records(&[1, 3]),
+            vec![
+                ("p:1-4".to_string(), "ACGT".to_string()),
+                ("p:8-12".to_string(), "TTTAC".to_string()),
+            ]
+        );
+        assert!(records(&[4]).is_empty());
+    }
+
+    #[test]
+    fn fasta_lines_are_wrapped() {
+        let record = SequenceRecord {
+            name: "seq".to_string(),
+            sequence: vec![b'A'; FASTA_LINE_WIDTH * 2 + 10],
+        };
+
+        let fasta = fasta_string(&[record]);
+        let lines = fasta.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], ">seq");
+        assert_eq!(lines[1].len(), FASTA_LINE_WIDTH);
+        assert_eq!(lines[2].len(), FASTA_LINE_WIDTH);
+        assert_eq!(lines[3].len(), 10);
+
+        let empty = SequenceRecord {
+            name: "empty".to_string(),
+            sequence: Vec::new(),
+        };
+        assert_eq!(fasta_string(&[empty]), ">empty\n");
+    }
+}
@@ -0,0 +1,446 @@
+use handlegraph::{
+    handle::{Direction, Edge, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+use rustc_hash::FxHashSet;
+
+use super::GraphQuery;
+
+/// A histogram with power of two bins: bin 0 holds the zeroes, and
+/// bin `b` the values from `2^(b-1)` up to `2^b - 1`
+#[derive(Debug, Default, Clone)]
+pub struct Histogram {
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn from_values(values: &[usize]) -> Self {
+        let mut counts = Vec::new();
+
+        for &value in values {
+            let bin = Self::bin_of(value);
+
+            if bin >= counts.len() {
+                counts.resize(bin + 1, 0);
+            }
+
+            counts[bin] += 1;
+        }
+
+        Self { counts }
+    }
+
+    pub fn bin_of(value: usize) -> usize {
+        if value == 0 {
+            0
+        } else {
+            (64 - (value as u64).leading_zeros()) as usize
+        }
+    }
+
+    /// The smallest and largest values in the bin, inclusive
+    pub fn bin_range(bin: usize) -> (usize, usize) {
+        if bin == 0 {
+            (0, 0)
+        } else {
+            (1 << (bin - 1), (1 << bin) - 1)
+        }
+    }
+
+    pub fn bin_label(bin: usize) -> String {
+        let (min, max) = Self::bin_range(bin);
+
+        if min == max {
+            min.to_string()
+        } else {
+            format!("{}-{}", min, max)
+        }
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeStat {
+    Length,
+    Degree,
+    /// The number of path steps on the node
+    Depth,
+}
+
+impl NodeStat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeStat::Length => "Node length",
+            NodeStat::Degree => "Degree",
+            NodeStat::Depth => "Coverage depth",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PathStatistics {
+    pub path: PathId,
+    pub name: String,
+    pub step_count: usize,
+    pub base_count: usize,
+}
+
+/// Summary statistics and distributions of the nodes, edges, paths,
+/// and connected components of a graph
+#[derive(Debug, Clone)]
+pub struct GraphStatistics {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub total_len: usize,
+
+    pub n50: usize,
+    pub mean_len: f64,
+    pub max_len: usize,
+
+    /// Nodes with no edges on at least one side
+    pub tips: usize,
+    pub self_loops: usize,
+    /// Edges that connect a forward handle to a reverse handle
+    pub inverting_edges: usize,
+
+    /// The number of nodes in each connected component, largest
+    /// first
+    pub component_sizes: Vec<usize>,
+
+    pub paths: Vec<PathStatistics>,
+
+    /// The IDs of the nodes, sorted; the per-node statistics below
+    /// are in the same order
+    node_ids: Vec<NodeId>,
+
+    lengths: Vec<usize>,
+    degrees: Vec<usize>,
+    depths: Vec<usize>,
+
+    length_hist: Histogram,
+    degree_hist: Histogram,
+    depth_hist: Histogram,
+}
+
+impl GraphStatistics {
+    pub fn compute(graph_query: &GraphQuery) -> Self {
+        let graph = graph_query.graph();
+
+        let mut handles = graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        let node_count = handles.len();
+        let node_ids = handles.iter().map(|h| h.id()).collect::<Vec<_>>();
+
+        let mut lengths = Vec::with_capacity(node_count);
+        let mut degrees = Vec::with_capacity(node_count);
+        let mut depths = Vec::with_capacity(node_count);
+
+        let mut tips = 0;
+
+        for &handle in handles.iter() {
+            lengths.push(graph.node_len(handle));
+
+            let left = graph.degree(handle, Direction::Left);
+            let right = graph.degree(handle, Direction::Right);
+            degrees.push(left + right);
+
+            if left == 0 || right == 0 {
+                tips += 1;
+            }
+
+            let depth = graph
+                .steps_on_handle(handle)
+                .map(|steps| steps.count())
+                .unwrap_or(0);
+            depths.push(depth);
+        }
+
+        let total_len = lengths.iter().sum::<usize>();
+
+        let n50 = {
+            let mut sorted = lengths.clone();
+            sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut sum = 0;
+            sorted
+                .into_iter()
+                .find(|len| {
+                    sum += len;
+                    sum * 2 >= total_len
+                })
+                .unwrap_or(0)
+        };
+
+        let mean_len = if node_count == 0 {
+            0.0
+        } else {
+            total_len as f64 / node_count as f64
+        };
+
+        let max_len = lengths.iter().copied().max().unwrap_or(0);
+
+        let mut edge_count = 0;
+        let mut self_loops = 0;
+        let mut inverting_edges = 0;
+
+        let mut components = UnionFind::new(node_count);
+
+        for Edge(a, b) in graph.edges() {
+            edge_count += 1;
+
+            if a.id() == b.id() {
+                self_loops += 1;
+            }
+
+            if a.is_reverse() != b.is_reverse() {
+                inverting_edges += 1;
+            }
+
+            let a = node_ids.binary_search(&a.id());
+            let b = node_ids.binary_search(&b.id());
+
+            if let (Ok(a), Ok(b)) = (a, b) {
+                components.union(a, b);
+            }
+        }
+
+        let mut component_sizes = components.set_sizes();
+        component_sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut paths = graph
+            .path_ids()
+            .filter_map(|path| {
+                let name = graph.get_path_name_vec(path)?;
+
+                Some(PathStatistics {
+                    path,
+                    name: name.to_str_lossy().into_owned(),
+                    step_count: graph.path_len(path)?,
+                    base_count: graph.path_bases_len(path)?,
+                })
+            })
+            .collect::<Vec<_>>();
+        paths.sort_by_key(|path| path.path.0);
+
+        let length_hist = Histogram::from_values(&lengths);
+        let degree_hist = Histogram::from_values(&degrees);
+        let depth_hist = Histogram::from_values(&depths);
+
+        Self {
+            node_count,
+            edge_count,
+            total_len,
+
+            n50,
+            mean_len,
+            max_len,
+
+            tips,
+            self_loops,
+            inverting_edges,
+
+            component_sizes,
+
+            paths,
+
+            node_ids,
+            lengths,
+            degrees,
+            depths,
+
+            length_hist,
+            degree_hist,
+            depth_hist,
+        }
+    }
+
+    fn values(&self, stat: NodeStat) -> &[usize] {
+        match stat {
+            NodeStat::Length => &self.lengths,
+            NodeStat::Degree => &self.degrees,
+            NodeStat::Depth => &self.depths,
+        }
+    }
+
+    pub fn histogram(&self, stat: NodeStat) -> &Histogram {
+        match stat {
+            NodeStat::Length => &self.length_hist,
+            NodeStat::Degree => &self.degree_hist,
+            NodeStat::Depth => &self.depth_hist,
+        }
+    }
+
+    /// The nodes whose value of `stat` falls in the histogram bin
+    pub fn nodes_in_bin(
+        &self,
+        stat: NodeStat,
+        bin: usize,
+    ) -> FxHashSet<NodeId> {
+        self.values(stat)
+            .iter()
+            .enumerate()
+            .filter(|(_, &value)| Histogram::bin_of(value) == bin)
+            .map(|(ix, _)| self.node_ids[ix])
+            .collect()
+    }
+}
+
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut ix: usize) -> usize {
+        while self.parents[ix] != ix {
+            self.parents[ix] = self.parents[self.parents[ix]];
+            ix = self.parents[ix];
+        }
+        ix
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let a = self.find(a);
+        let b = self.find(b);
+
+        if a == b {
+            return;
+        }
+
+        let (large, small) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+
+        self.parents[small] = large;
+        self.sizes[large] += self.sizes[small];
+    }
+
+    /// The sizes of the disjoint sets
+    fn set_sizes(&mut self) -> Vec<usize> {
+        let mut sizes = Vec::new();
+
+        for ix in 0..self.parents.len() {
+            if self.find(ix) == ix {
+                sizes.push(self.sizes[ix]);
+            }
+        }
+
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use handlegraph::handle::Handle;
+
+    use crate::graph_query::test_graph;
+
+    #[test]
+    fn histogram_bins_are_powers_of_two() {
+        assert_eq!(Histogram::bin_of(0), 0);
+        assert_eq!(Histogram::bin_of(1), 1);
+        assert_eq!(Histogram::bin_of(2), 2);
+        assert_eq!(Histogram::bin_of(3), 2);
+        assert_eq!(Histogram::bin_of(4), 3);
+        assert_eq!(Histogram::bin_of(7), 3);
+        assert_eq!(Histogram::bin_of(8), 4);
+
+        assert_eq!(Histogram::bin_range(0), (0, 0));
+        assert_eq!(Histogram::bin_range(1), (1, 1));
+        assert_eq!(Histogram::bin_range(3), (4, 7));
+
+        for bin in 0..32 {
+            let (min, max) = Histogram::bin_range(bin);
+            assert_eq!(Histogram::bin_of(min), bin);
+            assert_eq!(Histogram::bin_of(max), bin);
+            assert_eq!(Histogram::bin_of(max + 1), bin + 1);
+        }
+
+        let hist = Histogram::from_values(&[0, 1, 3, 2, 9]);
+        assert_eq!(hist.counts, vec![1, 1, 2, 0, 1]);
+        assert_eq!(hist.max_count(), 2);
+    }
+
+    // the node IDs have gaps, so they can't be used as indices
+    fn graph() -> GraphQuery {
+        let fwd = |id: u64| Handle::pack(NodeId::from(id), false);
+        let rev = |id: u64| Handle::pack(NodeId::from(id), true);
+
+        test_graph(
+            &[(2, "ACGTA"), (5, "ACGT"), (9, "ACG"), (12, "AC")],
+            &[(fwd(2), fwd(5)), (fwd(9), rev(12))],
+            &[],
+        )
+    }
+
+    #[test]
+    fn graph_statistics() {
+        let stats = GraphStatistics::compute(&graph());
+
+        assert_eq!(stats.node_count, 4);
+        assert_eq!(stats.edge_count, 2);
+        assert_eq!(stats.total_len, 14);
+        assert_eq!(stats.max_len, 5);
+        assert_eq!(stats.tips, 4);
+        assert_eq!(stats.self_loops, 0);
+        assert_eq!(stats.inverting_edges, 1);
+
+        // 5 + 4 is the first sum that covers half of the 14 bases
+        assert_eq!(stats.n50, 4);
+    }
+
+    #[test]
+    fn component_sizes_with_node_id_gaps() {
+        let stats = GraphStatistics::compute(&graph());
+        assert_eq!(stats.component_sizes, vec![2, 2]);
+
+        let fwd = |id: u64| Handle::pack(NodeId::from(id), false);
+
+        let stats = GraphStatistics::compute(&test_graph(
+            &[(3, "A"), (7, "A"), (8, "A"), (20, "A")],
+            &[(fwd(3), fwd(20)), (fwd(20), fwd(7))],
+            &[],
+        ));
+        assert_eq!(stats.component_sizes, vec![3, 1]);
+    }
+
+    #[test]
+    fn nodes_in_bin_uses_node_ids() {
+        let stats = GraphStatistics::compute(&graph());
+
+        let ids = |ids: &[u64]| {
+            ids.iter()
+                .map(|&id| NodeId::from(id))
+                .collect::<FxHashSet<_>>()
+        };
+
+        // lengths 4 to 7
+        assert_eq!(stats.nodes_in_bin(NodeStat::Length, 3), ids(&[2, 5]));
+        // lengths 2 and 3
+        assert_eq!(stats.nodes_in_bin(NodeStat::Length, 2), ids(&[9, 12]));
+        assert!(stats.nodes_in_bin(NodeStat::Length, 1).is_empty());
+
+        assert_eq!(
+            stats.nodes_in_bin(NodeStat::Degree, 1),
+            ids(&[2, 5, 9, 12])
+        );
+    }
+}
@@ -26,6 +26,7 @@ use crate::{
     context::ContextEntry,
     graph_query::GraphQueryWorker,
     reactor::Reactor,
+    universe::{GraphLayout, Node},
     vulkan::{render_pass::Framebuffers, texture::Gradients},
 };
 use crate::{app::OverlayState, geometry::*};
@@ -38,6 +39,7 @@ use crate::input::binds::{
     BindableInput, KeyBind, MouseButtonBind, SystemInput, SystemInputBindings,
     WheelBind,
 };
+use crate::input::InputBindings;
 
 use crate::vulkan::{draw_system::gui::GuiPipeline, GfaestusVk};
 
@@ -94,6 +96,18 @@ pub struct Gui {
 
     annotation_file_list: AnnotationFileList,
 
+    session_window: SessionWindow,
+    export_window: ExportWindow,
+    bookmarks_window: BookmarksWindow,
+    minimap_window: MinimapWindow,
+    ruler_window: RulerWindow,
+    path_highlight_window: PathHighlightWindow,
+    path_matrix_window: PathMatrixWindow,
+    path_comparison_window: PathComparisonWindow,
+    dotplot_window: DotplotWindow,
+    statistics_window: StatisticsWindow,
+    validation_window: ValidationWindow,
+
     pub console: Console<'static>,
     console_down: bool,
 }
@@ -192,6 +206,7 @@ impl AppViewState {
         settings: &AppSettings,
         shared_state: &SharedState,
         overlay_state: OverlayState,
+        input_bindings: InputBindings,
         _dropped_file: Arc<std::sync::Mutex<Option<PathBuf>>>,
     ) -> Self {
         let graph = graph_query.graph();
@@ -203,7 +218,8 @@ impl AppViewState {
             total_len: graph.total_length(),
         };
 
-        let settings = SettingsWindow::new(settings, shared_state);
+        let settings =
+            SettingsWindow::new(settings, shared_state, input_bindings);
 
         let node_details_state = NodeDetails::default();
         let node_id_cell = node_details_state.node_id_cell().clone();
@@ -226,7 +242,7 @@ impl AppViewState {
         let path_list =
             ViewStateChannel::<PathList, PathListMsg>::new(path_list_state);
 
-        let overlay_list_state = OverlayList::new(overlay_state);
+        let overlay_list_state = OverlayList::new(reactor, overlay_state);
         let overlay_list = ViewStateChannel::<OverlayList, OverlayListMsg>::new(
             overlay_list_state,
         );
@@ -298,6 +314,17 @@ impl AppViewState {
 #[derive(Debug, Clone, Copy)]
 pub struct OpenWindows {
     settings: bool,
+    session: bool,
+    export: bool,
+    bookmarks: bool,
+    minimap: bool,
+    ruler: bool,
+    path_highlights: bool,
+    path_matrix: bool,
+    path_comparison: bool,
+    dotplot: bool,
+    statistics: bool,
+    validation: bool,
 
     annotation_files: bool,
     annotation_records: bool,
@@ -318,6 +345,17 @@ impl std::default::Default for OpenWindows {
     fn default() -> Self {
         Self {
             settings: false,
+            session: false,
+            export: false,
+            bookmarks: false,
+            minimap: false,
+            ruler: false,
+            path_highlights: false,
+            path_matrix: false,
+            path_comparison: false,
+            dotplot: false,
+            statistics: false,
+            validation: false,
 
             annotation_files: false,
             annotation_records: false,
@@ -347,6 +385,9 @@ pub enum GuiMsg {
     Cut,
     Copy,
     Paste,
+    /// Replaces the clipboard contents, for results computed off the
+    /// GUI thread
+    SetClipboard(String),
 
     // TODO this shouldn't really be here, as things like the console
     // will never update the modifiers
@@ -382,6 +423,7 @@ impl Gui {
         shared_state: SharedState,
         channels: &AppChannels,
         settings: AppSettings,
+        input_bindings: InputBindings,
         graph_query: &Arc<GraphQuery>,
     ) -> Result<Self> {
         let render_pass = app.render_passes.gui;
@@ -425,15 +467,47 @@ impl Gui {
             &settings,
             &shared_state,
             shared_state.overlay_state().clone(),
+            input_bindings,
             dropped_file.clone(),
         );
 
-        let menu_bar = MenuBar::new(shared_state.overlay_state().clone());
+        let menu_bar = MenuBar::new(
+            shared_state.overlay_state().clone(),
+            channels.main_view_tx.clone(),
+        );
 
         let clipboard_ctx = ClipboardProvider::new().unwrap();
 
         let mut path_picker_source = PathPickerSource::new(graph_query)?;
 
+        let session_window = SessionWindow::new(channels.session_tx.clone());
+        let export_window = ExportWindow::new(channels.export_tx.clone());
+        let bookmarks_window = BookmarksWindow::new(
+            shared_state.clone(),
+            channels.main_view_tx.clone(),
+        );
+        let minimap_window = MinimapWindow::new(shared_state.clone());
+        let ruler_window = RulerWindow::new(path_picker_source.create_picker());
+        let path_highlight_window = PathHighlightWindow::new(
+            path_picker_source.create_picker(),
+            channels.path_highlight_tx.clone(),
+            channels.path_highlight_rx.clone(),
+        );
+        let path_matrix_window =
+            PathMatrixWindow::new(reactor, channels.app_tx.clone());
+        let path_comparison_window = PathComparisonWindow::new(
+            reactor,
+            path_picker_source.create_picker(),
+        );
+        let dotplot_window = DotplotWindow::new(
+            reactor,
+            path_picker_source.create_picker(),
+            path_picker_source.create_picker(),
+            channels.app_tx.clone(),
+        );
+        let statistics_window = StatisticsWindow::new(channels.app_tx.clone());
+        let validation_window = ValidationWindow::new(channels.app_tx.clone());
+
         let annotation_file_list = AnnotationFileList::new(
             reactor,
             app_msg_tx.clone(),
@@ -510,6 +584,18 @@ impl Gui {
 
             annotation_file_list,
 
+            session_window,
+            export_window,
+            bookmarks_window,
+            minimap_window,
+            ruler_window,
+            path_highlight_window,
+            path_matrix_window,
+            path_comparison_window,
+            dotplot_window,
+            statistics_window,
+            validation_window,
+
             console_down: false,
             console,
         };
@@ -525,6 +611,53 @@ impl Gui {
         self.hover_node_id = node;
     }
 
+    /// Update the overview minimap with the node positions of the
+    /// layout
+    pub fn set_minimap_layout<L: GraphLayout>(&mut self, layout: &L) {
+        self.minimap_window.set_layout(layout);
+    }
+
+    /// Validate the loaded graph in the background, opening the
+    /// validation window if any problems are found
+    pub fn validate_graph(
+        &mut self,
+        graph_query_worker: &GraphQueryWorker,
+        missing_layout: Vec<NodeId>,
+    ) {
+        self.validation_window
+            .validate(graph_query_worker, missing_layout);
+    }
+
+    /// Draw the scale bar and the reference path ruler, if enabled
+    pub fn draw_ruler(
+        &mut self,
+        graph_query: &GraphQuery,
+        nodes: &[Node],
+        bp_per_world_unit: f32,
+    ) {
+        self.ruler_window.draw(
+            &self.ctx,
+            &self.shared_state,
+            graph_query,
+            nodes,
+            bp_per_world_unit,
+        );
+    }
+
+    /// Draw the highlighted paths on top of the graph
+    pub fn draw_path_highlights(
+        &mut self,
+        graph_query: &GraphQuery,
+        nodes: &[Node],
+    ) {
+        self.path_highlight_window.draw(
+            &self.ctx,
+            &self.shared_state,
+            graph_query,
+            nodes,
+        );
+    }
+
     pub fn app_view_state(&self) -> &AppViewState {
         &self.view_state
     }
@@ -559,6 +692,42 @@ impl Gui {
             .scroll_to_label_record(records, column, value);
     }
 
+    /// The annotation record filters in use, as `(file type, file
+    /// name, column, filter fields)`
+    pub fn annotation_filter_fields(
+        &self,
+    ) -> Vec<(AnnotationFileType, String, String, Vec<String>)> {
+        let gff3 = self
+            .gff3_list
+            .filter_fields()
+            .into_iter()
+            .map(|(f, c, v)| (AnnotationFileType::Gff3, f, c, v));
+
+        let bed = self
+            .bed_list
+            .filter_fields()
+            .into_iter()
+            .map(|(f, c, v)| (AnnotationFileType::Bed, f, c, v));
+
+        gff3.chain(bed).collect()
+    }
+
+    pub fn restore_annotation_filter(
+        &mut self,
+        file_type: AnnotationFileType,
+        file_name: &str,
+        columns: Vec<(String, Vec<String>)>,
+    ) {
+        match file_type {
+            AnnotationFileType::Gff3 => {
+                self.gff3_list.restore_filter(file_name, columns)
+            }
+            AnnotationFileType::Bed => {
+                self.bed_list.restore_filter(file_name, columns)
+            }
+        }
+    }
+
     pub fn scroll_to_bed_record(
         &mut self,
         records: &BedRecords,
@@ -634,7 +803,14 @@ impl Gui {
                 .state
                 .ui(&self.ctx, overlay_creator);
 
+            for report in
+                view_state.overlay_creator.state.take_console_reports()
+            {
+                self.console.append_output(&report);
+            }
+
             view_state.overlay_list.state.gradient_picker_ui(&self.ctx);
+            view_state.overlay_list.state.legend_ui(&self.ctx);
         }
 
         if let Some(rect) = self.shared_state.active_mouse_rect_screen() {
@@ -726,6 +902,42 @@ impl Gui {
             .settings
             .ui(&self.ctx, &mut self.open_windows.settings);
 
+        self.session_window
+            .ui(&self.ctx, &mut self.open_windows.session);
+
+        self.export_window
+            .ui(&self.ctx, &mut self.open_windows.export);
+
+        self.bookmarks_window
+            .ui(&self.ctx, &mut self.open_windows.bookmarks);
+
+        self.minimap_window
+            .ui(&self.ctx, &mut self.open_windows.minimap);
+
+        self.ruler_window
+            .ui(&self.ctx, &mut self.open_windows.ruler);
+
+        self.path_highlight_window
+            .ui(&self.ctx, &mut self.open_windows.path_highlights);
+
+        self.path_matrix_window
+            .ui(&self.ctx, &mut self.open_windows.path_matrix);
+
+        self.path_comparison_window
+            .ui(&self.ctx, &mut self.open_windows.path_comparison);
+
+        self.dotplot_window
+            .ui(&self.ctx, &mut self.open_windows.dotplot);
+
+        self.statistics_window.ui(
+            &self.ctx,
+            &mut self.open_windows.statistics,
+            graph_query_worker,
+        );
+
+        self.validation_window
+            .ui(&self.ctx, &mut self.open_windows.validation);
+
         if view_state.settings.gui.show_fps {
             let top = self.menu_bar.height();
             view_state.fps.state.ui(
@@ -762,6 +974,7 @@ impl Gui {
                     &self.app_msg_tx,
                     node_details,
                     graph_query,
+                    graph_query_worker,
                     ctx_tx,
                 );
             }
@@ -804,6 +1017,8 @@ impl Gui {
                     node_details_id_cell,
                     node_details,
                     &self.app_msg_tx,
+                    self.path_highlight_window.sender(),
+                    self.hover_node_id,
                     ctx_tx,
                 );
             }
@@ -970,6 +1185,9 @@ impl Gui {
                             .push(egui::Event::Text(text.clone()));
                     }
                 }
+                GuiMsg::SetClipboard(text) => {
+                    let _ = self.clipboard_ctx.set_contents(text);
+                }
                 GuiMsg::SetModifiers(mods) => {
                     let modifiers = egui::Modifiers {
                         alt: mods.alt(),
@@ -1133,6 +1351,21 @@ pub enum GuiInput {
 }
 
 impl BindableInput for GuiInput {
+    fn all_inputs() -> Vec<Self> {
+        use GuiInput as Input;
+        vec![
+            Input::KeyEguiInspectionUi,
+            Input::KeyEguiSettingsUi,
+            Input::KeyEguiMemoryUi,
+            Input::ButtonLeft,
+            Input::ButtonRight,
+            Input::WheelScroll,
+            Input::KeyToggleConsole,
+            Input::KeyConsoleDown,
+            Input::KeyConsoleUp,
+        ]
+    }
+
     fn default_binds() -> SystemInputBindings<Self> {
         use winit::event;
         use winit::event::VirtualKeyCode as Key;
@@ -23,17 +23,20 @@ use bstr::ByteSlice;
 
 use crate::{
     annotations::{
-        AnnotationCollection, AnnotationRecord, Annotations, BedColumn,
-        BedRecord, BedRecords, ColumnKey, Gff3Column, Gff3Record, Gff3Records,
+        AnnotationCollection, Annotations, BedColumn, BedRecords, ColumnKey,
+        Gff3Column, Gff3Records,
     },
-    overlays::OverlayKind,
+    overlays::{BuiltinOverlay, OverlayKind},
     reactor::{ModalError, ModalHandler, ModalSuccess},
 };
 use crate::{
     app::{
-        selection::NodeSelection, AppChannels, AppMsg, OverlayCreatorMsg,
-        Select,
+        selection::NodeSelection,
+        tour::{Tour, TourStep},
+        AppChannels, AppMsg, ExportMsg, OverlayCreatorMsg, PathHighlightMsg,
+        Select, TourMsg,
     },
+    context::{ContextEntry, ContextKind, CustomContextAction},
     geometry::*,
     quad_tree::*,
     reactor::Reactor,
@@ -42,7 +45,7 @@ use crate::{
 };
 use crate::{
     app::{AppSettings, SharedState},
-    graph_query::GraphQuery,
+    graph_query::{fasta_string, GraphQuery},
 };
 
 use parking_lot::Mutex;
@@ -86,9 +89,11 @@ pub struct Console<'a> {
 
     rayon_pool: Arc<rayon::ThreadPool>,
 
-    // TODO this shouldn't be a Vec, and it should probably use an
-    // RwLock or something inside
-    window_defs: Arc<Mutex<Vec<ConsoleGuiDsl>>>,
+    // Destroyed windows leave a `None` in their slot, so that the
+    // indices returned by `new_window` stay valid
+    //
+    // TODO it should probably use an RwLock or something inside
+    window_defs: Arc<Mutex<Vec<Option<ConsoleGuiDsl>>>>,
 
     future_tx: crossbeam::channel::Sender<
         Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>,
@@ -270,7 +275,7 @@ impl Console<'static> {
         let output_history =
             vec![" < close this console with Esc >".to_string()];
 
-        let key_code_map = Arc::new(virtual_key_code_map());
+        let key_code_map = Arc::new(crate::input::virtual_key_code_map());
 
         let overlay_list = Arc::new(Mutex::new(Vec::new()));
 
@@ -395,7 +400,7 @@ impl Console<'static> {
         let mut engine = shared.create_engine();
 
         // Bind a Rhai function to execute when the given key is
-        // pressed. See the virtual_key_code_map() function below for
+        // pressed. See the virtual_key_code_map() function in `input` for
         // which keys are available.
         //
         // `fn_name` must be the name of a function that is part of
@@ -462,6 +467,101 @@ impl Console<'static> {
             },
         );
 
+        // Add an entry to the context menu, shown when the menu is
+        // opened on a node, path, or selection, depending on the
+        // `context` argument ("node", "path", or "selection").
+        //
+        // When clicked, the function `fn_name` is called with the
+        // node ID, path ID, or node selection, respectively. As with
+        // `bind_key`, the function must be in the console API or an
+        // imported module.
+        let modules = self.modules.clone();
+        let shared = self.shared();
+        let context_action_tx = self.channels.context_action_tx.clone();
+        let result_tx = self.result_tx.clone();
+        engine.register_result_fn(
+            "add_context_action",
+            move |context: &str,
+                  name: &str,
+                  fn_name: &str|
+                  -> std::result::Result<(), Box<EvalAltResult>> {
+                let kind = ContextKind::parse(context).ok_or_else(|| {
+                    format!(
+                        "Unknown context `{}`, must be one of \"node\", \
+                         \"path\", or \"selection\"",
+                        context
+                    )
+                })?;
+
+                let scope = Self::create_scope();
+
+                let mut engine = shared.create_engine();
+                {
+                    let modules = modules.lock();
+                    for module in modules.iter() {
+                        engine.register_global_module(module.clone());
+                    }
+                }
+
+                let script =
+                    format!("fn a_function(entry) {{\n{}(entry);\n}}", fn_name);
+                let ast = engine.compile_with_scope(&scope, &script)?;
+
+                let function =
+                    rhai::Func::<(rhai::Dynamic,), ()>::create_from_ast(
+                        engine,
+                        ast,
+                        "a_function",
+                    );
+
+                let result_tx = result_tx.clone();
+                let callback = move |entry: ContextEntry| {
+                    let arg = match entry {
+                        ContextEntry::Node(node) => rhai::Dynamic::from(node),
+                        ContextEntry::Path(path) => rhai::Dynamic::from(path),
+                        ContextEntry::Selection { nodes } => {
+                            rhai::Dynamic::from(NodeSelection { nodes })
+                        }
+                    };
+
+                    if let Err(err) = function(arg) {
+                        log::warn!("context action error: {:?}", err);
+                        let _ = result_tx.send(Err(err));
+                    }
+                };
+
+                let action = CustomContextAction {
+                    name: name.to_string(),
+                    kind,
+                    callback: Arc::new(callback),
+                };
+
+                context_action_tx.send(action).unwrap();
+
+                Ok(())
+            },
+        );
+
+        self.add_overlay_fn(&mut engine, false);
+
+        self.add_gui_dsl_fns(&mut engine);
+
+        {
+            let modules = self.modules.lock();
+
+            for module in modules.iter() {
+                engine.register_global_module(module.clone());
+            }
+        }
+
+        engine
+    }
+
+    /// Registers `create_overlay_from_fn`. If `replace` is true, an
+    /// existing overlay with the same name has its data replaced
+    /// instead of a new overlay being added, so watched scripts can
+    /// be re-run without creating duplicate overlays
+    fn add_overlay_fn(&self, engine: &mut rhai::Engine, replace: bool) {
         let rayon_pool = self.rayon_pool.clone();
         let graph = self.graph.clone();
         let config = ScriptConfig {
@@ -470,6 +570,7 @@ impl Console<'static> {
         };
 
         let overlay_tx = self.channels.new_overlay_tx.clone();
+        let result_tx = self.result_tx.clone();
         let shared = self.shared();
         let modules = self.modules.clone();
         engine.register_fn(
@@ -509,44 +610,42 @@ impl Console<'static> {
                             );
 
                             match result {
-                                Ok(data) => {
-                                    let msg = OverlayCreatorMsg::NewOverlay {
-                                        name: name.to_string(),
-                                        data,
+                                Ok((data, legend)) => {
+                                    let name = name.to_string();
+                                    let msg = if replace {
+                                        OverlayCreatorMsg::ReplaceOverlay {
+                                            name,
+                                            data,
+                                            legend,
+                                        }
+                                    } else {
+                                        OverlayCreatorMsg::NewOverlay {
+                                            name,
+                                            data,
+                                            legend,
+                                        }
                                     };
                                     overlay_tx.send(msg).unwrap();
                                     log::info!("overlay data success");
                                 }
-                                Err(_err) => {
+                                Err(err) => {
                                     log::warn!("overlay failure");
+                                    let _ = result_tx.send(Err(err));
                                 }
                             }
                         }
-                        Err(_err) => {
+                        Err(err) => {
                             log::warn!("ast failure");
+                            let _ = result_tx.send(Err(err.into()));
                         }
                     }
                 }
             },
         );
-
-        self.add_gui_dsl_fns(&mut engine);
-
-        {
-            let modules = self.modules.lock();
-
-            for module in modules.iter() {
-                engine.register_global_module(module.clone());
-            }
-        }
-
-        engine
     }
 
     fn add_gui_dsl_fns(&self, engine: &mut rhai::Engine) {
         // create a new window with the provided title, and return the index of the window
-        //
-        // NB: there's no way yet to remove or hide windows created here
         let window_defs = self.window_defs.clone();
         engine.register_fn("new_window", move |title: &str| {
             let mut win_defs = window_defs.lock();
@@ -557,7 +656,7 @@ impl Console<'static> {
                 egui::Id::new(format!("{}-win_def_dsl-{}", title, ix,)),
             );
 
-            win_defs.push(window);
+            win_defs.push(Some(window));
 
             ix as i64
         });
@@ -567,7 +666,7 @@ impl Console<'static> {
         engine.register_fn("add_label", move |ix: i64, text: &str| {
             let mut win_defs = window_defs.lock();
 
-            if let Some(window) = win_defs.get_mut(ix as usize) {
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
                 window.elements.push(ConsoleGuiElem::Label {
                     text: text.to_string(),
                 });
@@ -580,7 +679,7 @@ impl Console<'static> {
             move |ix: i64, text: &str, callback_id: &str| {
                 let mut win_defs = window_defs.lock();
 
-                if let Some(window) = win_defs.get_mut(ix as usize) {
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
                     window.elements.push(ConsoleGuiElem::Button {
                         text: text.to_string(),
                         callback_id: callback_id.to_string(),
@@ -593,7 +692,7 @@ impl Console<'static> {
         engine.register_fn("add_text_edit", move |ix: i64, data_id: &str| {
             let mut win_defs = window_defs.lock();
 
-            if let Some(window) = win_defs.get_mut(ix as usize) {
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
                 window.elements.push(ConsoleGuiElem::TextInput {
                     label: "".to_string(),
                     data_id: data_id.to_string(),
@@ -609,7 +708,7 @@ impl Console<'static> {
             move |ix: i64, data_id: &str| {
                 let mut win_defs = window_defs.lock();
 
-                if let Some(window) = win_defs.get_mut(ix as usize) {
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
                     if let Some(contents) = window.get_text_data(data_id) {
                         return Ok(rhai::Dynamic::from(contents.to_string()));
                     }
@@ -628,7 +727,7 @@ impl Console<'static> {
             move |ix: i64, callback_id: &str, fn_name: &str| {
                 let mut win_defs = window_defs.lock();
 
-                if let Some(window) = win_defs.get_mut(ix as usize) {
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
                     let scope = Self::create_scope();
                     let mut engine = shared.create_engine();
                     {
@@ -670,6 +769,188 @@ impl Console<'static> {
                 }
             },
         );
+
+        // The widgets below are bound to a variable in the same store
+        // as the `get`/`set` and `get_var`/`set_var` functions; if
+        // `var` isn't an app setting, it's a console variable, which
+        // is initialized if it doesn't exist yet
+
+        // add a slider over the range `min..=max`; if the bound
+        // variable is an integer, so is the slider
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn(
+            "add_slider",
+            move |ix: i64, label: &str, var: &str, min: f32, max: f32| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                    get_set.init_var(var, rhai::Dynamic::from(min));
+                    window.elements.push(ConsoleGuiElem::Slider {
+                        label: label.to_string(),
+                        var: var.to_string(),
+                        min,
+                        max,
+                    });
+                }
+            },
+        );
+
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn(
+            "add_slider",
+            move |ix: i64, label: &str, var: &str, min: i64, max: i64| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                    get_set.init_var(var, rhai::Dynamic::from(min));
+                    window.elements.push(ConsoleGuiElem::Slider {
+                        label: label.to_string(),
+                        var: var.to_string(),
+                        min: min as f32,
+                        max: max as f32,
+                    });
+                }
+            },
+        );
+
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn(
+            "add_checkbox",
+            move |ix: i64, label: &str, var: &str| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                    get_set.init_var(var, rhai::Dynamic::from(false));
+                    window.elements.push(ConsoleGuiElem::Checkbox {
+                        label: label.to_string(),
+                        var: var.to_string(),
+                    });
+                }
+            },
+        );
+
+        // add a dropdown with the provided options, which are
+        // converted to strings; the bound variable holds the selected
+        // option, and is initialized to the first one
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn(
+            "add_combo_box",
+            move |ix: i64, label: &str, var: &str, options: rhai::Array| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                    let options = options
+                        .into_iter()
+                        .map(|opt| opt.to_string())
+                        .collect::<Vec<_>>();
+
+                    if let Some(first) = options.first() {
+                        get_set
+                            .init_var(var, rhai::Dynamic::from(first.clone()));
+                    }
+
+                    window.elements.push(ConsoleGuiElem::ComboBox {
+                        label: label.to_string(),
+                        var: var.to_string(),
+                        options,
+                    });
+                }
+            },
+        );
+
+        // the bound variable can be either an RGB or RGBA color
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn(
+            "add_color_picker",
+            move |ix: i64, label: &str, var: &str| {
+                let mut win_defs = window_defs.lock();
+
+                if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                    let white = rgb::RGBA::new(1.0f32, 1.0, 1.0, 1.0);
+                    get_set.init_var(var, rhai::Dynamic::from(white));
+                    window.elements.push(ConsoleGuiElem::ColorPicker {
+                        label: label.to_string(),
+                        var: var.to_string(),
+                    });
+                }
+            },
+        );
+
+        // the plots show the array of numbers in the bound variable,
+        // which is read every frame
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn("add_bar_plot", move |ix: i64, var: &str| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                get_set.init_var(var, rhai::Dynamic::from(rhai::Array::new()));
+                window.elements.push(ConsoleGuiElem::Plot {
+                    var: var.to_string(),
+                    kind: PlotKind::Bar,
+                });
+            }
+        });
+
+        let window_defs = self.window_defs.clone();
+        let get_set = self.get_set.clone();
+        engine.register_fn("add_line_plot", move |ix: i64, var: &str| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                get_set.init_var(var, rhai::Dynamic::from(rhai::Array::new()));
+                window.elements.push(ConsoleGuiElem::Plot {
+                    var: var.to_string(),
+                    kind: PlotKind::Line,
+                });
+            }
+        });
+
+        // hidden windows keep their elements and callbacks, and can be
+        // shown again; windows can also be hidden using their close
+        // button
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("hide_window", move |ix: i64| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                window.open = false;
+            }
+        });
+
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("show_window", move |ix: i64| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(window) = window_def_mut(&mut win_defs, ix) {
+                window.open = true;
+            }
+        });
+
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("window_is_open", move |ix: i64| {
+            let mut win_defs = window_defs.lock();
+
+            window_def_mut(&mut win_defs, ix)
+                .map(|window| window.open)
+                .unwrap_or(false)
+        });
+
+        // destroying a window drops its callbacks; the index is not
+        // reused
+        let window_defs = self.window_defs.clone();
+        engine.register_fn("destroy_window", move |ix: i64| {
+            let mut win_defs = window_defs.lock();
+
+            if let Some(slot) = win_defs.get_mut(ix as usize) {
+                *slot = None;
+            }
+        });
     }
 
     pub fn eval_input(
@@ -767,6 +1048,73 @@ impl Console<'static> {
         self.remote_handles.remove(handle_name);
     }
 
+    /// Evaluates the script at `path`, and then re-evaluates it every
+    /// time the file is modified, polling every 500ms. The result,
+    /// including any compile errors, is shown in the console output.
+    ///
+    /// Overlays created with `create_overlay_from_fn` replace the
+    /// previous versions, rather than being added as new overlays.
+    pub fn watch_file(
+        &mut self,
+        reactor: &mut Reactor,
+        path: &str,
+    ) -> Result<()> {
+        let handle_name = format!("watch:{}", path);
+
+        let mut engine = self.create_engine();
+        self.add_overlay_fn(&mut engine, true);
+
+        let scope = self.scope.clone();
+        let result_tx = self.result_tx.clone();
+
+        let path = PathBuf::from(path);
+        let mut last_modified: Option<std::time::SystemTime> = None;
+
+        let handle = reactor.spawn_interval(
+            move || {
+                let modified = std::fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .ok();
+
+                if modified.is_none() || modified == last_modified {
+                    return;
+                }
+                last_modified = modified;
+
+                let script = match std::fs::read_to_string(&path) {
+                    Ok(script) => script,
+                    Err(err) => {
+                        let msg = format!(
+                            "Error reading '{}': {:?}",
+                            path.display(),
+                            err
+                        );
+                        let _ = result_tx.send(Err(msg.into()));
+                        return;
+                    }
+                };
+
+                let msg = format!(">>> Evaluating file '{}'", path.display());
+                let _ = result_tx.send(Ok(rhai::Dynamic::from(msg)));
+
+                let mut scope = scope.lock();
+                let result = engine
+                    .eval_with_scope::<rhai::Dynamic>(&mut scope, &script);
+                let _ = result_tx.send(result);
+            },
+            std::time::Duration::from_millis(500),
+        )?;
+
+        self.remote_handles.insert(handle_name, handle);
+
+        Ok(())
+    }
+
+    pub fn unwatch_file(&mut self, path: &str) {
+        let handle_name = format!("watch:{}", path);
+        self.remote_handles.remove(&handle_name);
+    }
+
     // NB: edit this to add new console commands that do *not* use the Rhai engine
     fn exec_console_command(
         &mut self,
@@ -841,6 +1189,19 @@ impl Console<'static> {
                 self.eval_file_interval(reactor, &handle, &file)?;
             }
 
+            return Ok(true);
+        } else if input.starts_with(":watch ") {
+            // Evaluate the provided script, and re-evaluate it every
+            // time the file changes
+            let file_path = &self.input_line[":watch ".len()..].to_string();
+            self.watch_file(reactor, file_path.trim())?;
+
+            return Ok(true);
+        } else if input.starts_with(":unwatch ") {
+            // see `:watch`
+            let file_path = &self.input_line[":unwatch ".len()..].to_string();
+            self.unwatch_file(file_path.trim());
+
             return Ok(true);
         } else if input.starts_with(":end_interval ") {
             // see `:start_interval`
@@ -945,8 +1306,8 @@ impl Console<'static> {
         {
             let mut win_defs = self.window_defs.lock();
 
-            for win_def in win_defs.iter_mut() {
-                win_def.show(ctx);
+            for win_def in win_defs.iter_mut().flatten() {
+                win_def.show(ctx, &self.get_set);
             }
         }
 
@@ -1220,6 +1581,37 @@ impl GetSetTruth {
         self.getters.insert(name.to_string(), Box::new(getter) as _);
         self.setters.insert(name.to_string(), Box::new(setter) as _);
     }
+
+    /// Get the value of `name`, which is either a setting with a
+    /// getter, or a console variable
+    pub fn get_bound(&self, name: &str) -> Option<rhai::Dynamic> {
+        if let Some(get) = self.getters.get(name) {
+            return Some(get());
+        }
+        self.console_vars.lock().get(name).cloned()
+    }
+
+    /// Set the value of `name`, using the setter if it's a setting,
+    /// otherwise setting the console variable
+    pub fn set_bound(&self, name: &str, val: rhai::Dynamic) {
+        if let Some(set) = self.setters.get(name) {
+            set(val);
+        } else {
+            self.console_vars.lock().insert(name.to_string(), val);
+        }
+    }
+
+    /// Set the console variable `name` to `val`, unless it's a
+    /// setting or already exists
+    pub fn init_var(&self, name: &str, val: rhai::Dynamic) {
+        if self.getters.contains_key(name) {
+            return;
+        }
+        self.console_vars
+            .lock()
+            .entry(name.to_string())
+            .or_insert(val);
+    }
 }
 
 impl ConsoleShared {
@@ -1278,6 +1670,11 @@ impl ConsoleShared {
 
         self.add_view_fns(&mut engine);
 
+        self.add_tour_fns(&mut engine);
+        self.add_export_fns(&mut engine);
+        self.add_path_highlight_fns(&mut engine);
+        self.add_sequence_fns(&mut engine);
+
         self.add_overlay_fns(&mut engine);
 
         self.add_modal_fns(&mut engine);
@@ -1584,6 +1981,317 @@ impl ConsoleShared {
             "overlay_id",
             move |overlay: (usize, OverlayKind, String)| (overlay.0, overlay.1),
         );
+
+        // computes one of the built-in overlays by name, e.g.
+        // `builtin_overlay("GC content")`, replacing any existing
+        // overlay with that name
+        let rayon_pool = self.rayon_pool.clone();
+        let graph = self.graph.clone();
+        let overlay_tx = self.channels.new_overlay_tx.clone();
+        engine.register_result_fn(
+            "builtin_overlay",
+            move |name: &str| -> std::result::Result<(), Box<EvalAltResult>> {
+                let overlay =
+                    BuiltinOverlay::from_name(name).ok_or_else(|| {
+                        let names = BuiltinOverlay::ALL
+                            .iter()
+                            .map(|o| format!("\"{}\"", o.name()))
+                            .collect::<Vec<_>>();
+                        format!(
+                            "Unknown built-in overlay `{}`, must be one of {}",
+                            name,
+                            names.join(", ")
+                        )
+                    })?;
+
+                let (data, legend) =
+                    overlay.compute(&rayon_pool, graph.graph());
+
+                let msg = OverlayCreatorMsg::ReplaceOverlay {
+                    name: overlay.name().to_string(),
+                    data,
+                    legend,
+                };
+                overlay_tx.send(msg).unwrap();
+
+                Ok(())
+            },
+        );
+    }
+
+    fn add_tour_fns(&self, engine: &mut Engine) {
+        use std::time::Duration;
+
+        engine.register_type::<Tour>();
+
+        engine.register_fn("Tour", Tour::default);
+
+        let ms = |ms: i64| Duration::from_millis(ms.max(0) as u64);
+
+        engine.register_fn(
+            "view",
+            move |tour: &mut Tour, view: View, t: i64| {
+                tour.push(TourStep::View {
+                    view,
+                    duration: ms(t),
+                });
+            },
+        );
+
+        engine.register_fn(
+            "view",
+            move |tour: &mut Tour, x: f32, y: f32, scale: f32, t: i64| {
+                let view = View {
+                    center: Point::new(x, y),
+                    scale,
+                };
+                tour.push(TourStep::View {
+                    view,
+                    duration: ms(t),
+                });
+            },
+        );
+
+        engine.register_fn("wait", move |tour: &mut Tour, t: i64| {
+            tour.push(TourStep::Wait(ms(t)));
+        });
+
+        engine.register_fn("overlay", |tour: &mut Tour, name: &str| {
+            tour.push(TourStep::Overlay(name.to_string()));
+        });
+
+        engine.register_fn(
+            "select",
+            |tour: &mut Tour, selection: NodeSelection| {
+                let mut nodes = selection.nodes.into_iter().collect::<Vec<_>>();
+                nodes.sort();
+                tour.push(TourStep::Select(nodes));
+            },
+        );
+
+        engine.register_fn("select", |tour: &mut Tour, node: NodeId| {
+            tour.push(TourStep::Select(vec![node]));
+        });
+
+        engine.register_fn("clear_selection", |tour: &mut Tour| {
+            tour.push(TourStep::ClearSelection);
+        });
+
+        engine.register_fn(
+            "labels",
+            |tour: &mut Tour, name: &str, visible: bool| {
+                tour.push(TourStep::Labels {
+                    name: name.to_string(),
+                    visible,
+                });
+            },
+        );
+
+        engine.register_result_fn("save", |tour: &mut Tour, path: &str| {
+            tour.write(path).map_err(|err| format!("{:?}", err).into())
+        });
+
+        engine.register_result_fn("load_tour", |path: &str| {
+            Tour::load(path).map_err(|err| format!("{:?}", err).into())
+        });
+
+        let tour_tx = self.channels.tour_tx.clone();
+        engine.register_fn("play_tour", move |tour: Tour| {
+            let msg = TourMsg::Play {
+                tour,
+                frame_dir: None,
+            };
+            tour_tx.send(msg).unwrap();
+        });
+
+        // play the tour, writing every frame as a PNG in `frame_dir`
+        let tour_tx = self.channels.tour_tx.clone();
+        let capture_supported =
+            self.shared_state.frame_capture_supported.clone();
+        engine.register_result_fn(
+            "play_tour",
+            move |tour: Tour, frame_dir: &str| {
+                if !capture_supported.load() {
+                    return Err(
+                        "Frame capture is not supported on this device".into(),
+                    );
+                }
+
+                let msg = TourMsg::Play {
+                    tour,
+                    frame_dir: Some(PathBuf::from(frame_dir)),
+                };
+                tour_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
+
+        let tour_tx = self.channels.tour_tx.clone();
+        engine.register_fn("stop_tour", move || {
+            tour_tx.send(TourMsg::Stop).unwrap();
+        });
+    }
+
+    fn add_export_fns(&self, engine: &mut Engine) {
+        let export_tx = self.channels.export_tx.clone();
+        engine.register_fn("export_svg", move |path: &str| {
+            let msg = ExportMsg::Svg {
+                path: PathBuf::from(path),
+                whole_graph: false,
+            };
+            export_tx.send(msg).unwrap();
+        });
+
+        let export_tx = self.channels.export_tx.clone();
+        engine.register_fn(
+            "export_svg",
+            move |path: &str, whole_graph: bool| {
+                let msg = ExportMsg::Svg {
+                    path: PathBuf::from(path),
+                    whole_graph,
+                };
+                export_tx.send(msg).unwrap();
+            },
+        );
+
+        let export_tx = self.channels.export_tx.clone();
+        engine.register_fn("export_png", move |path: &str, width: i64| {
+            let msg = ExportMsg::Png {
+                path: PathBuf::from(path),
+                width: width as u32,
+                height: None,
+            };
+            export_tx.send(msg).unwrap();
+        });
+
+        let export_tx = self.channels.export_tx.clone();
+        engine.register_fn(
+            "export_png",
+            move |path: &str, width: i64, height: i64| {
+                let msg = ExportMsg::Png {
+                    path: PathBuf::from(path),
+                    width: width as u32,
+                    height: Some(height as u32),
+                };
+                export_tx.send(msg).unwrap();
+            },
+        );
+    }
+
+    fn add_path_highlight_fns(&self, engine: &mut Engine) {
+        let highlight_tx = self.channels.path_highlight_tx.clone();
+        engine.register_fn("highlight_path", move |path: PathId| {
+            let msg = PathHighlightMsg::Add { path, color: None };
+            highlight_tx.send(msg).unwrap();
+        });
+
+        let graph = self.graph.graph.clone();
+        let highlight_tx = self.channels.path_highlight_tx.clone();
+        engine.register_result_fn("highlight_path", move |path_name: &str| {
+            let path = graph
+                .get_path_id(path_name.as_bytes())
+                .ok_or("The provided path does not exist")?;
+
+            let msg = PathHighlightMsg::Add { path, color: None };
+            highlight_tx.send(msg).unwrap();
+            Ok(())
+        });
+
+        let graph = self.graph.graph.clone();
+        let highlight_tx = self.channels.path_highlight_tx.clone();
+        engine.register_result_fn(
+            "highlight_path",
+            move |path_name: &str, color: rgb::RGBA<f32>| {
+                let path = graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                let msg = PathHighlightMsg::Add {
+                    path,
+                    color: Some(color),
+                };
+                highlight_tx.send(msg).unwrap();
+                Ok(())
+            },
+        );
+
+        let graph = self.graph.graph.clone();
+        let highlight_tx = self.channels.path_highlight_tx.clone();
+        engine.register_result_fn(
+            "unhighlight_path",
+            move |path_name: &str| {
+                let path = graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                highlight_tx.send(PathHighlightMsg::Remove(path)).unwrap();
+                Ok(())
+            },
+        );
+
+        let highlight_tx = self.channels.path_highlight_tx.clone();
+        engine.register_fn("clear_path_highlights", move || {
+            highlight_tx.send(PathHighlightMsg::Clear).unwrap();
+        });
+    }
+
+    fn add_sequence_fns(&self, engine: &mut Engine) {
+        let graph = self.graph.clone();
+        engine.register_result_fn("path_fasta", move |path_name: &str| {
+            let path = graph
+                .graph
+                .get_path_id(path_name.as_bytes())
+                .ok_or("The provided path does not exist")?;
+
+            let record = graph
+                .path_sequence(path, None)
+                .ok_or("Error spelling the path sequence")?;
+
+            Ok(fasta_string(&[record]))
+        });
+
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "path_fasta",
+            move |path_name: &str, start: i64, end: i64| {
+                let path = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                let range = (start.max(0) as usize, end.max(0) as usize);
+
+                let record = graph
+                    .path_sequence(path, Some(range))
+                    .ok_or("Error spelling the path sequence")?;
+
+                Ok(fasta_string(&[record]))
+            },
+        );
+
+        let graph = self.graph.clone();
+        engine.register_result_fn(
+            "selection_fasta",
+            move |selection: NodeSelection, path_name: &str| {
+                let path = graph
+                    .graph
+                    .get_path_id(path_name.as_bytes())
+                    .ok_or("The provided path does not exist")?;
+
+                let records = graph
+                    .path_selection_sequences(path, &selection.nodes)
+                    .ok_or("Error spelling the path sequence")?;
+
+                Ok(fasta_string(&records))
+            },
+        );
+
+        engine.register_result_fn("export_fasta", |file: &str, fasta: &str| {
+            std::fs::write(file, fasta).map_err(|err| {
+                format!("Error writing FASTA to {}: {}", file, err)
+            })?;
+            Ok(())
+        });
     }
 
     fn add_view_fns(&self, engine: &mut Engine) {
@@ -1682,114 +2390,7 @@ impl ConsoleShared {
     }
 
     fn add_annotation_fns(&self, engine: &mut rhai::Engine) {
-        engine.register_result_fn(
-            "get_record",
-            move |coll: &mut Arc<Gff3Records>, ix: i64| {
-                if let Some(record) = coll.records().get(ix as usize).cloned() {
-                    Ok(record)
-                } else {
-                    Err(Box::new(EvalAltResult::ErrorArrayBounds(
-                        coll.records().len(),
-                        ix as i64,
-                        rhai::Position::NONE,
-                    )))
-                }
-            },
-        );
-
-        engine.register_result_fn(
-            "get_record",
-            move |coll: &mut Arc<BedRecords>, ix: i64| {
-                if let Some(record) = coll.records().get(ix as usize).cloned() {
-                    Ok(record)
-                } else {
-                    Err(Box::new(EvalAltResult::ErrorArrayBounds(
-                        coll.records().len(),
-                        ix as i64,
-                        rhai::Position::NONE,
-                    )))
-                }
-            },
-        );
-
-        engine.register_fn("len", move |coll: &mut Arc<Gff3Records>| {
-            coll.len() as i64
-        });
-
-        engine.register_fn("len", move |coll: &mut Arc<BedRecords>| {
-            coll.len() as i64
-        });
-
-        engine.register_fn("gff3_column", |key: &str| match key {
-            "SeqId" => Gff3Column::SeqId,
-            "Source" => Gff3Column::Source,
-            "Type" => Gff3Column::Type,
-            "Start" => Gff3Column::Start,
-            "End" => Gff3Column::End,
-            "Score" => Gff3Column::Score,
-            "Strand" => Gff3Column::Strand,
-            "Frame" => Gff3Column::Frame,
-            attr => Gff3Column::Attribute(attr.as_bytes().to_owned()),
-        });
-
-        engine
-            .register_fn("bed_column", |ix: i64| BedColumn::Index(ix as usize));
-        engine.register_result_fn(
-            "bed_column",
-            |coll: &mut Arc<BedRecords>, header: &str| {
-                if let Some(col) = coll.header_to_column(header.as_bytes()) {
-                    Ok(col)
-                } else {
-                    Err("Header not found in provided BED file".into())
-                }
-            },
-        );
-        engine.register_result_fn("bed_column", |key: &str| match key {
-            "Chr" => Ok(BedColumn::Chr),
-            "Start" => Ok(BedColumn::Start),
-            "End" => Ok(BedColumn::End),
-            "Name" => Ok(BedColumn::Name),
-            _ => Err("Only headers \"name\", \"start\", \"end\", and \"name\" can be referred to without a BED record context".into()),
-        });
-
-        fn get_impl<R, K>(record: &mut R, column: K) -> rhai::Dynamic
-        where
-            R: AnnotationRecord<ColumnKey = K>,
-            K: ColumnKey,
-        {
-            if column == K::seq_id() {
-                let seq_id = record.seq_id();
-                rhai::Dynamic::from(seq_id.to_str().unwrap().to_string())
-            } else if column == K::start() {
-                rhai::Dynamic::from(record.start())
-            } else if column == K::end() {
-                rhai::Dynamic::from(record.end())
-            } else {
-                let fields = record.get_all(&column);
-                let dyn_fields = fields
-                    .into_iter()
-                    .map(|val| {
-                        rhai::Dynamic::from(format!("{}", val.as_bstr()))
-                    })
-                    .collect::<Vec<_>>();
-
-                rhai::Dynamic::from(dyn_fields)
-            }
-        }
-
-        engine.register_fn(
-            "get",
-            move |record: &mut Gff3Record, column: Gff3Column| {
-                get_impl(record, column)
-            },
-        );
-
-        engine.register_fn(
-            "get",
-            move |record: &mut BedRecord, column: BedColumn| {
-                get_impl(record, column)
-            },
-        );
+        crate::script::add_annotation_record_fns(engine);
 
         let app_msg_tx = self.channels.app_tx.clone();
         let graph = self.graph.graph.clone();
@@ -1987,16 +2588,15 @@ impl ConsoleShared {
             let path_name = path_name.to_str().unwrap();
 
             log::warn!("calling calculate_annotation_set");
-            let label_set =
-                crate::gui::windows::annotations::calculate_annotation_set(
-                    graph,
-                    annots.as_ref(),
-                    &record_indices,
-                    path_id,
-                    path_name,
-                    &column,
-                    label_set_name,
-                );
+            let label_set = crate::annotations::calculate_annotation_set(
+                graph,
+                annots.as_ref(),
+                &record_indices,
+                path_id,
+                path_name,
+                &column,
+                label_set_name,
+            );
 
             if let Some(label_set) = label_set {
                 log::warn!("label set calculated");
@@ -2054,186 +2654,60 @@ impl ConsoleShared {
     }
 }
 
-fn virtual_key_code_map() -> HashMap<String, winit::event::VirtualKeyCode> {
-    use winit::event::VirtualKeyCode as Key;
-
-    let keys = [
-        ("Key1", Key::Key1),
-        ("Key2", Key::Key2),
-        ("Key3", Key::Key3),
-        ("Key4", Key::Key4),
-        ("Key5", Key::Key5),
-        ("Key6", Key::Key6),
-        ("Key7", Key::Key7),
-        ("Key8", Key::Key8),
-        ("Key9", Key::Key9),
-        ("Key0", Key::Key0),
-        ("A", Key::A),
-        ("B", Key::B),
-        ("C", Key::C),
-        ("D", Key::D),
-        ("E", Key::E),
-        ("F", Key::F),
-        ("G", Key::G),
-        ("H", Key::H),
-        ("I", Key::I),
-        ("J", Key::J),
-        ("K", Key::K),
-        ("L", Key::L),
-        ("M", Key::M),
-        ("N", Key::N),
-        ("O", Key::O),
-        ("P", Key::P),
-        ("Q", Key::Q),
-        ("R", Key::R),
-        ("S", Key::S),
-        ("T", Key::T),
-        ("U", Key::U),
-        ("V", Key::V),
-        ("W", Key::W),
-        ("X", Key::X),
-        ("Y", Key::Y),
-        ("Z", Key::Z),
-        ("Escape", Key::Escape),
-        ("F1", Key::F1),
-        ("F2", Key::F2),
-        ("F3", Key::F3),
-        ("F4", Key::F4),
-        ("F5", Key::F5),
-        ("F6", Key::F6),
-        ("F7", Key::F7),
-        ("F8", Key::F8),
-        ("F9", Key::F9),
-        ("F10", Key::F10),
-        ("F11", Key::F11),
-        ("F12", Key::F12),
-        ("F13", Key::F13),
-        ("F14", Key::F14),
-        ("F15", Key::F15),
-        ("F16", Key::F16),
-        ("F17", Key::F17),
-        ("F18", Key::F18),
-        ("F19", Key::F19),
-        ("F20", Key::F20),
-        ("F21", Key::F21),
-        ("F22", Key::F22),
-        ("F23", Key::F23),
-        ("F24", Key::F24),
-        ("Snapshot", Key::Snapshot),
-        ("Scroll", Key::Scroll),
-        ("Pause", Key::Pause),
-        ("Insert", Key::Insert),
-        ("Home", Key::Home),
-        ("Delete", Key::Delete),
-        ("End", Key::End),
-        ("PageDown", Key::PageDown),
-        ("PageUp", Key::PageUp),
-        ("Left", Key::Left),
-        ("Up", Key::Up),
-        ("Right", Key::Right),
-        ("Down", Key::Down),
-        ("Back", Key::Back),
-        ("Return", Key::Return),
-        ("Space", Key::Space),
-        ("Compose", Key::Compose),
-        ("Caret", Key::Caret),
-        ("Numlock", Key::Numlock),
-        ("Numpad0", Key::Numpad0),
-        ("Numpad1", Key::Numpad1),
-        ("Numpad2", Key::Numpad2),
-        ("Numpad3", Key::Numpad3),
-        ("Numpad4", Key::Numpad4),
-        ("Numpad5", Key::Numpad5),
-        ("Numpad6", Key::Numpad6),
-        ("Numpad7", Key::Numpad7),
-        ("Numpad8", Key::Numpad8),
-        ("Numpad9", Key::Numpad9),
-        ("NumpadAdd", Key::NumpadAdd),
-        ("NumpadDivide", Key::NumpadDivide),
-        ("NumpadDecimal", Key::NumpadDecimal),
-        ("NumpadComma", Key::NumpadComma),
-        ("NumpadEnter", Key::NumpadEnter),
-        ("NumpadEquals", Key::NumpadEquals),
-        ("NumpadMultiply", Key::NumpadMultiply),
-        ("NumpadSubtract", Key::NumpadSubtract),
-        ("AbntC1", Key::AbntC1),
-        ("AbntC2", Key::AbntC2),
-        ("Apostrophe", Key::Apostrophe),
-        ("Apps", Key::Apps),
-        ("Asterisk", Key::Asterisk),
-        ("At", Key::At),
-        ("Ax", Key::Ax),
-        ("Backslash", Key::Backslash),
-        ("Calculator", Key::Calculator),
-        ("Capital", Key::Capital),
-        ("Colon", Key::Colon),
-        ("Comma", Key::Comma),
-        ("Convert", Key::Convert),
-        ("Equals", Key::Equals),
-        ("Grave", Key::Grave),
-        ("Kana", Key::Kana),
-        ("Kanji", Key::Kanji),
-        ("LAlt", Key::LAlt),
-        ("LBracket", Key::LBracket),
-        ("LControl", Key::LControl),
-        ("LShift", Key::LShift),
-        ("LWin", Key::LWin),
-        ("Mail", Key::Mail),
-        ("MediaSelect", Key::MediaSelect),
-        ("MediaStop", Key::MediaStop),
-        ("Minus", Key::Minus),
-        ("Mute", Key::Mute),
-        ("MyComputer", Key::MyComputer),
-        ("NavigateForward", Key::NavigateForward),
-        ("NavigateBackward", Key::NavigateBackward),
-        ("NextTrack", Key::NextTrack),
-        ("NoConvert", Key::NoConvert),
-        ("OEM102", Key::OEM102),
-        ("Period", Key::Period),
-        ("PlayPause", Key::PlayPause),
-        ("Plus", Key::Plus),
-        ("Power", Key::Power),
-        ("PrevTrack", Key::PrevTrack),
-        ("RAlt", Key::RAlt),
-        ("RBracket", Key::RBracket),
-        ("RControl", Key::RControl),
-        ("RShift", Key::RShift),
-        ("RWin", Key::RWin),
-        ("Semicolon", Key::Semicolon),
-        ("Slash", Key::Slash),
-        ("Sleep", Key::Sleep),
-        ("Stop", Key::Stop),
-        ("Sysrq", Key::Sysrq),
-        ("Tab", Key::Tab),
-        ("Underline", Key::Underline),
-        ("Unlabeled", Key::Unlabeled),
-        ("VolumeDown", Key::VolumeDown),
-        ("VolumeUp", Key::VolumeUp),
-        ("Wake", Key::Wake),
-        ("WebBack", Key::WebBack),
-        ("WebFavorites", Key::WebFavorites),
-        ("WebForward", Key::WebForward),
-        ("WebHome", Key::WebHome),
-        ("WebRefresh", Key::WebRefresh),
-        ("WebSearch", Key::WebSearch),
-        ("WebStop", Key::WebStop),
-        ("Yen", Key::Yen),
-        ("Copy", Key::Copy),
-        ("Paste", Key::Paste),
-        ("Cut", Key::Cut),
-    ]
-    .iter()
-    .map(|(n, c)| (n.to_string(), *c))
-    .collect();
-
-    keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotKind {
+    Bar,
+    Line,
 }
 
 pub enum ConsoleGuiElem {
-    Label { text: String },
-    Button { text: String, callback_id: String },
-    TextInput { label: String, data_id: String },
-    Row { fields: Vec<String> },
+    Label {
+        text: String,
+    },
+    Button {
+        text: String,
+        callback_id: String,
+    },
+    TextInput {
+        label: String,
+        data_id: String,
+    },
+    Row {
+        fields: Vec<String>,
+    },
+    Slider {
+        label: String,
+        var: String,
+        min: f32,
+        max: f32,
+    },
+    Checkbox {
+        label: String,
+        var: String,
+    },
+    ComboBox {
+        label: String,
+        var: String,
+        options: Vec<String>,
+    },
+    ColorPicker {
+        label: String,
+        var: String,
+    },
+    Plot {
+        var: String,
+        kind: PlotKind,
+    },
+}
+
+fn window_def_mut(
+    win_defs: &mut [Option<ConsoleGuiDsl>],
+    ix: i64,
+) -> Option<&mut ConsoleGuiDsl> {
+    if ix < 0 {
+        return None;
+    }
+    win_defs.get_mut(ix as usize).and_then(Option::as_mut)
 }
 
 pub struct ConsoleGuiDsl {
@@ -2243,6 +2717,8 @@ pub struct ConsoleGuiDsl {
     callbacks: HashMap<String, Box<dyn Fn() + Send + Sync + 'static>>,
 
     text_data: HashMap<String, String>,
+
+    open: bool,
 }
 
 impl ConsoleGuiDsl {
@@ -2254,6 +2730,8 @@ impl ConsoleGuiDsl {
             callbacks: HashMap::default(),
 
             text_data: HashMap::default(),
+
+            open: true,
         }
     }
 
@@ -2261,9 +2739,14 @@ impl ConsoleGuiDsl {
         self.text_data.get(data_id).map(|s| s.as_str())
     }
 
-    pub fn show(&mut self, ctx: &egui::CtxRef) {
+    pub fn show(&mut self, ctx: &egui::CtxRef, get_set: &GetSetTruth) {
+        let mut open = self.open;
+
+        let id = self.id;
+
         egui::Window::new(&self.window_title)
-            .id(self.id)
+            .id(id)
+            .open(&mut open)
             .show(ctx, |ui| {
                 for elem in self.elements.iter_mut() {
                     match elem {
@@ -2296,8 +2779,241 @@ impl ConsoleGuiDsl {
                         ConsoleGuiElem::Row { fields } => {
                             // TODO
                         }
+                        ConsoleGuiElem::Slider {
+                            label,
+                            var,
+                            min,
+                            max,
+                        } => {
+                            let label: &str = label;
+                            let value = get_set.get_bound(var);
+
+                            if let Some(mut v) =
+                                value.clone().and_then(|v| v.try_cast::<i64>())
+                            {
+                                let range = (*min as i64)..=(*max as i64);
+                                let slider =
+                                    egui::Slider::new::<i64>(&mut v, range)
+                                        .text(label);
+                                if ui.add(slider).changed() {
+                                    get_set.set_bound(var, v.into());
+                                }
+                            } else if let Some(mut v) =
+                                value.and_then(|v| v.try_cast::<f32>())
+                            {
+                                let slider = egui::Slider::new::<f32>(
+                                    &mut v,
+                                    *min..=*max,
+                                )
+                                .text(label);
+                                if ui.add(slider).changed() {
+                                    get_set.set_bound(var, v.into());
+                                }
+                            } else {
+                                Self::missing_var(ui, label, var);
+                            }
+                        }
+                        ConsoleGuiElem::Checkbox { label, var } => {
+                            let label: &str = label;
+
+                            if let Some(mut v) = get_set
+                                .get_bound(var)
+                                .and_then(|v| v.try_cast::<bool>())
+                            {
+                                if ui.checkbox(&mut v, label).changed() {
+                                    get_set.set_bound(var, v.into());
+                                }
+                            } else {
+                                Self::missing_var(ui, label, var);
+                            }
+                        }
+                        ConsoleGuiElem::ComboBox {
+                            label,
+                            var,
+                            options,
+                        } => {
+                            let label: &str = label;
+                            let selected = get_set
+                                .get_bound(var)
+                                .map(|v| v.to_string())
+                                .unwrap_or_default();
+
+                            let mut new_selection = None;
+
+                            ui.horizontal(|ui| {
+                                egui::ComboBox::from_id_source(
+                                    id.with(var.as_str()),
+                                )
+                                .selected_text(&selected)
+                                .show_ui(
+                                    ui,
+                                    |ui| {
+                                        for opt in options.iter() {
+                                            if ui
+                                                .selectable_label(
+                                                    opt == &selected,
+                                                    opt,
+                                                )
+                                                .clicked()
+                                            {
+                                                new_selection =
+                                                    Some(opt.clone());
+                                            }
+                                        }
+                                    },
+                                );
+                                ui.label(label);
+                            });
+
+                            if let Some(opt) = new_selection {
+                                get_set.set_bound(var, opt.into());
+                            }
+                        }
+                        ConsoleGuiElem::ColorPicker { label, var } => {
+                            let label: &str = label;
+                            let value = get_set.get_bound(var);
+
+                            if let Some(color) = value
+                                .clone()
+                                .and_then(|v| v.try_cast::<rgb::RGB<f32>>())
+                            {
+                                let mut color = [color.r, color.g, color.b];
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .color_edit_button_rgb(&mut color)
+                                        .changed()
+                                    {
+                                        let [r, g, b] = color;
+                                        let color = rgb::RGB::new(r, g, b);
+                                        get_set.set_bound(
+                                            var,
+                                            rhai::Dynamic::from(color),
+                                        );
+                                    }
+                                    ui.label(label);
+                                });
+                            } else if let Some(color) = value
+                                .and_then(|v| v.try_cast::<rgb::RGBA<f32>>())
+                            {
+                                let mut color =
+                                    [color.r, color.g, color.b, color.a];
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .color_edit_button_rgba_unmultiplied(
+                                            &mut color,
+                                        )
+                                        .changed()
+                                    {
+                                        let [r, g, b, a] = color;
+                                        let color = rgb::RGBA::new(r, g, b, a);
+                                        get_set.set_bound(
+                                            var,
+                                            rhai::Dynamic::from(color),
+                                        );
+                                    }
+                                    ui.label(label);
+                                });
+                            } else {
+                                Self::missing_var(ui, label, var);
+                            }
+                        }
+                        ConsoleGuiElem::Plot { var, kind } => {
+                            let values = get_set
+                                .get_bound(var)
+                                .and_then(|v| v.try_cast::<rhai::Array>())
+                                .map(|array| {
+                                    array
+                                        .iter()
+                                        .filter_map(|v| {
+                                            v.as_float().ok().or_else(|| {
+                                                v.as_int()
+                                                    .ok()
+                                                    .map(|i| i as f32)
+                                            })
+                                        })
+                                        .collect::<Vec<f32>>()
+                                });
+
+                            match values {
+                                Some(values) => match kind {
+                                    PlotKind::Bar => {
+                                        Self::bar_plot(ui, &values);
+                                    }
+                                    PlotKind::Line => {
+                                        let line = egui::plot::Line::new(
+                                            egui::plot::Values::from_ys_f32(
+                                                &values,
+                                            ),
+                                        );
+                                        let plot = egui::plot::Plot::new(
+                                            format!("{:?}-{}", id, var),
+                                        )
+                                        .line(line)
+                                        .view_aspect(2.0);
+                                        ui.add(plot);
+                                    }
+                                },
+                                None => Self::missing_var(ui, "Plot", var),
+                            }
+                        }
                     }
                 }
             });
+
+        self.open = open;
+    }
+
+    fn missing_var(ui: &mut egui::Ui, label: &str, var: &str) {
+        ui.label(format!("{}: `{}` not found", label, var));
+    }
+
+    fn bar_plot(ui: &mut egui::Ui, values: &[f32]) {
+        let size = egui::vec2(200.0, 100.0);
+        let (response, painter) =
+            ui.allocate_painter(size, egui::Sense::hover());
+
+        let rect = response.rect;
+
+        painter.rect_stroke(
+            rect,
+            0.0,
+            ui.visuals().widgets.noninteractive.bg_stroke,
+        );
+
+        if values.is_empty() {
+            return;
+        }
+
+        let min = values.iter().copied().fold(0.0f32, f32::min);
+        let max = values.iter().copied().fold(0.0f32, f32::max);
+
+        if max - min <= std::f32::EPSILON {
+            return;
+        }
+
+        let y_at =
+            |v: f32| rect.bottom() - ((v - min) / (max - min)) * rect.height();
+        let zero_y = y_at(0.0);
+
+        let width = rect.width() / values.len() as f32;
+        let color = ui.visuals().selection.bg_fill;
+
+        for (ix, &v) in values.iter().enumerate() {
+            let x = rect.left() + ix as f32 * width;
+            let y = y_at(v);
+
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(x, y.min(zero_y)),
+                egui::pos2(x + (width * 0.8).max(1.0), y.max(zero_y)),
+            );
+            painter.rect_filled(bar, 0.0, color);
+        }
+
+        if let Some(pos) = response.hover_pos() {
+            let ix = ((pos.x - rect.left()) / width) as usize;
+            if let Some(v) = values.get(ix) {
+                response.on_hover_text(format!("{}: {}", ix, v));
+            }
+        }
     }
 }
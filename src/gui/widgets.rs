@@ -9,7 +9,7 @@ use handlegraph::{
 };
 use rustc_hash::FxHashMap;
 
-use crate::{app::AppMsg, overlays::OverlayKind};
+use crate::{app::mainview::MainViewMsg, app::AppMsg, overlays::OverlayKind};
 use crate::{app::OverlayState, geometry::*};
 
 pub trait Widget {
@@ -25,6 +25,7 @@ pub trait Widget {
 
 pub struct MenuBar {
     overlay_state: OverlayState,
+    main_view_tx: Sender<MainViewMsg>,
 
     overlay_list: Vec<(usize, String)>,
 
@@ -34,9 +35,13 @@ pub struct MenuBar {
 impl MenuBar {
     pub const ID: &'static str = "app_menu_bar";
 
-    pub fn new(overlay_state: OverlayState) -> Self {
+    pub fn new(
+        overlay_state: OverlayState,
+        main_view_tx: Sender<MainViewMsg>,
+    ) -> Self {
         Self {
             overlay_state,
+            main_view_tx,
             overlay_list: Vec::new(),
             height: AtomicCell::new(0.0),
         }
@@ -65,6 +70,19 @@ impl MenuBar {
         app_msg_tx: &Sender<AppMsg>,
     ) {
         let settings = &mut open_windows.settings;
+        let session = &mut open_windows.session;
+        let export = &mut open_windows.export;
+        let bookmarks = &mut open_windows.bookmarks;
+        let minimap = &mut open_windows.minimap;
+        let ruler = &mut open_windows.ruler;
+        let path_highlights = &mut open_windows.path_highlights;
+        let path_matrix = &mut open_windows.path_matrix;
+        let path_comparison = &mut open_windows.path_comparison;
+        let dotplot = &mut open_windows.dotplot;
+        let statistics = &mut open_windows.statistics;
+        let validation = &mut open_windows.validation;
+
+        let main_view_tx = &self.main_view_tx;
 
         let annotation_records = &mut open_windows.annotation_records;
         let annotation_files = &mut open_windows.annotation_files;
@@ -122,12 +140,79 @@ impl MenuBar {
                     if ui.button("Goto selection").clicked() {
                         app_msg_tx.send(AppMsg::GotoSelection).unwrap();
                     }
+
+                    if ui.button("Back").clicked() {
+                        main_view_tx.send(MainViewMsg::GoBack).unwrap();
+                    }
+
+                    if ui.button("Forward").clicked() {
+                        main_view_tx.send(MainViewMsg::GoForward).unwrap();
+                    }
+
+                    if ui.selectable_label(*bookmarks, "Bookmarks").clicked() {
+                        *bookmarks = !*bookmarks;
+                    }
+
+                    if ui.selectable_label(*minimap, "Overview").clicked() {
+                        *minimap = !*minimap;
+                    }
+
+                    if ui.selectable_label(*ruler, "Scale & ruler").clicked() {
+                        *ruler = !*ruler;
+                    }
+
+                    if ui
+                        .selectable_label(*path_highlights, "Path highlights")
+                        .clicked()
+                    {
+                        *path_highlights = !*path_highlights;
+                    }
+
+                    if ui
+                        .selectable_label(*path_matrix, "Path matrix")
+                        .clicked()
+                    {
+                        *path_matrix = !*path_matrix;
+                    }
+
+                    if ui
+                        .selectable_label(*path_comparison, "Compare paths")
+                        .clicked()
+                    {
+                        *path_comparison = !*path_comparison;
+                    }
+
+                    if ui.selectable_label(*dotplot, "Dotplot").clicked() {
+                        *dotplot = !*dotplot;
+                    }
+
+                    if ui
+                        .selectable_label(*statistics, "Graph statistics")
+                        .clicked()
+                    {
+                        *statistics = !*statistics;
+                    }
+
+                    if ui
+                        .selectable_label(*validation, "Graph validation")
+                        .clicked()
+                    {
+                        *validation = !*validation;
+                    }
                 });
 
                 menu::menu(ui, "Tools", |ui| {
                     if ui.selectable_label(*settings, "Settings").clicked() {
                         *settings = !*settings;
                     }
+
+                    if ui.selectable_label(*session, "Session").clicked() {
+                        *session = !*session;
+                    }
+
+                    if ui.selectable_label(*export, "Export").clicked() {
+                        *export = !*export;
+                    }
                 });
 
                 let mut selected =
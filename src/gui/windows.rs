@@ -1,19 +1,41 @@
 pub mod annotations;
+pub mod bookmarks;
+pub mod dotplot;
+pub mod export;
 pub mod file;
 pub mod filters;
 pub mod graph_details;
 pub mod graph_picker;
+pub mod minimap;
 pub mod overlays;
+pub mod path_comparison;
+pub mod path_highlight;
+pub mod path_matrix;
 pub mod paths;
+pub mod ruler;
+pub mod session;
 pub mod settings;
+pub mod statistics;
 pub mod util;
+pub mod validation;
 
 pub use annotations::*;
+pub use bookmarks::*;
+pub use dotplot::*;
+pub use export::*;
 pub use file::*;
 pub use filters::*;
 pub use graph_details::*;
 pub use graph_picker::*;
+pub use minimap::*;
 pub use overlays::*;
+pub use path_comparison::*;
+pub use path_highlight::*;
+pub use path_matrix::*;
 pub use paths::*;
+pub use ruler::*;
+pub use session::*;
 pub use settings::*;
+pub use statistics::*;
 pub use util::*;
+pub use validation::*;
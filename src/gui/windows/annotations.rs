@@ -30,7 +30,8 @@ use rustc_hash::FxHashMap;
 
 use crate::{
     annotations::{
-        record_column_hash_color, AnnotationCollection, AnnotationFileType,
+        calculate_annotation_set, record_column_hash_color,
+        record_column_label, AnnotationCollection, AnnotationFileType,
         AnnotationLabelSet, AnnotationRecord, Annotations, BedRecords,
         ColumnKey, Gff3Records,
     },
@@ -39,7 +40,7 @@ use crate::{
     geometry::Point,
     graph_query::{GraphQuery, GraphQueryWorker},
     gui::{util::grid_row_label, GuiMsg, Windows},
-    overlays::OverlayData,
+    overlays::{OverlayData, OverlayLegend},
     reactor::{Host, Outbox, Reactor},
 };
 
@@ -696,8 +697,8 @@ where
 
                 running_msg("Calculating node colors");
 
-                let colors_vec: Vec<(Vec<NodeId>, rgb::RGBA<f32>)> = rayon_pool
-                    .install(|| {
+                let colors_vec: Vec<(Vec<NodeId>, rgb::RGBA<f32>, String)> =
+                    rayon_pool.install(|| {
                         indices
                             .into_par_iter()
                             .filter_map(|&ix| {
@@ -721,7 +722,10 @@ where
                                     .map(|(h, _, _)| h.id())
                                     .collect();
 
-                                Some((ids, color))
+                                let label =
+                                    record_column_label(record, &input.column);
+
+                                Some((ids, color, label))
                             })
                             .collect::<Vec<_>>()
                     });
@@ -729,10 +733,13 @@ where
                 let mut node_colors: FxHashMap<NodeId, rgb::RGBA<f32>> =
                     FxHashMap::default();
 
-                for (ids, color) in colors_vec {
+                let mut categories = Vec::with_capacity(colors_vec.len());
+
+                for (ids, color, label) in colors_vec {
                     for id in ids {
                         node_colors.insert(id, color);
                     }
+                    categories.push((color, label));
                 }
 
                 let mut data = vec![
@@ -746,11 +753,13 @@ where
                 }
 
                 let overlay_data = OverlayData::RGB(data);
+                let legend = OverlayLegend::categories(categories);
 
                 overlay_tx
                     .send(OverlayCreatorMsg::NewOverlay {
                         name: input.name,
                         data: overlay_data,
+                        legend: Some(legend),
                     })
                     .unwrap();
 
@@ -962,70 +971,3 @@ where
             })
     }
 }
-
-pub(crate) fn calculate_annotation_set<C>(
-    graph: &GraphQuery,
-    records: &C,
-    record_indices: &[usize],
-    path_id: PathId,
-    path_name: &str,
-    column: &C::ColumnKey,
-    label_set_name: &str,
-) -> Option<AnnotationLabelSet>
-where
-    C: AnnotationCollection + Send + Sync + 'static,
-{
-    log::warn!("checking record_indices.is_empty");
-    if record_indices.is_empty() {
-        return None;
-    }
-
-    let offset = crate::annotations::path_name_offset(path_name.as_bytes());
-
-    log::warn!("getting path steps");
-    let steps = graph.path_pos_steps(path_id)?;
-
-    let mut label_strings: Vec<String> =
-        Vec::with_capacity(record_indices.len());
-    let mut label_indices: FxHashMap<NodeId, Vec<usize>> = FxHashMap::default();
-
-    for &record_ix in record_indices.iter() {
-        log::trace!("getting record");
-        let record = records.records().get(record_ix)?;
-
-        if let Some(range) = crate::annotations::path_step_range(
-            &steps,
-            offset,
-            record.start(),
-            record.end(),
-        ) {
-            if let Some(value) = record.get_first(column) {
-                if let Some((mid, _, _)) = range.get(range.len() / 2) {
-                    let index = label_strings.len();
-                    let label = format!("{}", value.as_bstr());
-                    label_strings.push(label);
-                    label_indices.entry(mid.id()).or_default().push(index);
-                }
-            }
-        }
-    }
-
-    for labels in label_indices.values_mut() {
-        labels.sort();
-        labels.dedup();
-        labels.shrink_to_fit();
-    }
-
-    label_strings.shrink_to_fit();
-    label_indices.shrink_to_fit();
-
-    Some(AnnotationLabelSet::new(
-        records,
-        path_id,
-        path_name.as_bytes(),
-        column,
-        label_set_name,
-        label_strings,
-        label_indices,
-    ))
-}
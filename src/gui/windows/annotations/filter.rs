@@ -168,6 +168,70 @@ impl<T: ColumnKey> RecordFilter<T> {
         self.range_filter(start, end);
     }
 
+    /// The filters that are in use, as pairs of column names and
+    /// filter fields; used when saving sessions
+    pub fn to_fields(&self) -> Vec<(String, Vec<String>)> {
+        let mut result = Vec::new();
+
+        if self.seq_id.op != FilterStringOp::None {
+            result.push((T::seq_id().to_string(), self.seq_id.to_fields()));
+        }
+
+        if self.start.op != FilterNumOp::None {
+            result.push((T::start().to_string(), self.start.to_fields()));
+        }
+
+        if self.end.op != FilterNumOp::None {
+            result.push((T::end().to_string(), self.end.to_fields()));
+        }
+
+        let mut columns = self
+            .columns
+            .iter()
+            .filter(|(_, filter)| filter.op != FilterStringOp::None)
+            .map(|(column, filter)| (column.to_string(), filter.to_fields()))
+            .collect::<Vec<_>>();
+        columns.sort();
+
+        result.extend(columns);
+
+        result
+    }
+
+    /// Set the filter for the column with the given name, using the
+    /// fields produced by `to_fields`; returns false if the column or
+    /// fields couldn't be parsed
+    pub fn set_from_fields(&mut self, column: &str, fields: &[&str]) -> bool {
+        if column == T::seq_id().to_string() {
+            if let Some(filter) = FilterString::from_fields(fields) {
+                self.seq_id = filter;
+                return true;
+            }
+        } else if column == T::start().to_string() {
+            if let Some(filter) = FilterNum::from_fields(fields) {
+                self.start = filter;
+                return true;
+            }
+        } else if column == T::end().to_string() {
+            if let Some(filter) = FilterNum::from_fields(fields) {
+                self.end = filter;
+                return true;
+            }
+        } else if let Some(filter) = self
+            .columns
+            .iter_mut()
+            .find(|(c, _)| c.to_string() == column)
+            .map(|(_, filter)| filter)
+        {
+            if let Some(new_filter) = FilterString::from_fields(fields) {
+                *filter = new_filter;
+                return true;
+            }
+        }
+
+        false
+    }
+
     pub fn filter_record<R>(&self, record: &R) -> bool
     where
         R: AnnotationRecord<ColumnKey = T>,
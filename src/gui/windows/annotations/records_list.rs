@@ -33,6 +33,10 @@ where
     filter_open: bool,
     filters: HashMap<String, RecordFilter<C::ColumnKey>>,
 
+    // filters restored from a session, applied when the filter for
+    // the file is created
+    pending_filters: HashMap<String, Vec<(String, Vec<String>)>>,
+
     column_picker_open: bool,
     enabled_columns: HashMap<String, ColumnPickerMany<C::ColumnKey>>,
     default_enabled_columns: HashSet<C::ColumnKey>,
@@ -67,6 +71,7 @@ where
             // slot_count: 20,
             filter_open: false,
             filters: HashMap::default(),
+            pending_filters: HashMap::default(),
 
             column_picker_open: false,
             enabled_columns: HashMap::default(),
@@ -236,6 +241,36 @@ where
         self.offset = 0;
     }
 
+    /// The filters in use for each file, as `(file name, column,
+    /// filter fields)`
+    pub fn filter_fields(&self) -> Vec<(String, String, Vec<String>)> {
+        let mut result = Vec::new();
+
+        for (file_name, filter) in self.filters.iter() {
+            for (column, fields) in filter.to_fields() {
+                result.push((file_name.to_string(), column, fields));
+            }
+        }
+
+        result.sort();
+        result
+    }
+
+    /// Replace the filter for `file_name` with the provided column
+    /// filters, the next time the file is shown
+    pub fn restore_filter(
+        &mut self,
+        file_name: &str,
+        columns: Vec<(String, Vec<String>)>,
+    ) {
+        self.filters.remove(file_name);
+        self.pending_filters.insert(file_name.to_string(), columns);
+
+        if self.current_file.as_deref() == Some(file_name) {
+            self.current_file = None;
+        }
+    }
+
     fn clear_filter(&mut self) {
         self.filtered_records.clear();
     }
@@ -277,10 +312,32 @@ where
         }
 
         {
+            let id = self.id;
+            let pending_filters = &mut self.pending_filters;
+
             let filter = self
                 .filters
                 .entry(file_name.to_string())
-                .or_insert(RecordFilter::new(self.id, records.as_ref()));
+                .or_insert_with(|| {
+                    let mut filter = RecordFilter::new(id, records.as_ref());
+
+                    if let Some(columns) = pending_filters.remove(file_name) {
+                        for (column, fields) in columns {
+                            let fields = fields
+                                .iter()
+                                .map(|f| f.as_str())
+                                .collect::<Vec<_>>();
+                            if !filter.set_from_fields(&column, &fields) {
+                                warn!(
+                                    "Could not restore filter on column {}",
+                                    column
+                                );
+                            }
+                        }
+                    }
+
+                    filter
+                });
 
             let ctx = ui.ctx();
 
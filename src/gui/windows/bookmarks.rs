@@ -0,0 +1,156 @@
+use crossbeam::channel::Sender;
+
+use crate::app::{mainview::MainViewMsg, Bookmark, SharedState};
+
+pub struct BookmarksWindow {
+    shared_state: SharedState,
+    main_view_tx: Sender<MainViewMsg>,
+
+    new_name: String,
+    new_note: String,
+
+    // index of the bookmark whose note is being edited
+    editing_note: Option<usize>,
+}
+
+impl BookmarksWindow {
+    pub const ID: &'static str = "bookmarks_window";
+
+    pub fn new(
+        shared_state: SharedState,
+        main_view_tx: Sender<MainViewMsg>,
+    ) -> Self {
+        Self {
+            shared_state,
+            main_view_tx,
+
+            new_name: String::new(),
+            new_note: String::new(),
+
+            editing_note: None,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let shared_state = &self.shared_state;
+        let main_view_tx = &self.main_view_tx;
+
+        let new_name = &mut self.new_name;
+        let new_note = &mut self.new_note;
+        let editing_note = &mut self.editing_note;
+
+        egui::Window::new("Bookmarks")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.set_min_width(300.0);
+
+                let (can_back, can_forward) = {
+                    let history = shared_state.view_history().lock();
+                    (history.can_go_back(), history.can_go_forward())
+                };
+
+                ui.horizontal(|ui| {
+                    let back_btn =
+                        ui.add(egui::Button::new("Back").enabled(can_back));
+                    let forward_btn = ui
+                        .add(egui::Button::new("Forward").enabled(can_forward));
+
+                    if back_btn.clicked() {
+                        main_view_tx.send(MainViewMsg::GoBack).unwrap();
+                    }
+
+                    if forward_btn.clicked() {
+                        main_view_tx.send(MainViewMsg::GoForward).unwrap();
+                    }
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(new_name);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Note");
+                    ui.text_edit_singleline(new_note);
+                });
+
+                let add_btn = ui.add(
+                    egui::Button::new("Bookmark current view")
+                        .enabled(!new_name.is_empty()),
+                );
+
+                let mut bookmarks = shared_state.bookmarks().lock();
+
+                if add_btn.clicked() {
+                    let view = shared_state.view();
+                    bookmarks.push(Bookmark::new(new_name, new_note, view));
+                    new_name.clear();
+                    new_note.clear();
+                }
+
+                ui.separator();
+
+                let mut to_remove = None;
+
+                egui::ScrollArea::from_max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("bookmarks_window_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (ix, bookmark) in
+                                bookmarks.iter_mut().enumerate()
+                            {
+                                let name_label = ui.add(
+                                    egui::Label::new(&bookmark.name).strong(),
+                                );
+
+                                if !bookmark.note.is_empty() {
+                                    name_label.on_hover_text(&bookmark.note);
+                                }
+
+                                if ui.button("Go").clicked() {
+                                    main_view_tx
+                                        .send(MainViewMsg::GotoView(
+                                            bookmark.view,
+                                        ))
+                                        .unwrap();
+                                }
+
+                                let editing = *editing_note == Some(ix);
+
+                                if ui
+                                    .selectable_label(editing, "Note")
+                                    .clicked()
+                                {
+                                    *editing_note =
+                                        if editing { None } else { Some(ix) };
+                                }
+
+                                if ui.button("Remove").clicked() {
+                                    to_remove = Some(ix);
+                                }
+
+                                ui.end_row();
+
+                                if editing {
+                                    ui.label("");
+                                    ui.text_edit_multiline(&mut bookmark.note);
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+
+                if let Some(ix) = to_remove {
+                    bookmarks.remove(ix);
+                    *editing_note = None;
+                }
+            })
+    }
+}
@@ -0,0 +1,285 @@
+use handlegraph::pathhandlegraph::PathId;
+
+use crossbeam::channel::Sender;
+
+use crate::app::dotplot::{Dotplot, DOT_FORWARD, DOT_INVERTED};
+use crate::app::{AppMsg, Select};
+use crate::reactor::{Host, Outbox, Reactor};
+
+use super::graph_picker::PathPicker;
+use super::ruler::format_bp;
+
+/// The number of cells along each side of the downsampled dotplot
+const DOTPLOT_RES: usize = 256;
+
+/// The length of each side of the dotplot, in pixels
+const DOTPLOT_SIZE: f32 = 400.0;
+
+fn dot_color(flags: u8) -> Option<egui::Color32> {
+    let both = DOT_FORWARD | DOT_INVERTED;
+
+    match flags {
+        0 => None,
+        DOT_FORWARD => Some(egui::Color32::from_rgb(60, 140, 230)),
+        DOT_INVERTED => Some(egui::Color32::from_rgb(230, 60, 60)),
+        f if f == both => Some(egui::Color32::from_rgb(170, 90, 200)),
+        _ => None,
+    }
+}
+
+/// Plots the positions of the nodes shared by two paths against each
+/// other, colored by whether the paths traverse them in the same
+/// orientation. Dragging a box selects the nodes in it
+pub struct DotplotWindow {
+    path_picker_a: PathPicker,
+    path_picker_b: PathPicker,
+    // which of the two path pickers is open, if any
+    picking: Option<bool>,
+
+    dotplot_host: Host<(PathId, PathId), Option<Dotplot>>,
+    dotplot: Option<(Dotplot, Vec<u8>)>,
+    computing: bool,
+
+    // the corners of the box being dragged, in the unit square
+    drag_box: Option<(egui::Pos2, egui::Pos2)>,
+
+    app_msg_tx: Sender<AppMsg>,
+}
+
+impl DotplotWindow {
+    pub const ID: &'static str = "dotplot_window";
+
+    pub fn new(
+        reactor: &mut Reactor,
+        path_picker_a: PathPicker,
+        path_picker_b: PathPicker,
+        app_msg_tx: Sender<AppMsg>,
+    ) -> Self {
+        let dotplot_host = {
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(
+                move |_outbox: &Outbox<Option<Dotplot>>,
+                      (path_a, path_b): (PathId, PathId)| {
+                    Dotplot::new(&graph, path_a, path_b)
+                },
+            )
+        };
+
+        Self {
+            path_picker_a,
+            path_picker_b,
+            picking: None,
+
+            dotplot_host,
+            dotplot: None,
+            computing: false,
+
+            drag_box: None,
+
+            app_msg_tx,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if let Some(dotplot) = self.dotplot_host.take() {
+            self.dotplot = dotplot.map(|dotplot| {
+                let grid = dotplot.grid(DOTPLOT_RES);
+                (dotplot, grid)
+            });
+            self.computing = false;
+        }
+
+        let mut picker_a_open = self.picking == Some(true);
+        let mut picker_b_open = self.picking == Some(false);
+
+        self.path_picker_a.ui(ctx, &mut picker_a_open);
+        self.path_picker_b.ui(ctx, &mut picker_b_open);
+
+        if !picker_a_open && !picker_b_open {
+            self.picking = None;
+        }
+
+        egui::Window::new("Dotplot")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                let path_a = self.path_picker_a.active_path();
+                let path_b = self.path_picker_b.active_path();
+
+                let picking = &mut self.picking;
+
+                egui::Grid::new("dotplot_paths").show(ui, |ui| {
+                    ui.label("Path A (x)");
+                    ui.label(path_a.map(|(_, name)| name).unwrap_or("-"));
+                    if ui.button("Choose path").clicked() {
+                        *picking = Some(true);
+                    }
+                    ui.end_row();
+
+                    ui.label("Path B (y)");
+                    ui.label(path_b.map(|(_, name)| name).unwrap_or("-"));
+                    if ui.button("Choose path").clicked() {
+                        *picking = Some(false);
+                    }
+                    ui.end_row();
+                });
+
+                let paths = path_a.zip(path_b).map(|((a, _), (b, _))| (a, b));
+
+                let plot_btn = ui.add(
+                    egui::Button::new("Plot")
+                        .enabled(paths.is_some() && !self.computing),
+                );
+
+                if let Some(paths) = paths {
+                    if plot_btn.clicked() {
+                        self.dotplot_host.call(paths).unwrap();
+                        self.computing = true;
+                    }
+                }
+
+                if self.computing {
+                    ui.label("Computing dotplot...");
+                }
+
+                if self.dotplot.is_some() {
+                    ui.separator();
+                    self.dotplot_ui(ui);
+                }
+            })
+    }
+
+    fn dotplot_ui(&mut self, ui: &mut egui::Ui) {
+        let (dotplot, grid) = self.dotplot.as_ref().unwrap();
+
+        ui.label(format!(
+            "{} ({}) against {} ({}), {} shared steps",
+            dotplot.path_a.1,
+            format_bp(dotplot.len_a as f64),
+            dotplot.path_b.1,
+            format_bp(dotplot.len_b as f64),
+            dotplot.dots.len()
+        ));
+
+        if dotplot.subsampled_nodes > 0 {
+            ui.label(format!(
+                "{} repeated nodes have too many pairs of steps to plot, \
+                 and are subsampled",
+                dotplot.subsampled_nodes
+            ));
+        }
+
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(DOTPLOT_SIZE, DOTPLOT_SIZE),
+            egui::Sense::drag(),
+        );
+
+        let painter = ui.painter_at(rect);
+
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let cell_size = DOTPLOT_SIZE / DOTPLOT_RES as f32;
+
+        let mut mesh = egui::epaint::Mesh::default();
+
+        for (ix, flags) in grid.iter().enumerate() {
+            if let Some(color) = dot_color(*flags) {
+                let col = ix % DOTPLOT_RES;
+                let row = ix / DOTPLOT_RES;
+
+                // path B increases upwards
+                let min = egui::pos2(
+                    rect.min.x + col as f32 * cell_size,
+                    rect.max.y - (row + 1) as f32 * cell_size,
+                );
+
+                mesh.add_colored_rect(
+                    egui::Rect::from_min_size(
+                        min,
+                        egui::vec2(cell_size, cell_size),
+                    ),
+                    color,
+                );
+            }
+        }
+
+        painter.add(egui::Shape::Mesh(mesh));
+
+        // the point in path coordinates, with y pointing up
+        let to_unit = |pos: egui::Pos2| {
+            let x = ((pos.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+            let y = ((rect.max.y - pos.y) / rect.height()).clamp(0.0, 1.0);
+            egui::pos2(x, y)
+        };
+
+        let len_a = dotplot.len_a as f32;
+        let len_b = dotplot.len_b as f32;
+
+        if let Some(pos) = response.hover_pos() {
+            let p = to_unit(pos);
+            ui.label(format!(
+                "{}: {:.0}, {}: {:.0}",
+                dotplot.path_a.1,
+                p.x * len_a,
+                dotplot.path_b.1,
+                p.y * len_b
+            ));
+        }
+
+        if response.drag_started() {
+            self.drag_box = response.interact_pointer_pos().map(|pos| {
+                let p = to_unit(pos);
+                (p, p)
+            });
+        }
+
+        if response.dragged() {
+            let pos = response.interact_pointer_pos();
+
+            if let Some(((_, end), pos)) = self.drag_box.as_mut().zip(pos) {
+                *end = to_unit(pos);
+            }
+        }
+
+        if let Some((start, end)) = self.drag_box {
+            let to_screen = |p: egui::Pos2| {
+                egui::pos2(
+                    rect.min.x + p.x * rect.width(),
+                    rect.max.y - p.y * rect.height(),
+                )
+            };
+
+            let stroke =
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(240, 180, 30));
+            painter.rect_stroke(
+                egui::Rect::from_two_pos(to_screen(start), to_screen(end)),
+                0.0,
+                stroke,
+            );
+
+            if response.drag_released() {
+                let range = |a: f32, b: f32, len: f32| {
+                    let start = (a.min(b) * len) as usize;
+                    let end = (a.max(b) * len).ceil() as usize;
+                    (start, end)
+                };
+
+                let range_a = range(start.x, end.x, len_a);
+                let range_b = range(start.y, end.y, len_b);
+
+                let nodes = dotplot.nodes_in(range_a, range_b);
+
+                let msg =
+                    AppMsg::Selection(Select::Many { nodes, clear: true });
+                self.app_msg_tx.send(msg).unwrap();
+
+                self.drag_box = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use crossbeam::channel::Sender;
+
+use crate::app::ExportMsg;
+use crate::export::{PNG_FILE_EXT, SVG_FILE_EXT};
+
+use super::file::FilePicker;
+
+pub struct ExportWindow {
+    svg_path: String,
+    whole_graph: bool,
+
+    png_path: String,
+    png_width: u32,
+    png_height: u32,
+    png_window_aspect: bool,
+
+    file_picker: FilePicker,
+    file_picker_open: bool,
+    // whether the file picker was opened for the PNG path
+    picking_png: bool,
+
+    export_tx: Sender<ExportMsg>,
+}
+
+impl ExportWindow {
+    pub const ID: &'static str = "export_window";
+
+    pub fn new(export_tx: Sender<ExportMsg>) -> Self {
+        let pwd = std::fs::canonicalize("./").unwrap();
+
+        let mut file_picker = FilePicker::new(
+            egui::Id::with(egui::Id::new(Self::ID), "file_picker"),
+            pwd,
+        )
+        .unwrap();
+
+        let extensions: [&str; 2] = [SVG_FILE_EXT, PNG_FILE_EXT];
+        file_picker.set_visible_extensions(&extensions).unwrap();
+
+        Self {
+            svg_path: String::new(),
+            whole_graph: false,
+
+            png_path: String::new(),
+            png_width: 3840,
+            png_height: 2160,
+            png_window_aspect: true,
+
+            file_picker,
+            file_picker_open: false,
+            picking_png: false,
+
+            export_tx,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if self.file_picker.selected_path().is_some() {
+            self.file_picker_open = false;
+        }
+
+        self.file_picker.ui(ctx, &mut self.file_picker_open);
+
+        if let Some(path) = self.file_picker.selected_path() {
+            let path_str = path.to_str().unwrap();
+            if self.picking_png {
+                self.png_path = path_str.to_string();
+            } else {
+                self.svg_path = path_str.to_string();
+            }
+        }
+
+        let svg_path = &mut self.svg_path;
+        let whole_graph = &mut self.whole_graph;
+
+        let png_path = &mut self.png_path;
+        let png_width = &mut self.png_width;
+        let png_height = &mut self.png_height;
+        let png_window_aspect = &mut self.png_window_aspect;
+
+        let file_picker = &mut self.file_picker;
+        let file_picker_open = &mut self.file_picker_open;
+        let picking_png = &mut self.picking_png;
+        let export_tx = &self.export_tx;
+
+        egui::Window::new("Export")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.set_min_width(300.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("SVG file");
+                    ui.separator();
+                    ui.text_edit_singleline(svg_path);
+                });
+
+                ui.checkbox(whole_graph, "Entire graph").on_hover_text(
+                    "Export the entire graph rather than the current view",
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.button("Choose file").clicked() {
+                        file_picker.reset_selection();
+                        *file_picker_open = true;
+                        *picking_png = false;
+                    }
+
+                    let export_btn = ui.add(
+                        egui::Button::new("Export SVG")
+                            .enabled(!svg_path.is_empty()),
+                    );
+
+                    if export_btn.clicked() {
+                        file_picker.reset_selection();
+                        let path = PathBuf::from(svg_path.as_str());
+                        export_tx
+                            .send(ExportMsg::Svg {
+                                path,
+                                whole_graph: *whole_graph,
+                            })
+                            .unwrap();
+                    }
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("PNG file");
+                    ui.separator();
+                    ui.text_edit_singleline(png_path);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Width");
+                    ui.add(
+                        egui::DragValue::new::<u32>(png_width)
+                            .clamp_range(1..=65536),
+                    );
+
+                    ui.label("Height");
+                    ui.add(
+                        egui::DragValue::new::<u32>(png_height)
+                            .clamp_range(1..=65536),
+                    );
+                });
+
+                ui.checkbox(png_window_aspect, "Window aspect ratio")
+                    .on_hover_text(
+                        "Derive the height from the width and the window size",
+                    );
+
+                ui.horizontal(|ui| {
+                    if ui.button("Choose file").clicked() {
+                        file_picker.reset_selection();
+                        *file_picker_open = true;
+                        *picking_png = true;
+                    }
+
+                    let export_btn = ui.add(
+                        egui::Button::new("Export PNG")
+                            .enabled(!png_path.is_empty()),
+                    );
+
+                    if export_btn.clicked() {
+                        file_picker.reset_selection();
+                        let path = PathBuf::from(png_path.as_str());
+                        let height = if *png_window_aspect {
+                            None
+                        } else {
+                            Some(*png_height)
+                        };
+                        export_tx
+                            .send(ExportMsg::Png {
+                                path,
+                                width: *png_width,
+                                height,
+                            })
+                            .unwrap();
+                    }
+                });
+            })
+    }
+}
@@ -28,6 +28,27 @@ pub struct FilterString {
     pub arg: String,
 }
 
+impl FilterStringOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterStringOp::None => "none",
+            FilterStringOp::Equal => "equal",
+            FilterStringOp::Contains => "contains",
+            FilterStringOp::ContainedIn => "contained_in",
+        }
+    }
+
+    pub fn parse(op: &str) -> Option<Self> {
+        match op {
+            "none" => Some(FilterStringOp::None),
+            "equal" => Some(FilterStringOp::Equal),
+            "contains" => Some(FilterStringOp::Contains),
+            "contained_in" => Some(FilterStringOp::ContainedIn),
+            _ => None,
+        }
+    }
+}
+
 impl std::default::Default for FilterString {
     fn default() -> Self {
         Self {
@@ -38,6 +59,17 @@ impl std::default::Default for FilterString {
 }
 
 impl FilterString {
+    /// The operation and argument, as stored in session files
+    pub fn to_fields(&self) -> Vec<String> {
+        vec![self.op.as_str().to_string(), self.arg.clone()]
+    }
+
+    pub fn from_fields(fields: &[&str]) -> Option<Self> {
+        let op = FilterStringOp::parse(fields.get(0)?)?;
+        let arg = fields.get(1).copied().unwrap_or_default().to_string();
+        Some(Self { op, arg })
+    }
+
     pub fn filter_str(&self, string: &str) -> bool {
         match self.op {
             FilterStringOp::None => true,
@@ -103,6 +135,29 @@ pub struct FilterNum<T: Numeric> {
     pub arg2: T,
 }
 
+impl FilterNumOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterNumOp::None => "none",
+            FilterNumOp::Equal => "equal",
+            FilterNumOp::LessThan => "less_than",
+            FilterNumOp::MoreThan => "more_than",
+            FilterNumOp::InRange => "in_range",
+        }
+    }
+
+    pub fn parse(op: &str) -> Option<Self> {
+        match op {
+            "none" => Some(FilterNumOp::None),
+            "equal" => Some(FilterNumOp::Equal),
+            "less_than" => Some(FilterNumOp::LessThan),
+            "more_than" => Some(FilterNumOp::MoreThan),
+            "in_range" => Some(FilterNumOp::InRange),
+            _ => None,
+        }
+    }
+}
+
 impl<T: Numeric> std::default::Default for FilterNum<T> {
     fn default() -> Self {
         Self {
@@ -114,6 +169,28 @@ impl<T: Numeric> std::default::Default for FilterNum<T> {
 }
 
 impl<T: Numeric> FilterNum<T> {
+    /// The operation and arguments, as stored in session files
+    pub fn to_fields(&self) -> Vec<String> {
+        vec![
+            self.op.as_str().to_string(),
+            self.arg1.to_f64().to_string(),
+            self.arg2.to_f64().to_string(),
+        ]
+    }
+
+    pub fn from_fields(fields: &[&str]) -> Option<Self> {
+        let op = FilterNumOp::parse(fields.get(0)?)?;
+        let arg = |ix: usize| -> Option<T> {
+            let v = fields.get(ix)?.parse::<f64>().ok()?;
+            Some(T::from_f64(v))
+        };
+        Some(Self {
+            op,
+            arg1: arg(1)?,
+            arg2: arg(2)?,
+        })
+    }
+
     pub fn filter(&self, val: T) -> bool {
         match self.op {
             FilterNumOp::None => true,
@@ -16,6 +16,9 @@ use std::sync::Arc;
 
 use bstr::ByteSlice;
 
+use crate::app::node_query::{NodeColumns, NodeField, NodeQuery};
+use crate::asynchronous::AsyncResult;
+use crate::graph_query::GraphQueryWorker;
 use crate::{app::AppMsg, context::ContextEntry, geometry::*};
 use crate::{graph_query::GraphQuery, gui::util::grid_row_label};
 
@@ -298,7 +301,6 @@ impl NodeListSlot {
 
 // pub struct
 
-#[derive(Debug)]
 pub struct NodeList {
     // probably not needed as I can assume compact node IDs
     all_nodes: Vec<NodeId>,
@@ -316,6 +318,25 @@ pub struct NodeList {
     apply_filter: AtomicCell<bool>,
 
     node_details_id: Arc<AtomicCell<Option<NodeId>>>,
+
+    // the values used to sort and query the nodes, computed in the
+    // background the first time the list is shown
+    columns: Option<AsyncResult<NodeColumns>>,
+
+    // the column to sort by, and whether it's sorted descending
+    sort: Option<(NodeField, bool)>,
+
+    query_text: String,
+    query: Option<NodeQuery>,
+    query_error: Option<String>,
+
+    // the sorted nodes matching the query, or `None` if the list
+    // isn't sorted or queried
+    matching: Option<Vec<NodeId>>,
+    update_matching: bool,
+
+    export_path: String,
+    export_result: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -341,6 +362,7 @@ impl NodeList {
                     self.apply_filter.fetch_xor(true);
                     self.update_slots = true;
                 }
+                self.update_matching = true;
             }
             NodeListMsg::NextPage => {
                 if self.page < self.page_count {
@@ -409,6 +431,20 @@ impl NodeList {
             apply_filter: true.into(),
 
             node_details_id,
+
+            columns: None,
+
+            sort: None,
+
+            query_text: String::new(),
+            query: None,
+            query_error: None,
+
+            matching: None,
+            update_matching: false,
+
+            export_path: String::new(),
+            export_result: None,
         }
     }
 
@@ -418,7 +454,101 @@ impl NodeList {
 
         if self.apply_filter.load() {
             self.update_slots = true;
+            self.update_matching = true;
+        }
+    }
+
+    /// The nodes the list shows when it's neither sorted nor queried
+    fn base_nodes(&self) -> &[NodeId] {
+        if !self.apply_filter.load() || self.filtered_nodes.is_empty() {
+            &self.all_nodes
+        } else {
+            &self.filtered_nodes
+        }
+    }
+
+    fn set_query(&mut self) {
+        let text = self.query_text.trim();
+
+        if text.is_empty() {
+            self.query = None;
+            self.query_error = None;
+        } else {
+            match NodeQuery::parse(text) {
+                Ok(query) => {
+                    self.query = Some(query);
+                    self.query_error = None;
+                }
+                Err(err) => {
+                    self.query = None;
+                    self.query_error = Some(format!("{}", err));
+                }
+            }
         }
+
+        self.update_matching = true;
+    }
+
+    /// Applies the query and sort order to the base nodes; returns
+    /// false if the node columns haven't been computed yet
+    fn apply_query(&mut self) -> bool {
+        if self.query.is_none() && self.sort.is_none() {
+            self.matching = None;
+        } else {
+            let columns = self.columns.as_ref().and_then(|c| c.get_result());
+
+            let columns = if let Some(columns) = columns {
+                columns
+            } else {
+                return false;
+            };
+
+            let query = self.query.as_ref();
+
+            let mut matching = self
+                .base_nodes()
+                .iter()
+                .copied()
+                .filter(|&node| {
+                    query.map(|q| q.matches(columns, node)).unwrap_or(true)
+                })
+                .collect::<Vec<_>>();
+
+            if let Some((field, descending)) = self.sort {
+                columns.sort(&mut matching, field, descending);
+            }
+
+            self.matching = Some(matching);
+        }
+
+        self.page = 0;
+        self.update_slots = true;
+
+        true
+    }
+
+    fn matching_nodes(&self) -> &[NodeId] {
+        if let Some(matching) = &self.matching {
+            matching
+        } else {
+            self.base_nodes()
+        }
+    }
+
+    fn export_matching(&self) -> anyhow::Result<usize> {
+        let columns =
+            self.columns.as_ref().and_then(|c| c.get_result()).ok_or(
+                anyhow::anyhow!("The node columns are still computing"),
+            )?;
+
+        let nodes = self.matching_nodes();
+
+        let file = std::fs::File::create(&self.export_path)?;
+        let writer = std::io::BufWriter::new(file);
+
+        columns.write_tsv(nodes, writer)?;
+
+        Ok(nodes.len())
     }
 
     pub fn ui(
@@ -427,16 +557,37 @@ impl NodeList {
         app_msg_tx: &Sender<AppMsg>,
         open_node_details: &mut bool,
         graph_query: &GraphQuery,
+        graph_query_worker: &GraphQueryWorker,
         ctx_tx: &Sender<ContextEntry>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
+        if self.columns.is_none() {
+            let columns =
+                graph_query_worker.run_query(|graph_query| async move {
+                    NodeColumns::new(&graph_query)
+                });
+            self.columns = Some(columns);
+        }
+
+        if let Some(columns) = self.columns.as_mut() {
+            columns.move_result_if_ready();
+        }
+
+        if self.update_matching && self.apply_query() {
+            self.update_matching = false;
+        }
+
         let filter = self.apply_filter.load();
 
-        let nodes = if !filter || self.filtered_nodes.is_empty() {
+        let nodes = if let Some(matching) = &self.matching {
+            matching
+        } else if !filter || self.filtered_nodes.is_empty() {
             &self.all_nodes
         } else {
             &self.filtered_nodes
         };
 
+        let node_count = nodes.len();
+
         self.page_count = nodes.len() / self.page_size;
 
         // this'll need fixing
@@ -462,8 +613,16 @@ impl NodeList {
 
                 let handle = Handle::pack(*node, false);
 
+                let graph = graph_query.graph();
+
                 slot.sequence.clear();
-                slot.sequence.extend(graph_query.graph().sequence(handle));
+                slot.sequence.extend(graph.sequence(handle));
+
+                let degree_l = graph.neighbors(handle, Direction::Left).count();
+                let degree_r =
+                    graph.neighbors(handle, Direction::Right).count();
+
+                slot.degree = (degree_l, degree_r);
 
                 slot.paths.clear();
                 slot.unique_paths.clear();
@@ -487,12 +646,23 @@ impl NodeList {
             self.update_slots = false;
         }
 
-        egui::Window::new("Nodes")
+        let columns_ready = self
+            .columns
+            .as_ref()
+            .map(|c| c.get_result().is_some())
+            .unwrap_or(false);
+
+        let mut new_sort = None;
+        let mut apply_query = false;
+        let mut select_matching = false;
+        let mut export_matching = false;
+
+        let response = egui::Window::new("Nodes")
             .id(egui::Id::new(Self::ID))
             .default_pos(egui::Pos2::new(200.0, 200.0))
             .show(ctx, |mut ui| {
                 ui.set_min_height(300.0);
-                ui.set_max_width(200.0);
+                ui.set_max_width(300.0);
 
                 ui.horizontal(|ui| {
                     let clear_selection_btn = ui
@@ -517,12 +687,62 @@ impl NodeList {
                 let page = &mut self.page;
                 let page_count = self.page_count;
                 let update_slots = &mut self.update_slots;
+                let update_matching = &mut self.update_matching;
+
+                let query_text = &mut self.query_text;
+                let export_path = &mut self.export_path;
 
                 let apply_filter = &self.apply_filter;
 
                 if ui.selectable_label(filter, "Show only selected").clicked() {
                     apply_filter.store(!filter);
                     *update_slots = true;
+                    *update_matching = true;
+                }
+
+                ui.horizontal(|ui| {
+                    let query_box =
+                        ui.text_edit_singleline(query_text).on_hover_text(
+                            "Filter by len, degree, coverage, paths, and id, \
+                             e.g. `len > 1000 && coverage < 3`",
+                        );
+
+                    if query_box.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter)
+                    {
+                        apply_query = true;
+                    }
+
+                    if ui.button("Query").clicked() {
+                        apply_query = true;
+                    }
+                });
+
+                if let Some(err) = &self.query_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+
+                if !columns_ready {
+                    ui.label("Computing node statistics...");
+                } else if self.matching.is_some() {
+                    ui.label(format!("{} matching nodes", node_count));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Select all matching").clicked() {
+                        select_matching = true;
+                    }
+
+                    if ui.button("Export matching").clicked() {
+                        export_matching = true;
+                    }
+
+                    ui.text_edit_singleline(export_path)
+                        .on_hover_text("The TSV file to export to");
+                });
+
+                if let Some(result) = &self.export_result {
+                    ui.label(result);
                 }
 
                 ui.label(format!("Page {}/{}", *page + 1, page_count + 1));
@@ -559,14 +779,47 @@ impl NodeList {
 
                 let node_id_cell = &self.node_details_id;
 
+                let sort = self.sort;
+
                 egui::ScrollArea::auto_sized().show(&mut ui, |mut ui| {
                     egui::Grid::new("node_list_grid").striped(true).show(
                         &mut ui,
                         |ui| {
-                            ui.label("Node");
-                            ui.label("Degree");
-                            ui.label("Seq. len");
-                            ui.label("Path count");
+                            let headers = [
+                                ("Node", NodeField::Id),
+                                ("Degree", NodeField::Degree),
+                                ("Seq. len", NodeField::Length),
+                                ("Coverage", NodeField::Coverage),
+                                ("Path count", NodeField::PathCount),
+                            ];
+
+                            for &(name, field) in headers.iter() {
+                                let (sorted, label) = match sort {
+                                    Some((f, true)) if f == field => {
+                                        (true, format!("{} ↓", name))
+                                    }
+                                    Some((f, false)) if f == field => {
+                                        (true, format!("{} ↑", name))
+                                    }
+                                    _ => (false, name.to_string()),
+                                };
+
+                                let header = ui
+                                    .selectable_label(sorted, label)
+                                    .on_hover_text("Click to sort");
+
+                                if header.clicked() && columns_ready {
+                                    // sort descending first, then
+                                    // ascending, then unsorted
+                                    new_sort = Some(match sort {
+                                        Some((f, true)) if f == field => {
+                                            Some((field, false))
+                                        }
+                                        Some((f, false)) if f == field => None,
+                                        _ => Some((field, true)),
+                                    });
+                                }
+                            }
                             ui.end_row();
 
                             for (ix, slot) in self.slots.iter().enumerate() {
@@ -581,15 +834,19 @@ impl NodeList {
                                     let seq_len =
                                         format!("{}", slot.sequence.len());
 
+                                    let coverage =
+                                        format!("{}", slot.paths.len());
+
                                     let uniq_paths = format!(
                                         "{}",
                                         slot.unique_paths.len() // slot.paths.len()
                                     );
 
-                                    let fields: [&str; 4] = [
+                                    let fields: [&str; 5] = [
                                         &node_id,
                                         &degree,
                                         &seq_len,
+                                        &coverage,
                                         &uniq_paths,
                                     ];
 
@@ -622,6 +879,39 @@ impl NodeList {
                 });
 
                 ui.shrink_width_to_current();
-            })
+            });
+
+        if let Some(sort) = new_sort {
+            self.sort = sort;
+            self.update_matching = true;
+        }
+
+        if apply_query {
+            self.set_query();
+        }
+
+        if select_matching {
+            use crate::app::Select;
+
+            let nodes = self.matching_nodes().iter().copied().collect();
+            app_msg_tx
+                .send(AppMsg::Selection(Select::Many { nodes, clear: true }))
+                .unwrap();
+        }
+
+        if export_matching {
+            let result = match self.export_matching() {
+                Ok(count) => {
+                    format!("Wrote {} nodes to {}", count, self.export_path)
+                }
+                Err(err) => {
+                    log::error!("Error exporting nodes: {:?}", err);
+                    format!("Error exporting nodes: {}", err)
+                }
+            };
+            self.export_result = Some(result);
+        }
+
+        response
     }
 }
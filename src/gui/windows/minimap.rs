@@ -0,0 +1,278 @@
+use crate::app::SharedState;
+use crate::geometry::{Point, Rect};
+use crate::universe::GraphLayout;
+
+/// The number of density cells along the longest side of the layout
+const DENSITY_RES: usize = 128;
+
+/// The length of the longest side of the minimap, in pixels
+const MINIMAP_SIZE: f32 = 256.0;
+
+/// Overview of the entire layout, showing the node density, the
+/// current view, the selection, and the bookmarks. Clicking or
+/// dragging in the minimap moves the view
+pub struct MinimapWindow {
+    shared_state: SharedState,
+
+    top_left: Point,
+    bottom_right: Point,
+
+    columns: usize,
+    rows: usize,
+
+    // node density per cell, log-scaled to 0.0..=1.0, row-major
+    density: Vec<f32>,
+
+    // whether the view before the current click or drag has been
+    // added to the navigation history
+    history_pushed: bool,
+}
+
+impl MinimapWindow {
+    pub const ID: &'static str = "minimap_window";
+
+    pub fn new(shared_state: SharedState) -> Self {
+        Self {
+            shared_state,
+
+            top_left: Point::ZERO,
+            bottom_right: Point::ZERO,
+
+            columns: 0,
+            rows: 0,
+
+            density: Vec::new(),
+
+            history_pushed: false,
+        }
+    }
+
+    /// Rasterize the node positions of `layout` into the density
+    /// image
+    pub fn set_layout<L: GraphLayout>(&mut self, layout: &L) {
+        let (top_left, bottom_right) = layout.bounding_box();
+
+        let width = (bottom_right.x - top_left.x).max(1.0);
+        let height = (bottom_right.y - top_left.y).max(1.0);
+
+        let (columns, rows) = if width >= height {
+            let rows = (DENSITY_RES as f32 * height / width).round() as usize;
+            (DENSITY_RES, rows.max(1))
+        } else {
+            let cols = (DENSITY_RES as f32 * width / height).round() as usize;
+            (cols.max(1), DENSITY_RES)
+        };
+
+        let mut counts = vec![0usize; columns * rows];
+
+        for node in layout.nodes() {
+            let center = node.center();
+
+            let x = ((center.x - top_left.x) / width) * columns as f32;
+            let y = ((center.y - top_left.y) / height) * rows as f32;
+
+            let col = (x as usize).min(columns - 1);
+            let row = (y as usize).min(rows - 1);
+
+            counts[row * columns + col] += 1;
+        }
+
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let log_max = (1.0 + max as f32).ln();
+
+        self.density = counts
+            .into_iter()
+            .map(|count| {
+                if log_max > 0.0 {
+                    (1.0 + count as f32).ln() / log_max
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        self.top_left = top_left;
+        self.bottom_right = Point::new(top_left.x + width, top_left.y + height);
+
+        self.columns = columns;
+        self.rows = rows;
+    }
+
+    fn minimap_size(&self) -> egui::Vec2 {
+        let width = self.bottom_right.x - self.top_left.x;
+        let height = self.bottom_right.y - self.top_left.y;
+
+        if width >= height {
+            egui::vec2(MINIMAP_SIZE, (MINIMAP_SIZE * height / width).max(1.0))
+        } else {
+            egui::vec2((MINIMAP_SIZE * width / height).max(1.0), MINIMAP_SIZE)
+        }
+    }
+
+    fn world_to_minimap(&self, rect: egui::Rect, world: Point) -> egui::Pos2 {
+        let width = self.bottom_right.x - self.top_left.x;
+        let height = self.bottom_right.y - self.top_left.y;
+
+        let x = (world.x - self.top_left.x) / width;
+        let y = (world.y - self.top_left.y) / height;
+
+        rect.min + egui::vec2(x * rect.width(), y * rect.height())
+    }
+
+    fn minimap_to_world(&self, rect: egui::Rect, pos: egui::Pos2) -> Point {
+        let width = self.bottom_right.x - self.top_left.x;
+        let height = self.bottom_right.y - self.top_left.y;
+
+        let x = (pos.x - rect.min.x) / rect.width();
+        let y = (pos.y - rect.min.y) / rect.height();
+
+        Point::new(
+            self.top_left.x + x.clamp(0.0, 1.0) * width,
+            self.top_left.y + y.clamp(0.0, 1.0) * height,
+        )
+    }
+
+    fn world_rect(&self, rect: egui::Rect, world: Rect) -> egui::Rect {
+        egui::Rect::from_two_pos(
+            self.world_to_minimap(rect, world.min()),
+            self.world_to_minimap(rect, world.max()),
+        )
+    }
+
+    fn draw_density(&self, ui: &egui::Ui, rect: egui::Rect) {
+        let painter = ui.painter_at(rect);
+
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let [r, g, b, _] = ui.visuals().text_color().to_array();
+
+        let cell_size = egui::vec2(
+            rect.width() / self.columns as f32,
+            rect.height() / self.rows as f32,
+        );
+
+        let mut mesh = egui::epaint::Mesh::default();
+
+        for (ix, density) in self.density.iter().enumerate() {
+            if *density <= 0.0 {
+                continue;
+            }
+
+            let col = ix % self.columns;
+            let row = ix / self.columns;
+
+            let min = rect.min
+                + egui::vec2(
+                    col as f32 * cell_size.x,
+                    row as f32 * cell_size.y,
+                );
+
+            let alpha = (density * 255.0) as u8;
+            let color = egui::Color32::from_rgba_unmultiplied(r, g, b, alpha);
+
+            mesh.add_colored_rect(
+                egui::Rect::from_min_size(min, cell_size),
+                color,
+            );
+        }
+
+        painter.add(egui::Shape::Mesh(mesh));
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        egui::Window::new("Overview")
+            .id(egui::Id::new(Self::ID))
+            .resizable(false)
+            .open(open)
+            .show(ctx, |ui| {
+                if self.density.is_empty() {
+                    ui.label("No layout loaded");
+                    return;
+                }
+
+                let (rect, response) = ui.allocate_exact_size(
+                    self.minimap_size(),
+                    egui::Sense::click_and_drag(),
+                );
+
+                self.draw_density(ui, rect);
+
+                let painter = ui.painter_at(rect);
+
+                let view = self.shared_state.view();
+                let dims = self.shared_state.screen_dims();
+
+                let view_rect = Rect::new(
+                    view.screen_point_to_world(dims, Point::ZERO),
+                    view.screen_point_to_world(
+                        dims,
+                        Point::new(dims.width, dims.height),
+                    ),
+                );
+
+                let view_stroke = egui::Stroke::new(
+                    1.5,
+                    egui::Color32::from_rgb(230, 60, 60),
+                );
+                painter.rect_stroke(
+                    self.world_rect(rect, view_rect),
+                    0.0,
+                    view_stroke,
+                );
+
+                if let Some(selection) = self.shared_state.selection_bounds() {
+                    let selection_stroke = egui::Stroke::new(
+                        1.5,
+                        egui::Color32::from_rgb(240, 180, 30),
+                    );
+                    painter.rect_stroke(
+                        self.world_rect(rect, selection),
+                        0.0,
+                        selection_stroke,
+                    );
+                }
+
+                let hover_pos = response.hover_pos();
+                let mut hovered_bookmark = None;
+
+                {
+                    let bookmarks = self.shared_state.bookmarks().lock();
+
+                    let bookmark_color = egui::Color32::from_rgb(60, 140, 230);
+
+                    for bookmark in bookmarks.iter() {
+                        let pos =
+                            self.world_to_minimap(rect, bookmark.view.center);
+                        painter.circle_filled(pos, 3.0, bookmark_color);
+
+                        if let Some(hover) = hover_pos {
+                            if hover.distance(pos) <= 5.0 {
+                                hovered_bookmark = Some(bookmark.name.clone());
+                            }
+                        }
+                    }
+                }
+
+                if let Some(pos) = response.interact_pointer_pos() {
+                    if !self.history_pushed {
+                        self.shared_state.view_history().lock().push(view);
+                        self.history_pushed = true;
+                    }
+
+                    let mut view = view;
+                    view.center = self.minimap_to_world(rect, pos);
+                    self.shared_state.set_view(view);
+                } else {
+                    self.history_pushed = false;
+
+                    if let Some(name) = hovered_bookmark {
+                        response.on_hover_text(name);
+                    }
+                }
+            })
+    }
+}
@@ -1,5 +1,7 @@
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 use crossbeam::atomic::AtomicCell;
 
@@ -18,7 +20,10 @@ use crate::{
 };
 
 use crate::app::{OverlayCreatorMsg, OverlayState};
-use crate::overlays::OverlayKind;
+use crate::overlays::{
+    BuiltinOverlay, OverlayData, OverlayKind, OverlayLegend,
+    OverlayNormalization, OVERLAY_FILE_EXT,
+};
 
 use super::file::FilePicker;
 
@@ -30,14 +35,138 @@ pub struct OverlayList {
     gradient_picker: GradientPicker,
 
     gradient_picker_open: AtomicCell<bool>,
+
+    legend_open: AtomicCell<bool>,
+
+    // the normalization being edited, and the overlay it's for
+    normalization_input: Option<(usize, OverlayNormalization)>,
+    overlay_tx: crossbeam::channel::Sender<OverlayCreatorMsg>,
+
+    overlay_file_path: String,
+    // overlays are saved here as `<name>.gfo`, so saving never
+    // overwrites the file that was loaded
+    save_dir: String,
+
+    file_picker: FilePicker,
+    file_picker_open: bool,
+
+    overlay_files: Host<OverlayFileOp, Result<String, String>>,
+    latest_file_result: Option<Result<String, String>>,
+
+    builtin_overlay: BuiltinOverlay,
+    builtin_overlays: Host<BuiltinOverlay, String>,
+    latest_builtin_result: Option<String>,
+}
+
+pub enum OverlayFileOp {
+    Load {
+        path: PathBuf,
+    },
+    Save {
+        path: PathBuf,
+        name: String,
+        data: Arc<OverlayData>,
+        legend: Option<OverlayLegend>,
+    },
 }
 
 impl OverlayList {
     pub const ID: &'static str = "overlay_list_window";
+    pub const LEGEND_ID: &'static str = "overlay_legend_window";
 
-    pub fn new(overlay_state: OverlayState) -> Self {
+    pub fn new(reactor: &mut Reactor, overlay_state: OverlayState) -> Self {
         let gradient_picker = GradientPicker::new(overlay_state.clone());
 
+        let pwd = std::fs::canonicalize("./").unwrap();
+
+        let mut file_picker = FilePicker::new(
+            egui::Id::with(egui::Id::new(Self::ID), "file_picker"),
+            pwd,
+        )
+        .unwrap();
+
+        let extensions: [&str; 1] = [OVERLAY_FILE_EXT];
+        file_picker.set_visible_extensions(&extensions).unwrap();
+
+        let overlay_files = {
+            let tx = reactor.overlay_create_tx.clone();
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(
+                move |_outbox: &Outbox<Result<String, String>>,
+                      op: OverlayFileOp| {
+                    match op {
+                        OverlayFileOp::Load { path } => {
+                            let (name, data, legend) =
+                                crate::overlays::load_overlay_file(
+                                    &path,
+                                    graph.graph(),
+                                )
+                                .map_err(|err| format!("{}", err))?;
+
+                            let result = format!("Loaded overlay {}", name);
+                            let msg = OverlayCreatorMsg::NewOverlay {
+                                name,
+                                data,
+                                legend,
+                            };
+                            tx.send(msg).unwrap();
+                            Ok(result)
+                        }
+                        OverlayFileOp::Save {
+                            path,
+                            name,
+                            data,
+                            legend,
+                        } => {
+                            crate::overlays::save_overlay_file(
+                                &path,
+                                graph.graph(),
+                                &name,
+                                &data,
+                                legend.as_ref(),
+                            )
+                            .map_err(|err| format!("{}", err))?;
+
+                            Ok(format!(
+                                "Saved overlay {} to {}",
+                                name,
+                                path.display()
+                            ))
+                        }
+                    }
+                },
+            )
+        };
+
+        let builtin_overlays = {
+            let tx = reactor.overlay_create_tx.clone();
+            let rayon_pool = reactor.rayon_pool.clone();
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(
+                move |_outbox: &Outbox<String>, overlay: BuiltinOverlay| {
+                    let t0 = Instant::now();
+
+                    let (data, legend) =
+                        overlay.compute(&rayon_pool, graph.graph());
+
+                    let msg = OverlayCreatorMsg::ReplaceOverlay {
+                        name: overlay.name().to_string(),
+                        data,
+                        legend,
+                    };
+                    tx.send(msg).unwrap();
+
+                    format!(
+                        "Computed overlay {} in {:.2} s",
+                        overlay.name(),
+                        t0.elapsed().as_secs_f32()
+                    )
+                },
+            )
+        };
+
         Self {
             overlay_state,
             overlay_names: Default::default(),
@@ -45,6 +174,24 @@ impl OverlayList {
             gradient_picker,
 
             gradient_picker_open: AtomicCell::new(false),
+
+            legend_open: AtomicCell::new(false),
+
+            normalization_input: None,
+            overlay_tx: reactor.overlay_create_tx.clone(),
+
+            overlay_file_path: String::new(),
+            save_dir: String::new(),
+
+            file_picker,
+            file_picker_open: false,
+
+            overlay_files,
+            latest_file_result: None,
+
+            builtin_overlay: BuiltinOverlay::PathDepth,
+            builtin_overlays,
+            latest_builtin_result: None,
         }
     }
 
@@ -58,11 +205,35 @@ impl OverlayList {
     }
 
     pub fn ui(
-        &self,
+        &mut self,
         ctx: &egui::CtxRef,
         open: &mut bool,
         open_creator: &mut bool,
     ) -> Option<egui::InnerResponse<Option<()>>> {
+        if let Some(result) = self.overlay_files.take() {
+            match &result {
+                Ok(msg) => info!("{}", msg),
+                Err(err) => error!("Overlay file error: {}", err),
+            }
+            self.latest_file_result = Some(result);
+        }
+
+        if let Some(result) = self.builtin_overlays.take() {
+            info!("{}", result);
+            self.latest_builtin_result = Some(result);
+        }
+
+        if self.file_picker.selected_path().is_some() {
+            self.file_picker_open = false;
+        }
+
+        self.file_picker.ui(ctx, &mut self.file_picker_open);
+
+        if let Some(path) = self.file_picker.selected_path() {
+            let path_str = path.to_str().unwrap();
+            self.overlay_file_path = path_str.to_string();
+        }
+
         egui::Window::new("Overlay List")
             .id(egui::Id::new(Self::ID))
             .open(open)
@@ -84,8 +255,99 @@ impl OverlayList {
                     {
                         self.gradient_picker_open.store(!open_gradient_picker);
                     }
+
+                    let open_legend = self.legend_open.load();
+                    if ui.selectable_label(open_legend, "Legend").clicked() {
+                        self.legend_open.store(!open_legend);
+                    }
                 });
 
+                let overlay_file_path = &mut self.overlay_file_path;
+                let save_dir = &mut self.save_dir;
+                let file_picker = &mut self.file_picker;
+                let file_picker_open = &mut self.file_picker_open;
+                let overlay_files = &mut self.overlay_files;
+
+                ui.horizontal(|ui| {
+                    ui.label("Overlay file");
+                    ui.separator();
+                    ui.text_edit_singleline(overlay_file_path);
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Choose file").clicked() {
+                        file_picker.reset_selection();
+                        *file_picker_open = true;
+                    }
+
+                    let load_btn = ui.add(
+                        egui::Button::new("Load overlay")
+                            .enabled(!overlay_file_path.is_empty()),
+                    );
+
+                    if load_btn.clicked() {
+                        file_picker.reset_selection();
+                        let path = PathBuf::from(overlay_file_path.as_str());
+                        overlay_files
+                            .call(OverlayFileOp::Load { path })
+                            .unwrap();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Save directory");
+                    ui.separator();
+                    ui.text_edit_singleline(save_dir).on_hover_text(
+                        "Overlays are saved here as <name>.gfo; \
+                         defaults to the working directory",
+                    );
+                });
+
+                ui.separator();
+
+                let builtin_overlay = &mut self.builtin_overlay;
+                let builtin_overlays = &mut self.builtin_overlays;
+
+                ui.horizontal(|ui| {
+                    ui.label("Built-in overlay");
+
+                    egui::ComboBox::from_id_source("overlay_list_builtin")
+                        .selected_text(builtin_overlay.name())
+                        .show_ui(ui, |ui| {
+                            for overlay in BuiltinOverlay::ALL.iter() {
+                                ui.selectable_value(
+                                    builtin_overlay,
+                                    *overlay,
+                                    overlay.name(),
+                                )
+                                .on_hover_text(overlay.description());
+                            }
+                        });
+
+                    if ui.button("Compute").clicked() {
+                        builtin_overlays.call(*builtin_overlay).unwrap();
+                    }
+                });
+
+                if let Some(result) = &self.latest_builtin_result {
+                    ui.label(result);
+                }
+
+                ui.separator();
+
+                match &self.latest_file_result {
+                    Some(Ok(msg)) => {
+                        ui.label(msg);
+                    }
+                    Some(Err(err)) => {
+                        ui.label(format!("Error: {}", err));
+                    }
+                    None => (),
+                }
+
+                let overlay_state = &self.overlay_state;
+                let overlay_names = &self.overlay_names;
+
                 egui::Grid::new("overlay_list_window_grid").show(
                     &mut ui,
                     |ui| {
@@ -93,11 +355,11 @@ impl OverlayList {
                         ui.end_row();
 
                         let mut overlay_names =
-                            self.overlay_names.iter().collect::<Vec<_>>();
+                            overlay_names.iter().collect::<Vec<_>>();
                         overlay_names.sort_by_key(|(id, _)| *id);
 
                         let mut current_overlay =
-                            self.overlay_state.current_overlay();
+                            overlay_state.current_overlay();
 
                         for (id, (_kind, name)) in overlay_names {
                             if ui
@@ -108,14 +370,49 @@ impl OverlayList {
                                 )
                                 .clicked()
                             {
-                                self.overlay_state
+                                overlay_state
                                     .set_current_overlay(current_overlay);
                             }
 
+                            let data = overlay_state.overlay_data(*id);
+
+                            let save_btn = ui.add(
+                                egui::Button::new("Save")
+                                    .enabled(data.is_some()),
+                            );
+
+                            if let (true, Some(data)) =
+                                (save_btn.clicked(), data)
+                            {
+                                let path = PathBuf::from(save_dir.as_str())
+                                    .join(format!(
+                                        "{}.{}",
+                                        name, OVERLAY_FILE_EXT
+                                    ));
+
+                                overlay_files
+                                    .call(OverlayFileOp::Save {
+                                        path,
+                                        name: name.to_string(),
+                                        data,
+                                        legend: overlay_state
+                                            .overlay_legend(*id),
+                                    })
+                                    .unwrap();
+                            }
+
                             ui.end_row();
                         }
                     },
                 );
+
+                normalization_ui(
+                    ui,
+                    overlay_state,
+                    overlay_names,
+                    &mut self.normalization_input,
+                    &self.overlay_tx,
+                );
             })
     }
 
@@ -128,6 +425,254 @@ impl OverlayList {
         self.gradient_picker_open.store(open);
         resp
     }
+
+    /// Shows what the colors of the active overlay stand for: the
+    /// gradient and original value range of value overlays, or the
+    /// value of each color of categorical overlays
+    pub fn legend_ui(
+        &self,
+        ctx: &egui::CtxRef,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        let mut open = self.legend_open.load();
+
+        let overlay_state = &self.overlay_state;
+        let overlay_names = &self.overlay_names;
+
+        let resp = egui::Window::new("Legend")
+            .id(egui::Id::new(Self::LEGEND_ID))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.set_min_width(200.0);
+                ui.set_max_width(200.0);
+
+                let overlay = overlay_state
+                    .current_overlay()
+                    .and_then(|id| Some((id, overlay_names.get(&id)?)));
+
+                let (overlay_id, (kind, name)) = match overlay {
+                    Some(overlay) => overlay,
+                    None => {
+                        ui.label("No overlay selected");
+                        return;
+                    }
+                };
+
+                ui.heading(name);
+
+                let legend = overlay_state.overlay_legend(overlay_id);
+
+                match (*kind, legend) {
+                    (OverlayKind::Value, legend) => {
+                        let gradient = overlay_state.gradient();
+                        ui.image(
+                            gradient.texture_id(),
+                            Point { x: 200.0, y: 15.0 },
+                        );
+
+                        let (min, max, mut units) = match legend {
+                            Some(OverlayLegend::Range { min, max, units }) => {
+                                (min, max, Some(units))
+                            }
+                            _ => (0.0, 1.0, None),
+                        };
+
+                        let units_str =
+                            units.as_ref().map(|u| u.as_str()).unwrap_or("");
+
+                        let normalization =
+                            overlay_state.overlay_normalization(overlay_id);
+
+                        let label_at = |t: f32| {
+                            normalization.value_at(t, min, max).map(|v| {
+                                OverlayLegend::format_value(v, units_str)
+                            })
+                        };
+
+                        let (low, mid, high) =
+                            (label_at(0.0), label_at(0.5), label_at(1.0));
+
+                        ui.columns(3, |columns| {
+                            if let Some(low) = low {
+                                columns[0].label(low);
+                            }
+                            if let Some(mid) = mid {
+                                columns[1].with_layout(
+                                    egui::Layout::top_down(egui::Align::Center),
+                                    |ui| {
+                                        ui.label(mid);
+                                    },
+                                );
+                            }
+                            if let Some(high) = high {
+                                columns[2].with_layout(
+                                    egui::Layout::right_to_left(),
+                                    |ui| {
+                                        ui.label(high);
+                                    },
+                                );
+                            }
+                        });
+
+                        if normalization != OverlayNormalization::Linear {
+                            ui.label(format!(
+                                "{} normalization",
+                                normalization.name()
+                            ));
+                        }
+
+                        if let Some(units) = units.as_mut() {
+                            ui.horizontal(|ui| {
+                                ui.label("Units");
+                                if ui.text_edit_singleline(units).changed() {
+                                    overlay_state
+                                        .set_overlay_units(overlay_id, units);
+                                }
+                            });
+                        } else {
+                            ui.label("The original value range is unknown");
+                        }
+                    }
+                    (
+                        OverlayKind::RGB,
+                        Some(OverlayLegend::Categories(cats)),
+                    ) => {
+                        egui::ScrollArea::from_max_height(300.0).show(
+                            ui,
+                            |ui| {
+                                egui::Grid::new("overlay_legend_categories")
+                                    .show(ui, |ui| {
+                                        for (color, value) in cats.iter() {
+                                            legend_swatch(ui, *color);
+                                            ui.label(value);
+                                            ui.end_row();
+                                        }
+                                    });
+                            },
+                        );
+                    }
+                    (OverlayKind::RGB, _) => {
+                        ui.label("No legend available for this overlay");
+                    }
+                }
+            });
+
+        self.legend_open.store(open);
+        resp
+    }
+}
+
+/// Lets the user pick the normalization of the active overlay, if
+/// it's a value overlay
+fn normalization_ui(
+    ui: &mut egui::Ui,
+    overlay_state: &OverlayState,
+    overlay_names: &FxHashMap<usize, (OverlayKind, String)>,
+    input: &mut Option<(usize, OverlayNormalization)>,
+    overlay_tx: &crossbeam::channel::Sender<OverlayCreatorMsg>,
+) {
+    let overlay_id = match overlay_state.current_overlay() {
+        Some(id)
+            if matches!(
+                overlay_names.get(&id),
+                Some((OverlayKind::Value, _))
+            ) =>
+        {
+            id
+        }
+        _ => return,
+    };
+
+    let current = overlay_state.overlay_normalization(overlay_id);
+
+    if input.map(|(id, _)| id) != Some(overlay_id) {
+        *input = Some((overlay_id, current));
+    }
+
+    let (_, normalization) = input.as_mut().unwrap();
+
+    // defaults for the parameters of the modes that have them
+    let (min, max) = match overlay_state.overlay_legend(overlay_id) {
+        Some(OverlayLegend::Range { min, max, .. }) => (min, max),
+        _ => (0.0, 1.0),
+    };
+    let speed = ((max - min) / 100.0).max(0.001) as f64;
+
+    let modes = [
+        OverlayNormalization::Linear,
+        OverlayNormalization::Log,
+        OverlayNormalization::Clamp { min, max },
+        OverlayNormalization::Quantile,
+        OverlayNormalization::Diverging {
+            mid: (min + max) / 2.0,
+        },
+    ];
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.label("Normalization");
+
+        egui::ComboBox::from_id_source("overlay_list_normalization")
+            .selected_text(normalization.name())
+            .show_ui(ui, |ui| {
+                for mode in modes.iter() {
+                    let selected = normalization.name() == mode.name();
+                    if ui.selectable_label(selected, mode.name()).clicked()
+                        && !selected
+                    {
+                        *normalization = *mode;
+                    }
+                }
+            });
+    });
+
+    match normalization {
+        OverlayNormalization::Clamp { min, max } => {
+            ui.horizontal(|ui| {
+                ui.label("Min");
+                ui.add(egui::DragValue::new::<f32>(min).speed(speed));
+                ui.label("Max");
+                ui.add(egui::DragValue::new::<f32>(max).speed(speed));
+            });
+        }
+        OverlayNormalization::Diverging { mid } => {
+            ui.horizontal(|ui| {
+                ui.label("Midpoint");
+                ui.add(egui::DragValue::new::<f32>(mid).speed(speed));
+            });
+        }
+        _ => (),
+    }
+
+    let apply_btn = ui.add(
+        egui::Button::new("Apply normalization")
+            .enabled(*normalization != current),
+    );
+
+    if apply_btn.clicked() {
+        overlay_tx
+            .send(OverlayCreatorMsg::SetNormalization {
+                overlay_id,
+                normalization: *normalization,
+            })
+            .unwrap();
+    }
+}
+
+fn legend_swatch(ui: &mut egui::Ui, color: rgb::RGBA<f32>) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+
+    let r = (color.r * 255.0) as u8;
+    let g = (color.g * 255.0) as u8;
+    let b = (color.b * 255.0) as u8;
+    let a = (color.a * 255.0) as u8;
+
+    ui.painter().rect_filled(
+        rect,
+        2.0,
+        egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+    );
 }
 
 #[derive(Debug, Clone)]
@@ -142,6 +687,9 @@ pub struct ScriptInput {
     path: PathBuf,
 
     config: ScriptConfig,
+
+    // if true, replace the overlay with the same name, if it exists
+    replace: bool,
 }
 
 pub enum ScriptMsg {
@@ -176,6 +724,14 @@ pub struct OverlayCreator {
 
     script_results: Host<ScriptInput, ScriptResult>,
     latest_result: Option<ScriptResult>,
+
+    watch_script: bool,
+    // the script to re-run when the file changes, and the last
+    // modification time that was run
+    watched_script: Option<(ScriptInput, Option<SystemTime>)>,
+    last_poll: Instant,
+
+    console_reports: Vec<String>,
 }
 
 impl OverlayCreator {
@@ -222,10 +778,20 @@ impl OverlayCreator {
                     );
 
                     let feedback = match overlay_data {
-                        Ok(data) => {
-                            let msg = OverlayCreatorMsg::NewOverlay {
-                                name: input.name,
-                                data,
+                        Ok((data, legend)) => {
+                            let name = input.name;
+                            let msg = if input.replace {
+                                OverlayCreatorMsg::ReplaceOverlay {
+                                    name,
+                                    data,
+                                    legend,
+                                }
+                            } else {
+                                OverlayCreatorMsg::NewOverlay {
+                                    name,
+                                    data,
+                                    legend,
+                                }
                             };
                             tx.send(msg).unwrap();
                             Ok(())
@@ -252,9 +818,55 @@ impl OverlayCreator {
 
             script_results,
             latest_result: None,
+
+            watch_script: false,
+            watched_script: None,
+            last_poll: Instant::now(),
+
+            console_reports: Vec::new(),
         })
     }
 
+    /// Messages that should be shown in the console output, e.g.
+    /// errors from reloading a watched script
+    pub fn take_console_reports(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.console_reports)
+    }
+
+    fn file_modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    fn poll_watched_script(&mut self) {
+        if self.last_poll.elapsed() < Duration::from_millis(500) {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        let is_running =
+            matches!(self.latest_result, Some(Err(ScriptMsg::Running(_))));
+
+        if is_running {
+            return;
+        }
+
+        if let Some((input, last_modified)) = self.watched_script.as_mut() {
+            let modified = Self::file_modified(&input.path);
+
+            if modified.is_some() && modified != *last_modified {
+                *last_modified = modified;
+
+                self.console_reports.push(format!(
+                    ">>> Reloading overlay script '{}'",
+                    input.path.display()
+                ));
+                self.script_results.call(input.clone()).unwrap();
+            }
+        }
+    }
+
     pub fn ui(
         &mut self,
         ctx: &egui::CtxRef,
@@ -271,14 +883,20 @@ impl OverlayCreator {
 
             if let Err(ScriptMsg::IOError(err)) = &result {
                 error!("Overlay script IO error: {:?}", err);
+                self.console_reports
+                    .push(format!("Overlay script IO error: {}", err));
             }
             if let Err(ScriptMsg::ScriptError(err)) = &result {
                 error!("Overlay script execution error: {:?}", err);
+                self.console_reports
+                    .push(format!("Overlay script error: {}", err));
             }
 
             self.latest_result = Some(result);
         }
 
+        self.poll_watched_script();
+
         let pos = egui::pos2(scr.center().x - 150.0, scr.center().y - 60.0);
 
         if self.file_picker.selected_path().is_some() {
@@ -307,6 +925,8 @@ impl OverlayCreator {
                 let file_picker_open = &mut self.file_picker_open;
 
                 let script_results = &mut self.script_results;
+                let watch_script = &mut self.watch_script;
+                let watched_script = &mut self.watched_script;
 
                 let _name_box = ui.horizontal(|ui| {
                     ui.label("Overlay name");
@@ -356,12 +976,40 @@ impl OverlayCreator {
                             name: name.to_string(),
                             path,
                             config,
+                            replace: *watch_script,
                         };
 
+                        if *watch_script {
+                            let modified =
+                                Self::file_modified(&script_input.path);
+                            *watched_script =
+                                Some((script_input.clone(), modified));
+                        }
+
                         script_results.call(script_input).unwrap();
                     }
                 });
 
+                ui.checkbox(watch_script, "Reload on file change");
+
+                let mut stop_watching = false;
+
+                if let Some((input, _)) = watched_script.as_ref() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Watching {} ({})",
+                            input.path.display(),
+                            input.name
+                        ));
+
+                        stop_watching = ui.button("Stop").clicked();
+                    });
+                }
+
+                if stop_watching {
+                    *watched_script = None;
+                }
+
                 match &self.latest_result {
                     Some(Err(ScriptMsg::IOError(err))) => {
                         ui.label(format!("IO Error: {:?}", err));
@@ -0,0 +1,290 @@
+use handlegraph::{handlegraph::*, pathhandlegraph::*};
+
+use std::path::PathBuf;
+
+use crate::app::path_comparison::{
+    PathComparison, SegmentKind, MAX_COMPARED_PATHS,
+};
+use crate::app::OverlayCreatorMsg;
+use crate::reactor::{Host, Outbox, Reactor};
+
+use super::graph_picker::PathPicker;
+
+/// The name of the overlay created by a comparison, replaced by
+/// every new comparison
+const OVERLAY_NAME: &str = "Path comparison";
+
+/// The most segments listed in the window
+const MAX_LISTED_SEGMENTS: usize = 1000;
+
+/// Compares two or more paths: their Jaccard similarity, the path
+/// ranges they share or cover alone, and an overlay coloring nodes by
+/// which of the paths cover them
+pub struct PathComparisonWindow {
+    paths: Vec<(PathId, String)>,
+
+    path_picker_open: bool,
+    path_picker: PathPicker,
+
+    comparison_host: Host<Vec<PathId>, Result<PathComparison, String>>,
+    comparison: Option<PathComparison>,
+    comparison_error: Option<String>,
+    comparing: bool,
+
+    only_divergent: bool,
+
+    bed_path: String,
+    bed_result: Option<String>,
+}
+
+impl PathComparisonWindow {
+    pub const ID: &'static str = "path_comparison_window";
+
+    pub fn new(reactor: &mut Reactor, path_picker: PathPicker) -> Self {
+        let comparison_host = {
+            let tx = reactor.overlay_create_tx.clone();
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(
+                move |_outbox: &Outbox<Result<PathComparison, String>>,
+                      paths: Vec<PathId>| {
+                    let comparison = PathComparison::new(&graph, &paths)
+                        .map_err(|err| err.to_string())?;
+
+                    let (data, legend) =
+                        comparison.overlay(graph.graph().node_count());
+
+                    let msg = OverlayCreatorMsg::ReplaceOverlay {
+                        name: OVERLAY_NAME.to_string(),
+                        data,
+                        legend,
+                    };
+                    tx.send(msg).unwrap();
+
+                    Ok(comparison)
+                },
+            )
+        };
+
+        Self {
+            paths: Vec::new(),
+
+            path_picker_open: false,
+            path_picker,
+
+            comparison_host,
+            comparison: None,
+            comparison_error: None,
+            comparing: false,
+
+            only_divergent: true,
+
+            bed_path: String::new(),
+            bed_result: None,
+        }
+    }
+
+    fn export_bed(&self) -> anyhow::Result<usize> {
+        let comparison = self
+            .comparison
+            .as_ref()
+            .ok_or(anyhow::anyhow!("No paths have been compared"))?;
+
+        let path = PathBuf::from(&self.bed_path);
+        let file = std::fs::File::create(path)?;
+        let mut writer = std::io::BufWriter::new(file);
+
+        comparison.write_divergent_bed(&mut writer)?;
+
+        Ok(comparison.divergent_segments().count())
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if let Some(result) = self.comparison_host.take() {
+            match result {
+                Ok(comparison) => {
+                    self.comparison = Some(comparison);
+                    self.comparison_error = None;
+                }
+                Err(err) => {
+                    log::error!("Error comparing paths: {}", err);
+                    self.comparison_error = Some(err);
+                }
+            }
+            self.comparing = false;
+        }
+
+        self.path_picker.ui(ctx, &mut self.path_picker_open);
+
+        egui::Window::new("Compare paths")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let path = self.path_picker.active_path();
+
+                    let path_name = path.map(|(_, name)| name).unwrap_or("-");
+                    ui.label(path_name);
+
+                    if ui.button("Choose path").clicked() {
+                        self.path_picker_open = true;
+                    }
+
+                    let can_add = self.paths.len() < MAX_COMPARED_PATHS;
+
+                    let add_btn = ui.add(
+                        egui::Button::new("Add")
+                            .enabled(path.is_some() && can_add),
+                    );
+
+                    if let Some((path, name)) = path {
+                        let exists = self.paths.iter().any(|(p, _)| *p == path);
+
+                        if add_btn.clicked() && !exists {
+                            self.paths.push((path, name.to_string()));
+                        }
+                    }
+                });
+
+                let mut to_remove = None;
+
+                egui::Grid::new("path_comparison_paths").show(ui, |ui| {
+                    for (ix, (_, name)) in self.paths.iter().enumerate() {
+                        ui.label(name);
+
+                        if ui.button("Remove").clicked() {
+                            to_remove = Some(ix);
+                        }
+
+                        ui.end_row();
+                    }
+                });
+
+                if let Some(ix) = to_remove {
+                    self.paths.remove(ix);
+                }
+
+                let compare_btn = ui.add(
+                    egui::Button::new("Compare")
+                        .enabled(self.paths.len() >= 2 && !self.comparing),
+                );
+
+                if compare_btn.clicked() {
+                    let paths = self.paths.iter().map(|(p, _)| *p).collect();
+                    self.comparison_host.call(paths).unwrap();
+                    self.comparing = true;
+                }
+
+                if self.comparing {
+                    ui.label("Comparing paths...");
+                }
+
+                if let Some(err) = &self.comparison_error {
+                    ui.label(format!("Error comparing paths: {}", err));
+                }
+
+                if self.comparison.is_some() {
+                    ui.separator();
+                    self.comparison_ui(ui);
+                }
+            })
+    }
+
+    fn comparison_ui(&mut self, ui: &mut egui::Ui) {
+        let comparison = self.comparison.as_ref().unwrap();
+
+        ui.label(format!(
+            "Jaccard similarity: {:.3} (nodes), {:.3} (bases)",
+            comparison.jaccard.nodes, comparison.jaccard.bases
+        ));
+
+        let path_count = comparison.paths.len();
+
+        if path_count > 2 {
+            ui.collapsing("Pairwise similarity (nodes)", |ui| {
+                egui::Grid::new("path_comparison_pairwise")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for (_, name) in comparison.paths.iter() {
+                            ui.label(name);
+                        }
+                        ui.end_row();
+
+                        for (i, (_, name)) in
+                            comparison.paths.iter().enumerate()
+                        {
+                            ui.label(name);
+                            for j in 0..path_count {
+                                let jaccard = comparison.pair(i, j);
+                                ui.label(format!("{:.3}", jaccard.nodes));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        }
+
+        ui.separator();
+
+        ui.checkbox(&mut self.only_divergent, "Only divergent segments");
+
+        let only_divergent = self.only_divergent;
+
+        let segments = comparison
+            .segments
+            .iter()
+            .filter(|s| !only_divergent || s.kind != SegmentKind::Shared)
+            .take(MAX_LISTED_SEGMENTS);
+
+        egui::ScrollArea::from_max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("path_comparison_segments")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Path");
+                    ui.label("Start");
+                    ui.label("End");
+                    ui.label("Kind");
+                    ui.end_row();
+
+                    for segment in segments {
+                        let (_, name) = &comparison.paths[segment.path_ix];
+
+                        ui.label(name);
+                        ui.label(segment.start.to_string());
+                        ui.label(segment.end.to_string());
+                        ui.label(segment.kind.name());
+                        ui.end_row();
+                    }
+                });
+        });
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("BED file");
+            ui.text_edit_singleline(&mut self.bed_path);
+        });
+
+        if ui.button("Export divergent regions").clicked() {
+            let result = match self.export_bed() {
+                Ok(count) => {
+                    format!("Wrote {} regions to {}", count, self.bed_path)
+                }
+                Err(err) => {
+                    log::error!("Error exporting BED: {:?}", err);
+                    format!("Error exporting BED: {}", err)
+                }
+            };
+            self.bed_result = Some(result);
+        }
+
+        if let Some(result) = &self.bed_result {
+            ui.label(result);
+        }
+    }
+}
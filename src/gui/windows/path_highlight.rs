@@ -0,0 +1,364 @@
+#[allow(unused_imports)]
+use handlegraph::{
+    handle::{Direction, Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use crossbeam::channel::{Receiver, Sender};
+
+use bstr::ByteSlice;
+
+use crate::app::path_highlight::{
+    arrow_heads, lane_offset, path_highlight_color, PathPolyline,
+};
+use crate::app::{PathHighlightMsg, SharedState};
+use crate::geometry::{Point, Rect};
+use crate::graph_query::GraphQuery;
+use crate::universe::Node;
+
+use super::graph_picker::PathPicker;
+
+/// The distance between two direction arrows along a path, in pixels
+const ARROW_SPACING: f32 = 80.0;
+
+/// Consecutive points of a path closer than this on the screen, in
+/// pixels, are merged when drawing
+const MIN_SEGMENT_LENGTH: f32 = 1.0;
+
+fn painter_layer() -> egui::LayerId {
+    egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("gui_path_highlight_background"),
+    )
+}
+
+struct PathHighlight {
+    path: PathId,
+    name: String,
+    color: rgb::RGBA<f32>,
+
+    // built the first time the path is drawn
+    polyline: Option<PathPolyline>,
+}
+
+/// Draws the highlighted paths as colored lines through the centers
+/// of their nodes, in step order, each offset to its own lane
+pub struct PathHighlightWindow {
+    show_highlights: bool,
+    show_arrows: bool,
+
+    // in pixels
+    line_width: f32,
+    lane_spacing: f32,
+
+    highlights: Vec<PathHighlight>,
+    next_color: usize,
+
+    path_picker_open: bool,
+    path_picker: PathPicker,
+
+    highlight_tx: Sender<PathHighlightMsg>,
+    highlight_rx: Receiver<PathHighlightMsg>,
+}
+
+impl PathHighlightWindow {
+    pub const ID: &'static str = "path_highlight_window";
+
+    pub fn new(
+        path_picker: PathPicker,
+        highlight_tx: Sender<PathHighlightMsg>,
+        highlight_rx: Receiver<PathHighlightMsg>,
+    ) -> Self {
+        Self {
+            show_highlights: true,
+            show_arrows: true,
+
+            line_width: 3.0,
+            lane_spacing: 5.0,
+
+            highlights: Vec::new(),
+            next_color: 0,
+
+            path_picker_open: false,
+            path_picker,
+
+            highlight_tx,
+            highlight_rx,
+        }
+    }
+
+    pub fn sender(&self) -> &Sender<PathHighlightMsg> {
+        &self.highlight_tx
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        self.path_picker.ui(ctx, &mut self.path_picker_open);
+
+        let show_highlights = &mut self.show_highlights;
+        let show_arrows = &mut self.show_arrows;
+        let line_width = &mut self.line_width;
+        let lane_spacing = &mut self.lane_spacing;
+
+        let highlights = &mut self.highlights;
+
+        let path_picker = &self.path_picker;
+        let path_picker_open = &mut self.path_picker_open;
+
+        let highlight_tx = &self.highlight_tx;
+
+        egui::Window::new("Path highlights")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.checkbox(show_highlights, "Show highlighted paths");
+                ui.checkbox(show_arrows, "Show direction arrows");
+
+                ui.add(
+                    egui::Slider::new::<f32>(line_width, 1.0..=10.0)
+                        .text("Line width (px)"),
+                );
+                ui.add(
+                    egui::Slider::new::<f32>(lane_spacing, 0.0..=20.0)
+                        .text("Lane spacing (px)"),
+                );
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    let path = path_picker.active_path();
+
+                    let path_name = path.map(|(_, name)| name).unwrap_or("-");
+                    ui.label(path_name);
+
+                    if ui.button("Choose path").clicked() {
+                        *path_picker_open = true;
+                    }
+
+                    let add_btn = ui.add(
+                        egui::Button::new("Highlight").enabled(path.is_some()),
+                    );
+
+                    if let Some((path, _)) = path {
+                        if add_btn.clicked() {
+                            highlight_tx
+                                .send(PathHighlightMsg::Add {
+                                    path,
+                                    color: None,
+                                })
+                                .unwrap();
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                let mut to_remove = None;
+
+                egui::Grid::new("path_highlight_list_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for highlight in highlights.iter_mut() {
+                            let color = &mut highlight.color;
+                            let mut rgba = [color.r, color.g, color.b, color.a];
+
+                            if ui
+                                .color_edit_button_rgba_unmultiplied(&mut rgba)
+                                .changed()
+                            {
+                                let [r, g, b, a] = rgba;
+                                *color = rgb::RGBA::new(r, g, b, a);
+                            }
+
+                            ui.label(&highlight.name);
+
+                            if ui.button("Remove").clicked() {
+                                to_remove = Some(highlight.path);
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(path) = to_remove {
+                    highlights.retain(|h| h.path != path);
+                }
+
+                if ui.button("Clear all").clicked() {
+                    highlights.clear();
+                }
+            })
+    }
+
+    fn apply_msgs(&mut self, graph_query: &GraphQuery) {
+        while let Ok(msg) = self.highlight_rx.try_recv() {
+            match msg {
+                PathHighlightMsg::Add { path, color } => {
+                    let graph = graph_query.graph();
+
+                    let name = if let Some(name) = graph.get_path_name_vec(path)
+                    {
+                        name.to_str_lossy().into_owned()
+                    } else {
+                        log::warn!("Can't highlight missing path {}", path.0);
+                        continue;
+                    };
+
+                    let color = color.unwrap_or_else(|| {
+                        let color = path_highlight_color(self.next_color);
+                        self.next_color += 1;
+                        color
+                    });
+
+                    if let Some(highlight) =
+                        self.highlights.iter_mut().find(|h| h.path == path)
+                    {
+                        highlight.color = color;
+                    } else {
+                        self.highlights.push(PathHighlight {
+                            path,
+                            name,
+                            color,
+                            polyline: None,
+                        });
+                    }
+                }
+                PathHighlightMsg::Remove(path) => {
+                    self.highlights.retain(|h| h.path != path);
+                }
+                PathHighlightMsg::Clear => {
+                    self.highlights.clear();
+                }
+            }
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::CtxRef,
+        shared_state: &SharedState,
+        graph_query: &GraphQuery,
+        nodes: &[Node],
+    ) {
+        self.apply_msgs(graph_query);
+
+        if !self.show_highlights || self.highlights.is_empty() {
+            return;
+        }
+
+        let view = shared_state.view();
+        let screen_dims = shared_state.screen_dims();
+
+        let offset = Point::new(screen_dims.width, screen_dims.height) / 2.0;
+
+        let margin = self.line_width * 2.0;
+        let clip: Rect = ctx.input().screen_rect().expand(margin).into();
+
+        let world_clip = Rect::new(
+            view.screen_point_to_world(screen_dims, clip.min()),
+            view.screen_point_to_world(screen_dims, clip.max()),
+        );
+
+        let painter = ctx.layer_painter(painter_layer());
+
+        let lanes = self.highlights.len();
+
+        for (lane, highlight) in self.highlights.iter_mut().enumerate() {
+            let path = highlight.path;
+
+            let polyline = highlight.polyline.get_or_insert_with(|| {
+                let steps = graph_query
+                    .graph()
+                    .path_steps(path)
+                    .map(|steps| {
+                        steps.map(|step| step.handle()).collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                PathPolyline::from_steps(nodes, steps)
+            });
+
+            let world_offset =
+                lane_offset(lane, lanes, self.lane_spacing) * view.scale;
+
+            let color = highlight.color;
+            let color = egui::Color32::from_rgba_unmultiplied(
+                (color.r * 255.0) as u8,
+                (color.g * 255.0) as u8,
+                (color.b * 255.0) as u8,
+                (color.a * 255.0) as u8,
+            );
+            let stroke = egui::Stroke::new(self.line_width, color);
+
+            let runs = polyline
+                .visible_ranges(world_clip, world_offset)
+                .into_iter()
+                .flat_map(|range| {
+                    let screen_points = polyline
+                        .offset_points(range, world_offset)
+                        .map(|p| view.world_point_to_screen(p) + offset);
+
+                    visible_runs(screen_points, clip)
+                });
+
+            for run in runs {
+                if self.show_arrows {
+                    let size = self.line_width * 2.0 + 4.0;
+
+                    for arrow in arrow_heads(&run, ARROW_SPACING, size) {
+                        let points = arrow.iter().map(|&p| p.into()).collect();
+                        painter.add(egui::Shape::convex_polygon(
+                            points,
+                            color,
+                            egui::Stroke::none(),
+                        ));
+                    }
+                }
+
+                let points = run.into_iter().map(|p| p.into()).collect();
+                painter.add(egui::Shape::line(points, stroke));
+            }
+        }
+    }
+}
+
+/// Splits a polyline into the runs of consecutive segments that
+/// overlap `clip`, merging points that are too close to be told apart
+fn visible_runs(
+    points: impl Iterator<Item = Point>,
+    clip: Rect,
+) -> Vec<Vec<Point>> {
+    let mut runs = Vec::new();
+    let mut run: Vec<Point> = Vec::new();
+
+    let mut prev: Option<Point> = None;
+
+    for p in points {
+        if let Some(p0) = prev {
+            if p0.dist(p) < MIN_SEGMENT_LENGTH {
+                continue;
+            }
+
+            if clip.intersects(Rect::new(p0, p)) {
+                if run.is_empty() {
+                    run.push(p0);
+                }
+                run.push(p);
+            } else if !run.is_empty() {
+                runs.push(std::mem::take(&mut run));
+            }
+        }
+
+        prev = Some(p);
+    }
+
+    if run.len() > 1 {
+        runs.push(run);
+    }
+
+    runs
+}
@@ -0,0 +1,424 @@
+use handlegraph::handle::NodeId;
+
+use crossbeam::channel::{Receiver, Sender};
+use rustc_hash::FxHashSet;
+
+use std::sync::Arc;
+
+use crate::app::path_matrix::{PathMatrix, STEP_FORWARD, STEP_REVERSE};
+use crate::app::selection::NodeSelection;
+use crate::app::{AppMsg, Select};
+use crate::geometry::Rect;
+use crate::reactor::{Host, Outbox, Reactor};
+
+/// The width of the path name column, in pixels
+const NAME_WIDTH: f32 = 120.0;
+
+/// The width of the matrix, in pixels, which is also the number of
+/// bins each row is split into
+const MATRIX_WIDTH: f32 = 600.0;
+
+/// The shortest range of the linearization that can be shown, in bp
+const MIN_VIEW_LEN: f64 = 10.0;
+
+fn step_color(flags: u8) -> Option<egui::Color32> {
+    let both = STEP_FORWARD | STEP_REVERSE;
+
+    match flags {
+        0 => None,
+        STEP_FORWARD => Some(egui::Color32::from_rgb(60, 140, 230)),
+        STEP_REVERSE => Some(egui::Color32::from_rgb(230, 60, 60)),
+        f if f == both => Some(egui::Color32::from_rgb(170, 90, 200)),
+        _ => None,
+    }
+}
+
+/// The binned rows for a range of the linearization, rebuilt when
+/// the range changes
+struct BinnedRows {
+    start: f64,
+    end: f64,
+
+    rows: Vec<Vec<u8>>,
+
+    // whether each bin contains a selected node
+    selected: Vec<bool>,
+}
+
+/// Shows the graph linearized in node ID order along the x axis, with
+/// one row per path showing which nodes it covers, and in which
+/// orientation. Clicking a node selects it in the main view
+pub struct PathMatrixWindow {
+    matrix: Option<Arc<PathMatrix>>,
+    matrix_host: Host<(), PathMatrix>,
+    matrix_requested: bool,
+
+    // the visible range of the linearization, in bp
+    view_start: f64,
+    view_len: f64,
+
+    row_height: f32,
+
+    binned: Option<BinnedRows>,
+
+    selection: NodeSelection,
+    selection_rx: Option<Receiver<(Rect, FxHashSet<NodeId>)>>,
+
+    app_msg_tx: Sender<AppMsg>,
+}
+
+impl PathMatrixWindow {
+    pub const ID: &'static str = "path_matrix_window";
+
+    pub fn new(reactor: &mut Reactor, app_msg_tx: Sender<AppMsg>) -> Self {
+        let matrix_host = {
+            let rayon_pool = reactor.rayon_pool.clone();
+            let graph = reactor.graph_query.clone();
+
+            reactor.create_host(move |_outbox: &Outbox<PathMatrix>, _: ()| {
+                PathMatrix::new(&rayon_pool, &graph)
+            })
+        };
+
+        Self {
+            matrix: None,
+            matrix_host,
+            matrix_requested: false,
+
+            view_start: 0.0,
+            view_len: 0.0,
+
+            row_height: 8.0,
+
+            binned: None,
+
+            selection: NodeSelection::default(),
+            selection_rx: None,
+
+            app_msg_tx,
+        }
+    }
+
+    fn reset_view(&mut self) {
+        if let Some(matrix) = &self.matrix {
+            self.view_start = 0.0;
+            self.view_len = (matrix.total_len() as f64).max(MIN_VIEW_LEN);
+        }
+    }
+
+    fn zoom(&mut self, factor: f64) {
+        let total = self
+            .matrix
+            .as_ref()
+            .map(|m| m.total_len() as f64)
+            .unwrap_or(0.0)
+            .max(MIN_VIEW_LEN);
+
+        let center = self.view_start + self.view_len / 2.0;
+
+        self.view_len = (self.view_len * factor).clamp(MIN_VIEW_LEN, total);
+        self.view_start = center - self.view_len / 2.0;
+        self.clamp_view();
+    }
+
+    fn clamp_view(&mut self) {
+        let total = self
+            .matrix
+            .as_ref()
+            .map(|m| m.total_len() as f64)
+            .unwrap_or(0.0);
+
+        let max_start = (total - self.view_len).max(0.0);
+        self.view_start = self.view_start.clamp(0.0, max_start);
+    }
+
+    fn update_binned(&mut self, bins: usize) {
+        let matrix = if let Some(matrix) = &self.matrix {
+            matrix
+        } else {
+            return;
+        };
+
+        let start = self.view_start;
+        let end = self.view_start + self.view_len;
+
+        if let Some(binned) = &self.binned {
+            if binned.start == start
+                && binned.end == end
+                && binned.rows.first().map(|r| r.len()) == Some(bins)
+            {
+                return;
+            }
+        }
+
+        let rows = (0..matrix.rows.len())
+            .map(|row| matrix.bin_row(row, start, end, bins))
+            .collect();
+
+        let mut selected = vec![false; bins];
+
+        for &node in self.selection.nodes.iter() {
+            let bins = matrix
+                .node_ix(node)
+                .and_then(|ix| matrix.node_bins(ix, start, end, bins));
+
+            if let Some((b0, b1)) = bins {
+                for bin in selected[b0..b1].iter_mut() {
+                    *bin = true;
+                }
+            }
+        }
+
+        self.binned = Some(BinnedRows {
+            start,
+            end,
+            rows,
+            selected,
+        });
+    }
+
+    fn receive(&mut self) {
+        if let Some(matrix) = self.matrix_host.take() {
+            self.matrix = Some(Arc::new(matrix));
+            self.binned = None;
+            self.reset_view();
+        }
+
+        let selection =
+            self.selection_rx.as_ref().and_then(|rx| rx.try_recv().ok());
+
+        if let Some((_rect, nodes)) = selection {
+            self.selection = NodeSelection { nodes };
+            self.selection_rx = None;
+            self.binned = None;
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if *open && !self.matrix_requested {
+            self.matrix_host.call(()).unwrap();
+            self.matrix_requested = true;
+        }
+
+        self.receive();
+
+        egui::Window::new("Path matrix")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                if self.matrix.is_none() {
+                    ui.label("Computing the path matrix...");
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Zoom in").clicked() {
+                        self.zoom(0.5);
+                    }
+
+                    if ui.button("Zoom out").clicked() {
+                        self.zoom(2.0);
+                    }
+
+                    if ui.button("Reset").clicked() {
+                        self.reset_view();
+                    }
+
+                    if ui.button("Show selection").clicked() {
+                        let (tx, rx) = crossbeam::channel::bounded(1);
+                        self.app_msg_tx
+                            .send(AppMsg::RequestSelection(tx))
+                            .unwrap();
+                        self.selection_rx = Some(rx);
+                    }
+                });
+
+                ui.add(
+                    egui::Slider::new::<f32>(&mut self.row_height, 2.0..=24.0)
+                        .text("Row height"),
+                );
+
+                ui.label(format!(
+                    "{:.0} - {:.0} bp",
+                    self.view_start,
+                    self.view_start + self.view_len
+                ));
+
+                ui.separator();
+
+                self.matrix_ui(ui);
+            })
+    }
+
+    fn matrix_ui(&mut self, ui: &mut egui::Ui) {
+        let bins = MATRIX_WIDTH as usize;
+        self.update_binned(bins);
+
+        let matrix = self.matrix.clone().unwrap();
+
+        let row_height = self.row_height;
+        let height = row_height * matrix.rows.len() as f32;
+
+        let mut drag_delta = 0.0;
+        let mut clicked = None;
+
+        egui::ScrollArea::from_max_height(400.0).show(ui, |ui| {
+            let size = egui::vec2(NAME_WIDTH + MATRIX_WIDTH, height);
+            let (rect, response) =
+                ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+
+            let painter = ui.painter_at(rect);
+
+            let matrix_rect = egui::Rect::from_min_max(
+                rect.min + egui::vec2(NAME_WIDTH, 0.0),
+                rect.max,
+            );
+
+            painter.rect_filled(
+                matrix_rect,
+                0.0,
+                ui.visuals().extreme_bg_color,
+            );
+
+            let binned = self.binned.as_ref().unwrap();
+
+            let bin_width = MATRIX_WIDTH / bins as f32;
+
+            let mut mesh = egui::epaint::Mesh::default();
+
+            let selected_color =
+                egui::Color32::from_rgba_unmultiplied(240, 180, 30, 60);
+
+            for (bin, selected) in binned.selected.iter().enumerate() {
+                if *selected {
+                    let min = matrix_rect.min
+                        + egui::vec2(bin as f32 * bin_width, 0.0);
+                    mesh.add_colored_rect(
+                        egui::Rect::from_min_size(
+                            min,
+                            egui::vec2(bin_width, height),
+                        ),
+                        selected_color,
+                    );
+                }
+            }
+
+            let clip = ui.clip_rect();
+
+            for (row_ix, (row, bins)) in
+                matrix.rows.iter().zip(binned.rows.iter()).enumerate()
+            {
+                let y = rect.min.y + row_ix as f32 * row_height;
+
+                if y + row_height < clip.min.y || y > clip.max.y {
+                    continue;
+                }
+
+                painter.text(
+                    egui::pos2(rect.min.x, y + row_height / 2.0),
+                    egui::Align2::LEFT_CENTER,
+                    &row.name,
+                    egui::TextStyle::Small,
+                    ui.visuals().text_color(),
+                );
+
+                // merge runs of bins with the same color into one rect
+                let mut bin = 0;
+                while bin < bins.len() {
+                    let flags = bins[bin];
+                    let run_start = bin;
+
+                    while bin < bins.len() && bins[bin] == flags {
+                        bin += 1;
+                    }
+
+                    if let Some(color) = step_color(flags) {
+                        let min = egui::pos2(
+                            matrix_rect.min.x + run_start as f32 * bin_width,
+                            y + 1.0,
+                        );
+                        let size = egui::vec2(
+                            (bin - run_start) as f32 * bin_width,
+                            (row_height - 2.0).max(1.0),
+                        );
+
+                        mesh.add_colored_rect(
+                            egui::Rect::from_min_size(min, size),
+                            color,
+                        );
+                    }
+                }
+            }
+
+            painter.add(egui::Shape::Mesh(mesh));
+
+            if response.dragged() {
+                drag_delta = response.drag_delta().x;
+            }
+
+            let hover = response
+                .hover_pos()
+                .filter(|pos| matrix_rect.contains(*pos))
+                .and_then(|pos| {
+                    let row_ix = ((pos.y - rect.min.y) / row_height) as usize;
+                    let row = matrix.rows.get(row_ix)?;
+
+                    let x = (pos.x - matrix_rect.min.x) / MATRIX_WIDTH;
+                    let bp = self.view_start + x as f64 * self.view_len;
+                    let node = matrix.node_at(bp as usize)?;
+                    let ix = matrix.node_ix(node)?;
+
+                    Some((row, node, ix))
+                });
+
+            if let Some((row, node, ix)) = hover {
+                let (n0, n1) = matrix.node_range(ix);
+
+                let orientation = match row.orientation(ix) {
+                    0 => "not visited",
+                    STEP_FORWARD => "forward",
+                    STEP_REVERSE => "reverse",
+                    _ => "both orientations",
+                };
+
+                let text = format!(
+                    "{}\nNode {} ({} bp)\n{}",
+                    row.name,
+                    node.0,
+                    n1 - n0,
+                    orientation
+                );
+
+                let response = response.on_hover_text(text);
+
+                if response.clicked() {
+                    clicked = Some(node);
+                }
+            }
+        });
+
+        if drag_delta != 0.0 {
+            let bp_per_px = self.view_len / MATRIX_WIDTH as f64;
+            self.view_start -= drag_delta as f64 * bp_per_px;
+            self.clamp_view();
+        }
+
+        if let Some(node) = clicked {
+            let clear = !ui.input().modifiers.shift;
+
+            let msg = AppMsg::Selection(Select::One { node, clear });
+            self.app_msg_tx.send(msg).unwrap();
+            self.app_msg_tx.send(AppMsg::GotoNode(node)).unwrap();
+
+            if clear {
+                self.selection.clear();
+            }
+            self.selection.nodes.insert(node);
+            self.binned = None;
+        }
+    }
+}
@@ -16,7 +16,7 @@ use std::sync::Arc;
 
 use bstr::ByteSlice;
 
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
     context::ContextEntry,
@@ -24,9 +24,9 @@ use crate::{
     reactor::{Host, Outbox, Reactor},
 };
 
-use crate::graph_query::GraphQuery;
+use crate::graph_query::{fasta_string, GraphQuery};
 use crate::{
-    app::{AppMsg, Select},
+    app::{AppMsg, PathHighlightMsg, Select},
     geometry::*,
 };
 
@@ -119,18 +119,203 @@ impl PathListSlot {
 pub struct PathDetails {
     pub(crate) path_details: PathListSlot,
     pub(crate) step_list: StepList,
+
+    // copying and exporting the path sequence
+    seq_whole_path: bool,
+    seq_start: usize,
+    seq_end: usize,
+    fasta_path: String,
+    fasta_result: Option<String>,
+
+    sequence_host: Host<SequenceRequest, Result<FastaOutput, String>>,
+    extracting_sequence: bool,
+}
+
+/// Where to put the FASTA of a path sequence
+#[derive(Debug, Clone)]
+enum FastaTarget {
+    Clipboard,
+    File(String),
+}
+
+#[derive(Debug, Clone)]
+struct SequenceRequest {
+    path: PathId,
+    range: Option<(usize, usize)>,
+    target: FastaTarget,
+}
+
+#[derive(Debug, Clone)]
+enum FastaOutput {
+    Clipboard(String),
+    File { bases: usize, file: String },
 }
 
 impl PathDetails {
     const ID: &'static str = "path_details_window";
 
     pub fn new(reactor: &mut Reactor) -> Self {
+        // spelling a whole path can take a while, so it's done off
+        // the GUI thread
+        let graph_query = reactor.graph_query.clone();
+
+        let sequence_host = reactor.create_host(
+            move |_outbox: &Outbox<Result<FastaOutput, String>>,
+                  request: SequenceRequest| {
+                Self::extract_sequence(&graph_query, request)
+                    .map_err(|err| err.to_string())
+            },
+        );
+
         Self {
             path_details: Default::default(),
             step_list: StepList::new(reactor, 15),
+
+            seq_whole_path: true,
+            seq_start: 0,
+            seq_end: 0,
+            fasta_path: String::new(),
+            fasta_result: None,
+
+            sequence_host,
+            extracting_sequence: false,
         }
     }
 
+    /// The range of the path to extract the sequence of, or `None`
+    /// for the whole path
+    fn seq_range(&self) -> Option<(usize, usize)> {
+        if self.seq_whole_path {
+            None
+        } else {
+            Some((self.seq_start, self.seq_end))
+        }
+    }
+
+    fn extract_sequence(
+        graph_query: &GraphQuery,
+        request: SequenceRequest,
+    ) -> anyhow::Result<FastaOutput> {
+        let record = graph_query
+            .path_sequence(request.path, request.range)
+            .ok_or(anyhow::anyhow!("Path not found"))?;
+
+        match request.target {
+            FastaTarget::Clipboard => {
+                Ok(FastaOutput::Clipboard(fasta_string(&[record])))
+            }
+            FastaTarget::File(file) => {
+                let out = std::fs::File::create(&file)?;
+                let writer = std::io::BufWriter::new(out);
+
+                record.write_fasta(writer)?;
+
+                Ok(FastaOutput::File {
+                    bases: record.sequence.len(),
+                    file,
+                })
+            }
+        }
+    }
+
+    fn sequence_ui(&mut self, ui: &mut egui::Ui, path: PathId) {
+        let base_count = self.path_details.base_count;
+
+        let mut copy_fasta = false;
+        let mut export_fasta = false;
+
+        let extracting = self.extracting_sequence;
+
+        ui.collapsing("Sequence", |ui| {
+            ui.checkbox(&mut self.seq_whole_path, "Whole path");
+
+            if !self.seq_whole_path {
+                let seq_start = &mut self.seq_start;
+                let seq_end = &mut self.seq_end;
+
+                let start_range = 0..=*seq_end;
+                let end_range = *seq_start..=base_count;
+
+                ui.horizontal(|ui| {
+                    ui.label("Base range");
+                    ui.add(
+                        egui::DragValue::new::<usize>(seq_start)
+                            .clamp_range(start_range),
+                    );
+                    ui.add(
+                        egui::DragValue::new::<usize>(seq_end)
+                            .clamp_range(end_range),
+                    );
+                });
+            }
+
+            let fasta_path = &mut self.fasta_path;
+
+            ui.horizontal(|ui| {
+                ui.set_enabled(!extracting);
+
+                copy_fasta = ui.button("Copy FASTA").clicked();
+                export_fasta = ui.button("Export FASTA").clicked();
+
+                ui.text_edit_singleline(fasta_path)
+                    .on_hover_text("The FASTA file to export to");
+            });
+
+            if extracting {
+                ui.label("Extracting sequence...");
+            } else if let Some(result) = &self.fasta_result {
+                ui.label(result);
+            }
+        });
+
+        let target = if copy_fasta {
+            Some(FastaTarget::Clipboard)
+        } else if export_fasta {
+            Some(FastaTarget::File(self.fasta_path.clone()))
+        } else {
+            None
+        };
+
+        if let Some(target) = target {
+            let request = SequenceRequest {
+                path,
+                range: self.seq_range(),
+                target,
+            };
+
+            self.sequence_host.call(request).unwrap();
+            self.extracting_sequence = true;
+        }
+    }
+
+    /// Shows the result of the latest sequence copy or export, if
+    /// it's finished
+    fn take_sequence_result(&mut self, ctx: &egui::CtxRef) {
+        let result = if let Some(result) = self.sequence_host.take() {
+            result
+        } else {
+            return;
+        };
+
+        self.extracting_sequence = false;
+
+        let msg = match result {
+            Ok(FastaOutput::Clipboard(fasta)) => {
+                ctx.output().copied_text = fasta;
+                "Copied FASTA to the clipboard".to_string()
+            }
+            Ok(FastaOutput::File { bases, file }) => {
+                format!("Wrote {} bases to {}", bases, file)
+            }
+            Err(err) => {
+                log::error!("Error extracting path sequence: {}", err);
+                format!("Error extracting path sequence: {}", err)
+            }
+        };
+
+        self.fasta_result = Some(msg);
+    }
+
     pub fn ui(
         &mut self,
         open_path_details: &mut bool,
@@ -139,15 +324,25 @@ impl PathDetails {
         node_details_id_cell: &AtomicCell<Option<NodeId>>,
         open_node_details: &mut bool,
         app_msg_tx: &Sender<AppMsg>,
+        path_highlight_tx: &Sender<PathHighlightMsg>,
+        hover_node: Option<NodeId>,
         ctx_tx: &Sender<ContextEntry>,
     ) -> Option<egui::InnerResponse<Option<()>>> {
         self.path_details.fetch(graph_query)?;
 
+        self.take_sequence_result(ctx);
+
         if let Some(path) = self.path_details.path_id.load() {
             if self.step_list.fetched_path_id != Some(path) {
                 self.step_list.steps_host.call(path).unwrap();
                 self.step_list.fetched_path_id = Some(path);
                 self.step_list.update_filter = true;
+                self.step_list.reset_focus();
+
+                self.seq_whole_path = true;
+                self.seq_start = 0;
+                self.seq_end = self.path_details.base_count;
+                self.fasta_result = None;
 
                 /*
                 self.step_list.async_path_update(graph_query_worker, path);
@@ -162,7 +357,7 @@ impl PathDetails {
             .default_pos(egui::Pos2::new(600.0, 200.0))
             .open(open_path_details)
             .show(ctx, |ui| {
-                if let Some(_path_id) = self.path_details.path_id.load() {
+                if let Some(path_id) = self.path_details.path_id.load() {
                     ui.set_min_height(200.0);
                     ui.set_max_width(300.0);
 
@@ -203,12 +398,23 @@ impl PathDetails {
                         ));
                     });
 
+                    if ui.button("Highlight path").clicked() {
+                        let msg = PathHighlightMsg::Add {
+                            path: path_id,
+                            color: None,
+                        };
+                        path_highlight_tx.send(msg).unwrap();
+                    }
+
+                    self.sequence_ui(ui, path_id);
+
                     self.step_list.ui(
                         ui,
                         app_msg_tx,
                         graph_query,
                         node_details_id_cell,
                         open_node_details,
+                        hover_node,
                         ctx_tx,
                     );
 
@@ -477,8 +683,13 @@ impl StepRange {
 //     Error(String),
 // }
 
-type StepsResult =
-    std::result::Result<(PathId, usize, Vec<(Handle, StepPtr, usize)>), String>;
+type StepsResult = std::result::Result<
+    (PathId, usize, Vec<(Handle, StepPtr, usize)>, NodeStepIndex),
+    String,
+>;
+
+/// The indices of the steps on each node of a path, in path order
+type NodeStepIndex = FxHashMap<NodeId, Vec<usize>>;
 
 pub struct StepList {
     fetched_path_id: Option<PathId>,
@@ -493,6 +704,16 @@ pub struct StepList {
     range_filter: StepRange,
 
     update_filter: bool,
+
+    // the index of the step that was last jumped to or clicked
+    focused_step: Option<usize>,
+
+    jump_pos: usize,
+    jump_node: String,
+    jump_error: Option<String>,
+
+    follow_hover: bool,
+    last_hover: Option<NodeId>,
 }
 
 impl StepList {
@@ -520,7 +741,13 @@ impl StepList {
                         })
                         .collect::<Vec<_>>();
 
-                    Ok((path, base_len, steps_vec))
+                    let mut node_steps: NodeStepIndex = FxHashMap::default();
+
+                    for (ix, (handle, _, _)) in steps_vec.iter().enumerate() {
+                        node_steps.entry(handle.id()).or_default().push(ix);
+                    }
+
+                    Ok((path, base_len, steps_vec, node_steps))
                 } else {
                     dbg!();
                     Err("Path not found".to_string())
@@ -541,20 +768,38 @@ impl StepList {
             range_filter: StepRange::default(),
 
             update_filter: false,
+
+            focused_step: None,
+
+            jump_pos: 0,
+            jump_node: String::new(),
+            jump_error: None,
+
+            follow_hover: false,
+            last_hover: None,
         }
     }
 
+    /// Called when a new path is shown
+    fn reset_focus(&mut self) {
+        self.page = 0;
+        self.focused_step = None;
+        self.jump_pos = 0;
+        self.jump_error = None;
+    }
+
     pub fn ui(
         &mut self,
         ui: &mut egui::Ui,
         app_msg_tx: &Sender<AppMsg>,
-        _graph_query: &GraphQuery,
+        graph_query: &GraphQuery,
         node_details_id_cell: &AtomicCell<Option<NodeId>>,
         open_node_details: &mut bool,
+        hover_node: Option<NodeId>,
         ctx_tx: &Sender<ContextEntry>,
     ) -> egui::InnerResponse<()> {
         if let Some(result) = self.steps_host.take() {
-            if let Ok((_path, path_base_len, steps)) = &result {
+            if let Ok((_path, path_base_len, steps, _)) = &result {
                 if self.update_filter {
                     self.range_filter =
                         StepRange::from_steps(*path_base_len, steps);
@@ -566,17 +811,161 @@ impl StepList {
             self.latest_result = Some(result);
         }
 
-        let steps = if let Some(Ok((_, len, steps))) = &self.latest_result {
-            if self.update_filter {
-                self.range_filter = StepRange::from_steps(*len, steps);
+        let empty_index = NodeStepIndex::default();
+
+        let (path, steps, node_steps) =
+            if let Some(Ok((path, len, steps, node_steps))) =
+                &self.latest_result
+            {
+                if self.update_filter {
+                    self.range_filter = StepRange::from_steps(*len, steps);
 
-                self.update_filter = false;
+                    self.update_filter = false;
+                }
+                (Some(*path), steps.as_slice(), node_steps)
+            } else {
+                self.range_filter = StepRange::default();
+                (None, &[][..], &empty_index)
+            };
+
+        // the step to scroll the list to, if any
+        let mut focus_request: Option<usize> = None;
+
+        if self.follow_hover && hover_node != self.last_hover {
+            if let Some(ix) = hover_node
+                .and_then(|node| node_steps.get(&node))
+                .and_then(|ixs| ixs.first())
+            {
+                focus_request = Some(*ix);
             }
-            steps.as_slice()
-        } else {
-            self.range_filter = StepRange::default();
-            &[]
-        };
+        }
+        self.last_hover = hover_node;
+
+        ui.checkbox(&mut self.follow_hover, "Follow hovered node")
+            .on_hover_text(
+                "Scroll to the step on the node under the cursor \
+                 in the main view",
+            );
+
+        let path_base_len = self.range_filter.path_base_len;
+
+        let jump_pos = &mut self.jump_pos;
+        let jump_node = &mut self.jump_node;
+
+        let mut jump_to_pos = false;
+        let mut find_node = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Base position");
+            ui.add(
+                egui::DragValue::new::<usize>(jump_pos)
+                    .clamp_range(0..=path_base_len),
+            );
+            jump_to_pos = ui.button("Go").clicked();
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Node");
+            let node_box = ui.text_edit_singleline(jump_node);
+            find_node = ui.button("Find").clicked()
+                || (node_box.lost_focus()
+                    && ui.input().key_pressed(egui::Key::Enter));
+        });
+
+        if jump_to_pos {
+            self.jump_error = None;
+
+            let pos = *jump_pos;
+
+            let ix = path.and_then(|path| {
+                let step = graph_query.find_step_at_base(path, pos)?;
+                let step_pos = graph_query
+                    .path_positions()
+                    .path_step_position(path, step)?;
+                steps.binary_search_by_key(&step_pos, |(_, _, p)| *p).ok()
+            });
+
+            if ix.is_some() {
+                focus_request = ix;
+            } else {
+                self.jump_error = Some(format!("No step at position {}", pos));
+            }
+        }
+
+        if find_node {
+            self.jump_error = None;
+
+            match jump_node.trim().parse::<u64>() {
+                Ok(id) => {
+                    let node = NodeId::from(id);
+
+                    if let Some(ixs) = node_steps.get(&node) {
+                        // cycle through the steps on the node
+                        let next = self
+                            .focused_step
+                            .and_then(|focused| {
+                                ixs.iter().find(|&&ix| ix > focused)
+                            })
+                            .or(ixs.first());
+
+                        focus_request = next.copied();
+                    } else {
+                        self.jump_error =
+                            Some(format!("Node {} is not on this path", id));
+                    }
+                }
+                Err(_) => {
+                    self.jump_error =
+                        Some(format!("Invalid node ID: {}", jump_node));
+                }
+            }
+        }
+
+        if let Some(err) = &self.jump_error {
+            ui.colored_label(egui::Color32::RED, err);
+        }
+
+        if let Some(ix) = focus_request {
+            let filter = &mut self.range_filter;
+
+            if ix < filter.from_ix || ix >= filter.to_ix {
+                filter.from_pos = 0;
+                filter.to_pos = filter.path_base_len;
+                filter.from_ix = 0;
+                filter.to_ix = steps.len();
+            }
+
+            self.page = (ix - filter.from_ix) / self.page_size;
+            self.focused_step = Some(ix);
+        }
+
+        let focused = self
+            .focused_step
+            .and_then(|ix| steps.get(ix).map(|(handle, _, _)| (ix, *handle)));
+
+        if let Some((ix, handle)) = focused {
+            let ixs = node_steps.get(&handle.id());
+            let count = ixs.map(|ixs| ixs.len()).unwrap_or(0);
+            let nth = ixs
+                .and_then(|ixs| ixs.iter().position(|&i| i == ix))
+                .unwrap_or(0);
+
+            ui.label(format!(
+                "Step {} on node {} ({} of {} on the node)",
+                ix,
+                handle.id().0,
+                nth + 1,
+                count
+            ));
+        }
+
+        let filtered_len = self
+            .range_filter
+            .to_ix
+            .min(steps.len())
+            .saturating_sub(self.range_filter.from_ix);
+        self.page_count = filtered_len.saturating_sub(1) / self.page_size;
+        self.page = self.page.min(self.page_count);
 
         let page = &mut self.page;
         let page_count = self.page_count;
@@ -660,6 +1049,8 @@ impl StepList {
             }
         });
 
+        let from_ix = self.range_filter.from_ix.min(self.range_filter.to_ix);
+
         let steps = {
             let from = self.range_filter.from_ix;
             let to = self.range_filter.to_ix;
@@ -690,6 +1081,8 @@ impl StepList {
 
         let separator = || egui::Separator::default().spacing(1.0);
 
+        let focused_step = &mut self.focused_step;
+
         egui::ScrollArea::auto_sized().show(ui, |mut ui| {
             egui::Grid::new("path_details_step_list")
                 .spacing(Point { x: 10.0, y: 5.0 })
@@ -707,6 +1100,8 @@ impl StepList {
                     for (slot_ix, (handle, step_ptr, pos)) in
                         steps[page_start..page_end].iter().enumerate()
                     {
+                        let step_ix = from_ix + page_start + slot_ix;
+
                         let node_id = handle.id();
 
                         let handle_str = if handle.is_reverse() {
@@ -730,9 +1125,26 @@ impl StepList {
                             true,
                         );
 
+                        if *focused_step == Some(step_ix) {
+                            let mut rect = row.rect;
+                            rect.max.x = ui.max_rect().right();
+
+                            let stroke = ui.visuals().selection.stroke;
+                            ui.painter().rect_stroke(rect, 0.0, stroke);
+                        }
+
                         if row.clicked() {
+                            *focused_step = Some(step_ix);
+
                             node_details_id_cell.store(Some(handle.id()));
                             *open_node_details = true;
+
+                            let select = Select::One {
+                                node: node_id,
+                                clear: true,
+                            };
+                            app_msg_tx.send(AppMsg::Selection(select)).unwrap();
+                            app_msg_tx.send(AppMsg::GotoNode(node_id)).unwrap();
                         }
 
                         if row.clicked_by(egui::PointerButton::Secondary) {
@@ -0,0 +1,405 @@
+#[allow(unused_imports)]
+use handlegraph::{
+    handle::{Direction, Handle, NodeId},
+    handlegraph::*,
+    pathhandlegraph::*,
+};
+
+use crate::app::SharedState;
+use crate::geometry::Point;
+use crate::graph_query::GraphQuery;
+use crate::universe::Node;
+use crate::view::{ScreenDims, View};
+
+use super::graph_picker::PathPicker;
+
+/// The maximum width of the scale bar, in pixels
+const SCALE_BAR_MAX_WIDTH: f32 = 150.0;
+
+/// The minimum distance between two ruler ticks, in pixels
+const TICK_SPACING: f32 = 60.0;
+
+/// The maximum number of ruler ticks drawn at once
+const MAX_TICKS: usize = 40;
+
+/// The number of cells along each side of the grid the ruler's steps
+/// are binned into
+const RULER_GRID_DIM: usize = 256;
+
+/// Formats a length in base pairs using the largest fitting unit,
+/// e.g. "500 bp", "20 kb", "1.5 Mb"
+pub fn format_bp(bp: f64) -> String {
+    let (value, unit) = if bp >= 1_000_000_000.0 {
+        (bp / 1_000_000_000.0, "Gb")
+    } else if bp >= 1_000_000.0 {
+        (bp / 1_000_000.0, "Mb")
+    } else if bp >= 1_000.0 {
+        (bp / 1_000.0, "kb")
+    } else {
+        (bp, "bp")
+    };
+
+    if (value - value.round()).abs() < 0.05 {
+        format!("{} {}", value.round(), unit)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+/// The largest length of the form `{1, 2, 5} * 10^n` that's no
+/// greater than `max_bp`
+fn scale_bar_length(max_bp: f64) -> f64 {
+    if max_bp < 1.0 {
+        return max_bp;
+    }
+
+    let magnitude = 10f64.powi(max_bp.log10().floor() as i32);
+
+    [5.0, 2.0, 1.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|len| *len <= max_bp)
+        .unwrap_or(magnitude)
+}
+
+fn painter_layer() -> egui::LayerId {
+    egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("gui_ruler_background"),
+    )
+}
+
+/// The start points of the steps of the ruler's reference path, with
+/// their path positions, binned into a uniform grid over the path's
+/// bounding box, so that only the steps near the cursor need to be
+/// checked each frame
+struct RulerSteps {
+    path: PathId,
+    // the number of nodes in the layout the grid was built from
+    node_count: usize,
+
+    origin: Point,
+    cell_size: f32,
+    cells: Vec<Vec<(Point, usize)>>,
+}
+
+impl RulerSteps {
+    fn new(
+        graph_query: &GraphQuery,
+        nodes: &[Node],
+        path: PathId,
+    ) -> Option<Self> {
+        let steps = graph_query.path_pos_steps(path)?;
+
+        let points = steps
+            .into_iter()
+            .filter_map(|(handle, _, pos)| {
+                let node = nodes.get((handle.id().0 - 1) as usize)?;
+                let start = if handle.is_reverse() {
+                    node.p1
+                } else {
+                    node.p0
+                };
+                Some((start, pos))
+            })
+            .collect::<Vec<_>>();
+
+        let (min, max) = points.iter().fold(
+            (
+                Point::new(std::f32::MAX, std::f32::MAX),
+                Point::new(std::f32::MIN, std::f32::MIN),
+            ),
+            |(min, max), (p, _)| {
+                (
+                    Point::new(min.x.min(p.x), min.y.min(p.y)),
+                    Point::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            },
+        );
+
+        let origin = if points.is_empty() { Point::ZERO } else { min };
+        let extent = (max.x - min.x).max(max.y - min.y).max(1.0);
+
+        let mut grid = Self {
+            path,
+            node_count: nodes.len(),
+
+            origin,
+            cell_size: extent / RULER_GRID_DIM as f32,
+            cells: vec![Vec::new(); RULER_GRID_DIM * RULER_GRID_DIM],
+        };
+
+        for (point, pos) in points {
+            let ix = grid.cell_index(grid.cell_of(point));
+            grid.cells[ix].push((point, pos));
+        }
+
+        Some(grid)
+    }
+
+    fn cell_of(&self, point: Point) -> (usize, usize) {
+        let coord = |v: f32, origin: f32| {
+            let c = ((v - origin) / self.cell_size).floor().max(0.0) as usize;
+            c.min(RULER_GRID_DIM - 1)
+        };
+
+        (coord(point.x, self.origin.x), coord(point.y, self.origin.y))
+    }
+
+    fn cell_index(&self, (col, row): (usize, usize)) -> usize {
+        row * RULER_GRID_DIM + col
+    }
+
+    /// The steps whose start points are within `radius` of `center`,
+    /// with their squared distance to it
+    fn near(&self, center: Point, radius: f32) -> Vec<(f32, Point, usize)> {
+        let radius_sqr = radius * radius;
+
+        let (col_0, row_0) =
+            self.cell_of(Point::new(center.x - radius, center.y - radius));
+        let (col_1, row_1) =
+            self.cell_of(Point::new(center.x + radius, center.y + radius));
+
+        let mut near = Vec::new();
+
+        for row in row_0..=row_1 {
+            for col in col_0..=col_1 {
+                let cell = &self.cells[self.cell_index((col, row))];
+
+                near.extend(cell.iter().filter_map(|&(start, pos)| {
+                    let dist_sqr = start.dist_sqr(center);
+                    (dist_sqr <= radius_sqr).then(|| (dist_sqr, start, pos))
+                }));
+            }
+        }
+
+        near
+    }
+}
+
+/// Draws a base-pair scale bar that tracks the view scale, and a
+/// ruler showing the path coordinates of a reference path at the
+/// nodes near the cursor
+pub struct RulerWindow {
+    show_scale_bar: bool,
+    show_ruler: bool,
+
+    // the radius around the cursor, in pixels, within which the
+    // reference path positions are shown
+    ruler_radius: f32,
+
+    path_picker_open: bool,
+    path_picker: PathPicker,
+
+    ruler_steps: Option<RulerSteps>,
+}
+
+impl RulerWindow {
+    pub const ID: &'static str = "ruler_window";
+
+    pub fn new(path_picker: PathPicker) -> Self {
+        Self {
+            show_scale_bar: true,
+            show_ruler: false,
+
+            ruler_radius: 200.0,
+
+            path_picker_open: false,
+            path_picker,
+
+            ruler_steps: None,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        self.path_picker.ui(ctx, &mut self.path_picker_open);
+
+        let show_scale_bar = &mut self.show_scale_bar;
+        let show_ruler = &mut self.show_ruler;
+        let ruler_radius = &mut self.ruler_radius;
+
+        let path_picker = &self.path_picker;
+        let path_picker_open = &mut self.path_picker_open;
+
+        egui::Window::new("Scale & ruler")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.checkbox(show_scale_bar, "Show scale bar");
+
+                ui.separator();
+
+                ui.checkbox(show_ruler, "Show path ruler");
+
+                ui.horizontal(|ui| {
+                    ui.label("Reference path");
+
+                    let path_name = path_picker
+                        .active_path()
+                        .map(|(_, name)| name)
+                        .unwrap_or("-");
+                    ui.label(path_name);
+
+                    if ui.button("Choose path").clicked() {
+                        *path_picker_open = true;
+                    }
+                });
+
+                ui.add(
+                    egui::Slider::new::<f32>(ruler_radius, 50.0..=1000.0)
+                        .text("Ruler radius (px)"),
+                );
+            })
+    }
+
+    fn update_ruler_steps(&mut self, graph_query: &GraphQuery, nodes: &[Node]) {
+        let path = self.path_picker.active_path().map(|(path, _)| path);
+
+        let current = self
+            .ruler_steps
+            .as_ref()
+            .map(|steps| (steps.path, steps.node_count));
+
+        if path.map(|path| (path, nodes.len())) == current {
+            return;
+        }
+
+        self.ruler_steps =
+            path.and_then(|path| RulerSteps::new(graph_query, nodes, path));
+    }
+
+    fn draw_scale_bar(
+        &self,
+        ctx: &egui::CtxRef,
+        view: View,
+        bp_per_world_unit: f32,
+    ) {
+        let bp_per_pixel = (view.scale * bp_per_world_unit) as f64;
+
+        if bp_per_pixel <= 0.0 {
+            return;
+        }
+
+        let bar_bp =
+            scale_bar_length(SCALE_BAR_MAX_WIDTH as f64 * bp_per_pixel);
+        let bar_width = (bar_bp / bp_per_pixel) as f32;
+
+        let screen_rect = ctx.input().screen_rect();
+
+        let left = screen_rect.min.x + 20.0;
+        let right = left + bar_width;
+        let y = screen_rect.max.y - 20.0;
+
+        let color = ctx.style().visuals.text_color();
+        let stroke = egui::Stroke::new(2.0, color);
+
+        let painter = ctx.layer_painter(painter_layer());
+
+        painter
+            .line_segment([egui::pos2(left, y), egui::pos2(right, y)], stroke);
+        painter.line_segment(
+            [egui::pos2(left, y - 5.0), egui::pos2(left, y + 5.0)],
+            stroke,
+        );
+        painter.line_segment(
+            [egui::pos2(right, y - 5.0), egui::pos2(right, y + 5.0)],
+            stroke,
+        );
+
+        painter.text(
+            egui::pos2(left + bar_width / 2.0, y - 6.0),
+            egui::Align2::CENTER_BOTTOM,
+            format_bp(bar_bp),
+            egui::TextStyle::Body,
+            color,
+        );
+    }
+
+    fn draw_ruler(
+        &self,
+        ctx: &egui::CtxRef,
+        view: View,
+        screen_dims: ScreenDims,
+        mouse_pos: Point,
+    ) {
+        let steps = if let Some(steps) = &self.ruler_steps {
+            steps
+        } else {
+            return;
+        };
+
+        let cursor = view.screen_point_to_world(screen_dims, mouse_pos);
+        let radius = self.ruler_radius * view.scale;
+
+        let mut near = steps.near(cursor, radius);
+
+        near.sort_by(|(a, _, _), (b, _, _)| a.partial_cmp(b).unwrap());
+
+        let offset = Point::new(screen_dims.width, screen_dims.height) / 2.0;
+
+        // keep the ticks closest to the cursor, skipping those that
+        // would overlap an already placed tick
+        let mut ticks: Vec<(Point, usize)> = Vec::new();
+
+        for (_, start, pos) in near {
+            let screen = view.world_point_to_screen(start) + offset;
+
+            let overlaps =
+                ticks.iter().any(|(p, _)| p.dist(screen) < TICK_SPACING);
+
+            if !overlaps {
+                ticks.push((screen, pos));
+            }
+
+            if ticks.len() >= MAX_TICKS {
+                break;
+            }
+        }
+
+        let color = ctx.style().visuals.text_color();
+        let stroke = egui::Stroke::new(1.5, color);
+
+        let painter = ctx.layer_painter(painter_layer());
+
+        for (screen, pos) in ticks {
+            let p: egui::Pos2 = screen.into();
+
+            painter.line_segment([p, p + egui::vec2(0.0, -12.0)], stroke);
+            painter.circle_filled(p, 2.0, color);
+
+            painter.text(
+                p + egui::vec2(0.0, -14.0),
+                egui::Align2::CENTER_BOTTOM,
+                pos.to_string(),
+                egui::TextStyle::Small,
+                color,
+            );
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        ctx: &egui::CtxRef,
+        shared_state: &SharedState,
+        graph_query: &GraphQuery,
+        nodes: &[Node],
+        bp_per_world_unit: f32,
+    ) {
+        let view = shared_state.view();
+        let screen_dims = shared_state.screen_dims();
+        let mouse_pos = shared_state.mouse_pos();
+
+        if self.show_scale_bar {
+            self.draw_scale_bar(ctx, view, bp_per_world_unit);
+        }
+
+        if self.show_ruler {
+            self.update_ruler_steps(graph_query, nodes);
+            self.draw_ruler(ctx, view, screen_dims, mouse_pos);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use crossbeam::channel::Sender;
+
+use crate::app::SessionMsg;
+use crate::session::SESSION_FILE_EXT;
+
+use super::file::FilePicker;
+
+pub struct SessionWindow {
+    session_path: String,
+
+    file_picker: FilePicker,
+    file_picker_open: bool,
+
+    session_tx: Sender<SessionMsg>,
+}
+
+impl SessionWindow {
+    pub const ID: &'static str = "session_window";
+
+    pub fn new(session_tx: Sender<SessionMsg>) -> Self {
+        let pwd = std::fs::canonicalize("./").unwrap();
+
+        let mut file_picker = FilePicker::new(
+            egui::Id::with(egui::Id::new(Self::ID), "file_picker"),
+            pwd,
+        )
+        .unwrap();
+
+        let extensions: [&str; 1] = [SESSION_FILE_EXT];
+        file_picker.set_visible_extensions(&extensions).unwrap();
+
+        Self {
+            session_path: String::new(),
+
+            file_picker,
+            file_picker_open: false,
+
+            session_tx,
+        }
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if self.file_picker.selected_path().is_some() {
+            self.file_picker_open = false;
+        }
+
+        self.file_picker.ui(ctx, &mut self.file_picker_open);
+
+        if let Some(path) = self.file_picker.selected_path() {
+            let path_str = path.to_str().unwrap();
+            self.session_path = path_str.to_string();
+        }
+
+        let session_path = &mut self.session_path;
+        let file_picker = &mut self.file_picker;
+        let file_picker_open = &mut self.file_picker_open;
+        let session_tx = &self.session_tx;
+
+        egui::Window::new("Session")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                ui.set_min_width(300.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Session file");
+                    ui.separator();
+                    ui.text_edit_singleline(session_path);
+                });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Choose file").clicked() {
+                        file_picker.reset_selection();
+                        *file_picker_open = true;
+                    }
+
+                    let save_btn = ui.add(
+                        egui::Button::new("Save session")
+                            .enabled(!session_path.is_empty()),
+                    );
+
+                    let load_btn = ui.add(
+                        egui::Button::new("Load session")
+                            .enabled(!session_path.is_empty()),
+                    );
+
+                    if save_btn.clicked() {
+                        file_picker.reset_selection();
+                        let path = PathBuf::from(session_path.as_str());
+                        session_tx.send(SessionMsg::Save(path)).unwrap();
+                    }
+
+                    if load_btn.clicked() {
+                        file_picker.reset_selection();
+                        let path = PathBuf::from(session_path.as_str());
+                        session_tx.send(SessionMsg::Load(path)).unwrap();
+                    }
+                });
+            })
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
-    app::{AppSettings, SharedState},
+    app::{config, AppSettings, SharedState},
     geometry::Point,
+    input::InputBindings,
 };
 
+pub mod bindings;
 pub mod debug;
 pub mod gui;
 pub mod main_view;
 
+use bindings::*;
 use debug::*;
 use gui::*;
 use main_view::*;
@@ -17,6 +20,10 @@ pub struct SettingsWindow {
     pub(crate) debug: DebugSettings,
     pub(crate) gui: GuiSettings,
     pub(crate) main_view: MainViewSettings,
+    pub(crate) bindings: BindingsSettings,
+
+    settings: AppSettings,
+    config_result: Option<Result<String, String>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -24,12 +31,17 @@ enum SettingsTab {
     MainView,
     Debug,
     Gui,
+    Bindings,
 }
 
 impl SettingsWindow {
     pub const ID: &'static str = "settings_window";
 
-    pub fn new(settings: &AppSettings, shared_state: &SharedState) -> Self {
+    pub fn new(
+        settings: &AppSettings,
+        shared_state: &SharedState,
+        input_bindings: InputBindings,
+    ) -> Self {
         let current_tab = SettingsTab::MainView;
 
         let main_view =
@@ -41,9 +53,33 @@ impl SettingsWindow {
             debug: Default::default(),
             gui: Default::default(),
             main_view,
+            bindings: BindingsSettings::new(input_bindings),
+
+            settings: settings.clone(),
+            config_result: None,
         }
     }
 
+    fn save_config(&mut self) {
+        let result = match config::config_file_path() {
+            Some(path) => config::save_config(
+                &path,
+                &self.settings,
+                self.bindings.input_bindings(),
+            )
+            .map(|_| format!("Saved config to {}", path.display()))
+            .map_err(|err| format!("{:?}", err)),
+            None => Err("Could not find config directory".to_string()),
+        };
+
+        match &result {
+            Ok(msg) => log::info!("{}", msg),
+            Err(err) => log::error!("Error saving config: {}", err),
+        }
+
+        self.config_result = Some(result);
+    }
+
     pub fn ui(
         &mut self,
         ctx: &egui::CtxRef,
@@ -71,6 +107,11 @@ impl SettingsWindow {
                         SettingsTab::Debug,
                         "Debug",
                     );
+                    ui.selectable_value(
+                        &mut self.current_tab,
+                        SettingsTab::Bindings,
+                        "Key bindings",
+                    );
                 });
 
                 match self.current_tab {
@@ -83,7 +124,35 @@ impl SettingsWindow {
                     SettingsTab::Gui => {
                         self.gui.ui(ui);
                     }
+                    SettingsTab::Bindings => {
+                        self.bindings.ui(ui);
+                    }
                 }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Save config")
+                        .on_hover_text(
+                            "Save the settings and key bindings, \
+                             to be loaded on startup",
+                        )
+                        .clicked()
+                    {
+                        self.save_config();
+                    }
+
+                    match &self.config_result {
+                        Some(Ok(msg)) => {
+                            ui.label(msg);
+                        }
+                        Some(Err(err)) => {
+                            ui.label(format!("Error: {}", err));
+                        }
+                        None => (),
+                    }
+                });
             })
     }
 }
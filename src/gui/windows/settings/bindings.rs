@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::input::binds::{
+    binding_to_string, mouse_button_to_string, BindableInput,
+    SystemInputBindings,
+};
+use crate::input::InputBindings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subsystem {
+    App,
+    MainView,
+    Gui,
+}
+
+pub struct BindingsSettings {
+    bindings: InputBindings,
+
+    // the subsystem and name of the input that will be bound to the
+    // next captured key or mouse button
+    rebinding: Option<(Subsystem, String)>,
+}
+
+impl BindingsSettings {
+    pub fn new(bindings: InputBindings) -> Self {
+        Self {
+            bindings,
+            rebinding: None,
+        }
+    }
+
+    pub fn input_bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    fn bind_captured(&mut self) {
+        if self.bindings.capture.load() {
+            return;
+        }
+
+        let captured = if let Some(captured) = self.bindings.captured.take() {
+            captured
+        } else {
+            return;
+        };
+
+        if let Some((subsystem, name)) = self.rebinding.take() {
+            fn bind<T: BindableInput>(
+                bindings: &Arc<Mutex<SystemInputBindings<T>>>,
+                name: &str,
+                captured: crate::input::CapturedInput,
+            ) {
+                if let Some(input) = T::from_name(name) {
+                    bindings.lock().bind_captured(captured, input);
+                }
+            }
+
+            match subsystem {
+                Subsystem::App => bind(&self.bindings.app, &name, captured),
+                Subsystem::MainView => {
+                    bind(&self.bindings.main_view, &name, captured)
+                }
+                Subsystem::Gui => bind(&self.bindings.gui, &name, captured),
+            }
+        }
+    }
+
+    fn subsystem_ui<T: BindableInput>(
+        ui: &mut egui::Ui,
+        subsystem: Subsystem,
+        bindings: &Arc<Mutex<SystemInputBindings<T>>>,
+        rebinding: &mut Option<(Subsystem, String)>,
+        capture: &crossbeam::atomic::AtomicCell<bool>,
+    ) {
+        let mut bindings = bindings.lock();
+
+        let key_binds = bindings.key_binds();
+        let mouse_binds = bindings.mouse_binds();
+        let wheel_bind = bindings.wheel_bind();
+
+        let grid_id =
+            egui::Id::new("bindings_settings_grid").with(subsystem as u8);
+
+        egui::Grid::new(grid_id).striped(true).show(ui, |ui| {
+            for input in T::all_inputs() {
+                let name = input.name();
+
+                ui.label(&name);
+
+                let keys = key_binds
+                    .iter()
+                    .filter(|(_, _, i)| *i == input)
+                    .map(|(key, mods, _)| {
+                        binding_to_string(*mods, &format!("{:?}", key))
+                    });
+
+                let buttons = mouse_binds
+                    .iter()
+                    .filter(|(_, _, i)| *i == input)
+                    .map(|(button, mods, _)| {
+                        let button = mouse_button_to_string(*button);
+                        binding_to_string(*mods, &format!("Mouse {}", button))
+                    });
+
+                let mut binds = keys.chain(buttons).collect::<Vec<_>>();
+
+                if let Some(wheel) = wheel_bind {
+                    if wheel.payload() == input {
+                        let mods = wheel.modifiers();
+                        binds.push(binding_to_string(mods, "Wheel"));
+                    }
+                }
+
+                if binds.is_empty() {
+                    ui.label("-");
+                } else {
+                    ui.label(binds.join(", "));
+                }
+
+                let is_rebinding = rebinding
+                    .as_ref()
+                    .map(|(s, n)| *s == subsystem && *n == name)
+                    .unwrap_or(false);
+
+                if is_rebinding {
+                    if ui.button("Cancel").clicked() {
+                        *rebinding = None;
+                        capture.store(false);
+                    }
+                } else if ui
+                    .button("Add")
+                    .on_hover_text(
+                        "Bind the next key or mouse button pressed \
+                         to this input",
+                    )
+                    .clicked()
+                {
+                    *rebinding = Some((subsystem, name.clone()));
+                    capture.store(true);
+                }
+
+                if ui.button("Clear").clicked() {
+                    bindings.clear_input(input);
+                }
+
+                ui.end_row();
+            }
+        });
+
+        if let Some(wheel) = wheel_bind {
+            let mut invert = wheel.invert();
+            let mut mult = wheel.mult();
+
+            ui.horizontal(|ui| {
+                let invert_box = ui.checkbox(&mut invert, "Invert wheel");
+                let mult_drag = ui.add(
+                    egui::DragValue::new::<f32>(&mut mult)
+                        .clamp_range(0.01..=10.0)
+                        .speed(0.01)
+                        .prefix("Wheel multiplier: "),
+                );
+
+                if invert_box.changed() || mult_drag.changed() {
+                    bindings.set_wheel_bind(Some(
+                        crate::input::binds::WheelBind::with_modifiers(
+                            invert,
+                            mult,
+                            wheel.payload(),
+                            wheel.modifiers(),
+                        ),
+                    ));
+                }
+            });
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        self.bind_captured();
+
+        if let Some((_, name)) = &self.rebinding {
+            ui.label(format!(
+                "Press a key or mouse button to bind to {}",
+                name
+            ));
+        }
+
+        let bindings = &self.bindings;
+        let rebinding = &mut self.rebinding;
+
+        egui::ScrollArea::from_max_height(400.0).show(ui, |ui| {
+            ui.collapsing("Main view", |ui| {
+                Self::subsystem_ui(
+                    ui,
+                    Subsystem::MainView,
+                    &bindings.main_view,
+                    rebinding,
+                    &bindings.capture,
+                );
+            });
+
+            ui.collapsing("App", |ui| {
+                Self::subsystem_ui(
+                    ui,
+                    Subsystem::App,
+                    &bindings.app,
+                    rebinding,
+                    &bindings.capture,
+                );
+            });
+
+            ui.collapsing("GUI", |ui| {
+                Self::subsystem_ui(
+                    ui,
+                    Subsystem::Gui,
+                    &bindings.gui,
+                    rebinding,
+                    &bindings.capture,
+                );
+            });
+        });
+
+        if ui.button("Reset to defaults").clicked() {
+            self.rebinding = None;
+            self.bindings.capture.store(false);
+            self.bindings.reset_to_defaults();
+        }
+    }
+}
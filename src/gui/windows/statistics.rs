@@ -0,0 +1,286 @@
+use crossbeam::channel::Sender;
+
+use crate::app::{AppMsg, Select};
+use crate::asynchronous::AsyncResult;
+use crate::graph_query::{
+    GraphQueryWorker, GraphStatistics, Histogram, NodeStat,
+};
+
+use super::ruler::format_bp;
+
+/// The size of each histogram, in pixels
+const HISTOGRAM_WIDTH: f32 = 360.0;
+const HISTOGRAM_HEIGHT: f32 = 100.0;
+
+/// The number of component sizes listed
+const LISTED_COMPONENTS: usize = 10;
+
+/// Statistics and distributions of the graph, computed in the
+/// background. Clicking a histogram bar selects the nodes in its bin
+pub struct StatisticsWindow {
+    stats: Option<AsyncResult<GraphStatistics>>,
+
+    app_msg_tx: Sender<AppMsg>,
+}
+
+impl StatisticsWindow {
+    pub const ID: &'static str = "statistics_window";
+
+    pub fn new(app_msg_tx: Sender<AppMsg>) -> Self {
+        Self {
+            stats: None,
+            app_msg_tx,
+        }
+    }
+
+    fn request_stats(&mut self, graph_query_worker: &GraphQueryWorker) {
+        let stats = graph_query_worker.run_query(|graph_query| async move {
+            GraphStatistics::compute(&graph_query)
+        });
+
+        self.stats = Some(stats);
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+        graph_query_worker: &GraphQueryWorker,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if *open && self.stats.is_none() {
+            self.request_stats(graph_query_worker);
+        }
+
+        if let Some(stats) = self.stats.as_mut() {
+            stats.move_result_if_ready();
+        }
+
+        let mut refresh = false;
+
+        let app_msg_tx = &self.app_msg_tx;
+
+        let response = egui::Window::new("Graph statistics")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                let stats = if let Some(stats) =
+                    self.stats.as_ref().and_then(|s| s.get_result())
+                {
+                    stats
+                } else {
+                    ui.label("Computing statistics...");
+                    return;
+                };
+
+                if ui.button("Refresh").clicked() {
+                    refresh = true;
+                }
+
+                egui::ScrollArea::from_max_height(600.0).show(ui, |ui| {
+                    stats_ui(ui, stats, app_msg_tx);
+                });
+            });
+
+        if refresh {
+            self.request_stats(graph_query_worker);
+        }
+
+        response
+    }
+}
+
+fn stats_ui(
+    ui: &mut egui::Ui,
+    stats: &GraphStatistics,
+    app_msg_tx: &Sender<AppMsg>,
+) {
+    egui::CollapsingHeader::new("Nodes")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("statistics_nodes").show(ui, |ui| {
+                ui.label("Nodes");
+                ui.label(stats.node_count.to_string());
+                ui.end_row();
+
+                ui.label("Total length");
+                ui.label(format_bp(stats.total_len as f64));
+                ui.end_row();
+
+                ui.label("N50");
+                ui.label(format_bp(stats.n50 as f64));
+                ui.end_row();
+
+                ui.label("Mean length");
+                ui.label(format!("{:.1} bp", stats.mean_len));
+                ui.end_row();
+
+                ui.label("Longest node");
+                ui.label(format_bp(stats.max_len as f64));
+                ui.end_row();
+            });
+
+            histogram_ui(ui, stats, NodeStat::Length, app_msg_tx);
+        });
+
+    egui::CollapsingHeader::new("Edges")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("statistics_edges").show(ui, |ui| {
+                ui.label("Edges");
+                ui.label(stats.edge_count.to_string());
+                ui.end_row();
+
+                ui.label("Tips");
+                ui.label(stats.tips.to_string());
+                ui.end_row();
+
+                ui.label("Self-loops");
+                ui.label(stats.self_loops.to_string());
+                ui.end_row();
+
+                ui.label("Inverting edges");
+                ui.label(stats.inverting_edges.to_string());
+                ui.end_row();
+            });
+
+            histogram_ui(ui, stats, NodeStat::Degree, app_msg_tx);
+        });
+
+    egui::CollapsingHeader::new("Coverage")
+        .default_open(false)
+        .show(ui, |ui| {
+            histogram_ui(ui, stats, NodeStat::Depth, app_msg_tx);
+        });
+
+    egui::CollapsingHeader::new("Components")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.label(format!(
+                "{} connected components",
+                stats.component_sizes.len()
+            ));
+
+            egui::Grid::new("statistics_components").striped(true).show(
+                ui,
+                |ui| {
+                    ui.label("Component");
+                    ui.label("Nodes");
+                    ui.end_row();
+
+                    for (ix, size) in stats
+                        .component_sizes
+                        .iter()
+                        .take(LISTED_COMPONENTS)
+                        .enumerate()
+                    {
+                        ui.label((ix + 1).to_string());
+                        ui.label(size.to_string());
+                        ui.end_row();
+                    }
+                },
+            );
+        });
+
+    egui::CollapsingHeader::new("Paths")
+        .default_open(false)
+        .show(ui, |ui| {
+            egui::Grid::new("statistics_paths")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Path");
+                    ui.label("Steps");
+                    ui.label("Length");
+                    ui.end_row();
+
+                    for path in stats.paths.iter() {
+                        ui.label(&path.name);
+                        ui.label(path.step_count.to_string());
+                        ui.label(format_bp(path.base_count as f64));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Draws the histogram of `stat` as bars; clicking a bar selects the
+/// nodes in its bin
+fn histogram_ui(
+    ui: &mut egui::Ui,
+    stats: &GraphStatistics,
+    stat: NodeStat,
+    app_msg_tx: &Sender<AppMsg>,
+) {
+    let histogram = stats.histogram(stat);
+
+    ui.label(format!("{} distribution", stat.name()));
+
+    let bins = histogram.counts.len();
+
+    if bins == 0 {
+        return;
+    }
+
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(HISTOGRAM_WIDTH, HISTOGRAM_HEIGHT),
+        egui::Sense::click(),
+    );
+
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    let bar_width = rect.width() / bins as f32;
+
+    // log scaled, so that small bins remain visible
+    let log_max = (1.0 + histogram.max_count() as f32).ln().max(1.0);
+
+    let hovered_bin = response
+        .hover_pos()
+        .map(|pos| (((pos.x - rect.min.x) / bar_width) as usize).min(bins - 1));
+
+    let color = ui.visuals().text_color();
+    let hover_color = egui::Color32::from_rgb(240, 180, 30);
+
+    for (bin, count) in histogram.counts.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+
+        let height = rect.height() * (1.0 + *count as f32).ln() / log_max;
+
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(
+                rect.min.x + bin as f32 * bar_width,
+                rect.max.y - height,
+            ),
+            egui::pos2(
+                rect.min.x + (bin + 1) as f32 * bar_width - 1.0,
+                rect.max.y,
+            ),
+        );
+
+        let fill = if hovered_bin == Some(bin) {
+            hover_color
+        } else {
+            color
+        };
+
+        painter.rect_filled(bar, 0.0, fill);
+    }
+
+    if let Some(bin) = hovered_bin {
+        let count = histogram.counts[bin];
+
+        let response = response.on_hover_text(format!(
+            "{}: {} nodes",
+            Histogram::bin_label(bin),
+            count
+        ));
+
+        if response.clicked() && count > 0 {
+            let nodes = stats.nodes_in_bin(stat, bin);
+            let msg = AppMsg::Selection(Select::Many { nodes, clear: true });
+            app_msg_tx.send(msg).unwrap();
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use handlegraph::handle::NodeId;
+
+use crossbeam::channel::Sender;
+
+use crate::app::validation::{IssueKind, ValidationReport};
+use crate::app::{AppMsg, Select};
+use crate::asynchronous::AsyncResult;
+use crate::graph_query::GraphQueryWorker;
+
+/// The most issues of each kind listed in the window
+const MAX_LISTED_ISSUES: usize = 500;
+
+/// Lists the problems found when validating the loaded graph, with
+/// buttons to select and go to the nodes involved in each. Opens
+/// itself if any problems are found
+pub struct ValidationWindow {
+    report: Option<AsyncResult<ValidationReport>>,
+    // whether the finished report has been logged
+    reported: bool,
+
+    app_msg_tx: Sender<AppMsg>,
+}
+
+impl ValidationWindow {
+    pub const ID: &'static str = "validation_window";
+
+    pub fn new(app_msg_tx: Sender<AppMsg>) -> Self {
+        Self {
+            report: None,
+            reported: false,
+            app_msg_tx,
+        }
+    }
+
+    /// Starts validating the graph in the background; `missing_layout`
+    /// are the nodes that had no rows in the layout file
+    pub fn validate(
+        &mut self,
+        graph_query_worker: &GraphQueryWorker,
+        missing_layout: Vec<NodeId>,
+    ) {
+        let report = graph_query_worker.run_query(|graph_query| async move {
+            ValidationReport::new(&graph_query, &missing_layout)
+        });
+
+        self.report = Some(report);
+        self.reported = false;
+    }
+
+    pub fn ui(
+        &mut self,
+        ctx: &egui::CtxRef,
+        open: &mut bool,
+    ) -> Option<egui::InnerResponse<Option<()>>> {
+        if let Some(report) = self.report.as_mut() {
+            report.move_result_if_ready();
+
+            if let Some(report) = report.get_result() {
+                if !self.reported {
+                    if report.is_empty() {
+                        log::info!("Graph validation found no problems");
+                    } else {
+                        log::warn!("Graph validation: {}", report.summary());
+                        *open = true;
+                    }
+                    self.reported = true;
+                }
+            }
+        }
+
+        let app_msg_tx = &self.app_msg_tx;
+
+        egui::Window::new("Graph validation")
+            .id(egui::Id::new(Self::ID))
+            .open(open)
+            .show(ctx, |ui| {
+                let report = if let Some(report) =
+                    self.report.as_ref().and_then(|r| r.get_result())
+                {
+                    report
+                } else {
+                    ui.label("Validating graph...");
+                    return;
+                };
+
+                if report.is_empty() {
+                    ui.label("No problems found");
+                    return;
+                }
+
+                egui::ScrollArea::from_max_height(600.0).show(ui, |ui| {
+                    for &kind in IssueKind::ALL.iter() {
+                        let count = report.count(kind);
+
+                        if count == 0 {
+                            continue;
+                        }
+
+                        let header = format!("{} ({})", kind.name(), count);
+
+                        ui.collapsing(header, |ui| {
+                            issues_ui(ui, report, kind, app_msg_tx);
+                        });
+                    }
+                });
+            })
+    }
+}
+
+fn issues_ui(
+    ui: &mut egui::Ui,
+    report: &ValidationReport,
+    kind: IssueKind,
+    app_msg_tx: &Sender<AppMsg>,
+) {
+    let grid_id = format!("validation_issues_{:?}", kind);
+
+    egui::Grid::new(grid_id).striped(true).show(ui, |ui| {
+        for issue in report.issues_of(kind).take(MAX_LISTED_ISSUES) {
+            ui.label(report.describe(issue));
+
+            let nodes = issue.nodes();
+
+            if ui.button("Select").clicked() {
+                let msg = AppMsg::Selection(Select::Many {
+                    nodes: nodes.iter().copied().collect(),
+                    clear: true,
+                });
+                app_msg_tx.send(msg).unwrap();
+            }
+
+            if ui.button("Go to").clicked() {
+                app_msg_tx.send(AppMsg::GotoNode(nodes[0])).unwrap();
+            }
+
+            ui.end_row();
+        }
+    });
+
+    let count = report.count(kind);
+
+    if count > MAX_LISTED_ISSUES {
+        ui.label(format!("... and {} more", count - MAX_LISTED_ISSUES));
+    }
+}
@@ -8,6 +8,8 @@ use winit::{
 
 use crossbeam::atomic::AtomicCell;
 use crossbeam::channel;
+use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::gui::GuiInput;
@@ -17,12 +19,14 @@ use crate::{app::SharedState, geometry::*};
 
 pub mod binds;
 
-pub use binds::{BindableInput, DigitalState, SystemInputBindings};
+pub use binds::{
+    BindableInput, CapturedInput, DigitalState, SystemInputBindings,
+};
 
 use binds::*;
 
 struct SubsystemInput<T: InputPayload + BindableInput> {
-    bindings: SystemInputBindings<T>,
+    bindings: Arc<Mutex<SystemInputBindings<T>>>,
 
     tx: channel::Sender<SystemInput<T>>,
     rx: channel::Receiver<SystemInput<T>>,
@@ -30,7 +34,7 @@ struct SubsystemInput<T: InputPayload + BindableInput> {
 
 impl<T: InputPayload + BindableInput> SubsystemInput<T> {
     fn from_default_binds() -> Self {
-        let bindings = T::default_binds();
+        let bindings = Arc::new(Mutex::new(T::default_binds()));
 
         let (tx, rx) = channel::unbounded::<SystemInput<T>>();
 
@@ -42,6 +46,30 @@ impl<T: InputPayload + BindableInput> SubsystemInput<T> {
     }
 }
 
+/// The bindings of each subsystem, shared between the input manager
+/// and the keybinding editor.
+///
+/// When `capture` is set, the next key or mouse button press is not
+/// passed on to the subsystems, and is instead stored in `captured`.
+#[derive(Clone)]
+pub struct InputBindings {
+    pub app: Arc<Mutex<SystemInputBindings<AppInput>>>,
+    pub main_view: Arc<Mutex<SystemInputBindings<MainViewInput>>>,
+    pub gui: Arc<Mutex<SystemInputBindings<GuiInput>>>,
+
+    pub capture: Arc<AtomicCell<bool>>,
+    pub captured: Arc<AtomicCell<Option<CapturedInput>>>,
+}
+
+impl InputBindings {
+    /// Reset the bindings of all subsystems to the defaults
+    pub fn reset_to_defaults(&self) {
+        *self.app.lock() = AppInput::default_binds();
+        *self.main_view.lock() = MainViewInput::default_binds();
+        *self.gui.lock() = GuiInput::default_binds();
+    }
+}
+
 pub struct InputManager {
     mouse_screen_pos: Arc<AtomicCell<Point>>,
 
@@ -55,6 +83,9 @@ pub struct InputManager {
 
     gui_focus_state: crate::gui::GuiFocusState,
 
+    capture: Arc<AtomicCell<bool>>,
+    captured: Arc<AtomicCell<Option<CapturedInput>>>,
+
     custom_binds: FxHashMap<
         winit::event::VirtualKeyCode,
         Arc<dyn Fn() + Send + Sync + 'static>,
@@ -81,6 +112,60 @@ impl InputManager {
         self.gui.clone_rx()
     }
 
+    pub fn bindings(&self) -> InputBindings {
+        InputBindings {
+            app: self.app.bindings.clone(),
+            main_view: self.main_view.bindings.clone(),
+            gui: self.gui.bindings.clone(),
+
+            capture: self.capture.clone(),
+            captured: self.captured.clone(),
+        }
+    }
+
+    /// If input capture is active, and the event is a key or mouse
+    /// button press, stores it as the captured input and returns true
+    fn capture_input(
+        &self,
+        event: &event::WindowEvent,
+        modifiers: event::ModifiersState,
+    ) -> bool {
+        if !self.capture.load() {
+            return false;
+        }
+
+        let captured = match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                use VirtualKeyCode as Key;
+
+                if input.state != ElementState::Pressed {
+                    return false;
+                }
+
+                match input.virtual_keycode {
+                    // modifiers are captured along with the key
+                    Some(Key::LShift) | Some(Key::RShift)
+                    | Some(Key::LControl) | Some(Key::RControl)
+                    | Some(Key::LAlt) | Some(Key::RAlt) | Some(Key::LWin)
+                    | Some(Key::RWin) | None => return false,
+                    Some(key) => CapturedInput::Key(key, modifiers),
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                if *state != ElementState::Pressed {
+                    return false;
+                }
+                CapturedInput::Mouse(*button, modifiers)
+            }
+            _ => return false,
+        };
+
+        self.captured.store(Some(captured));
+        self.capture.store(false);
+
+        true
+    }
+
     pub fn read_mouse_pos(&self) -> Point {
         self.mouse_screen_pos.load()
     }
@@ -139,6 +224,10 @@ impl InputManager {
 
             let modifiers = self.modifiers.load();
 
+            if self.capture_input(&winit_ev, modifiers) {
+                continue;
+            }
+
             if gui_wants_keyboard {
                 if let event::WindowEvent::KeyboardInput { input, .. } =
                     winit_ev
@@ -174,8 +263,11 @@ impl InputManager {
                 }
             }
 
-            if let Some(app_inputs) =
-                self.app.bindings.apply(&winit_ev, modifiers, mouse_pos)
+            if let Some(app_inputs) = self
+                .app
+                .bindings
+                .lock()
+                .apply(&winit_ev, modifiers, mouse_pos)
             {
                 for input in app_inputs {
                     if !(input.is_keyboard() && gui_wants_keyboard) {
@@ -184,8 +276,11 @@ impl InputManager {
                 }
             }
 
-            if let Some(gui_inputs) =
-                self.gui.bindings.apply(&winit_ev, modifiers, mouse_pos)
+            if let Some(gui_inputs) = self
+                .gui
+                .bindings
+                .lock()
+                .apply(&winit_ev, modifiers, mouse_pos)
             {
                 for input in gui_inputs {
                     self.gui.tx.send(input).unwrap();
@@ -195,6 +290,7 @@ impl InputManager {
             if let Some(main_view_inputs) = self
                 .main_view
                 .bindings
+                .lock()
                 .apply(&winit_ev, modifiers, mouse_pos)
             {
                 for input in main_view_inputs {
@@ -253,11 +349,192 @@ impl InputManager {
 
             gui_focus_state,
 
+            capture: Arc::new(AtomicCell::new(false)),
+            captured: Arc::new(AtomicCell::new(None)),
+
             custom_binds: FxHashMap::default(),
         }
     }
 }
 
+/// Map from key names, as used in scripts and config files, to the
+/// corresponding winit key codes. The names match the `Debug`
+/// representation of `VirtualKeyCode`.
+pub fn virtual_key_code_map() -> HashMap<String, winit::event::VirtualKeyCode> {
+    use winit::event::VirtualKeyCode as Key;
+
+    let keys = [
+        ("Key1", Key::Key1),
+        ("Key2", Key::Key2),
+        ("Key3", Key::Key3),
+        ("Key4", Key::Key4),
+        ("Key5", Key::Key5),
+        ("Key6", Key::Key6),
+        ("Key7", Key::Key7),
+        ("Key8", Key::Key8),
+        ("Key9", Key::Key9),
+        ("Key0", Key::Key0),
+        ("A", Key::A),
+        ("B", Key::B),
+        ("C", Key::C),
+        ("D", Key::D),
+        ("E", Key::E),
+        ("F", Key::F),
+        ("G", Key::G),
+        ("H", Key::H),
+        ("I", Key::I),
+        ("J", Key::J),
+        ("K", Key::K),
+        ("L", Key::L),
+        ("M", Key::M),
+        ("N", Key::N),
+        ("O", Key::O),
+        ("P", Key::P),
+        ("Q", Key::Q),
+        ("R", Key::R),
+        ("S", Key::S),
+        ("T", Key::T),
+        ("U", Key::U),
+        ("V", Key::V),
+        ("W", Key::W),
+        ("X", Key::X),
+        ("Y", Key::Y),
+        ("Z", Key::Z),
+        ("Escape", Key::Escape),
+        ("F1", Key::F1),
+        ("F2", Key::F2),
+        ("F3", Key::F3),
+        ("F4", Key::F4),
+        ("F5", Key::F5),
+        ("F6", Key::F6),
+        ("F7", Key::F7),
+        ("F8", Key::F8),
+        ("F9", Key::F9),
+        ("F10", Key::F10),
+        ("F11", Key::F11),
+        ("F12", Key::F12),
+        ("F13", Key::F13),
+        ("F14", Key::F14),
+        ("F15", Key::F15),
+        ("F16", Key::F16),
+        ("F17", Key::F17),
+        ("F18", Key::F18),
+        ("F19", Key::F19),
+        ("F20", Key::F20),
+        ("F21", Key::F21),
+        ("F22", Key::F22),
+        ("F23", Key::F23),
+        ("F24", Key::F24),
+        ("Snapshot", Key::Snapshot),
+        ("Scroll", Key::Scroll),
+        ("Pause", Key::Pause),
+        ("Insert", Key::Insert),
+        ("Home", Key::Home),
+        ("Delete", Key::Delete),
+        ("End", Key::End),
+        ("PageDown", Key::PageDown),
+        ("PageUp", Key::PageUp),
+        ("Left", Key::Left),
+        ("Up", Key::Up),
+        ("Right", Key::Right),
+        ("Down", Key::Down),
+        ("Back", Key::Back),
+        ("Return", Key::Return),
+        ("Space", Key::Space),
+        ("Compose", Key::Compose),
+        ("Caret", Key::Caret),
+        ("Numlock", Key::Numlock),
+        ("Numpad0", Key::Numpad0),
+        ("Numpad1", Key::Numpad1),
+        ("Numpad2", Key::Numpad2),
+        ("Numpad3", Key::Numpad3),
+        ("Numpad4", Key::Numpad4),
+        ("Numpad5", Key::Numpad5),
+        ("Numpad6", Key::Numpad6),
+        ("Numpad7", Key::Numpad7),
+        ("Numpad8", Key::Numpad8),
+        ("Numpad9", Key::Numpad9),
+        ("NumpadAdd", Key::NumpadAdd),
+        ("NumpadDivide", Key::NumpadDivide),
+        ("NumpadDecimal", Key::NumpadDecimal),
+        ("NumpadComma", Key::NumpadComma),
+        ("NumpadEnter", Key::NumpadEnter),
+        ("NumpadEquals", Key::NumpadEquals),
+        ("NumpadMultiply", Key::NumpadMultiply),
+        ("NumpadSubtract", Key::NumpadSubtract),
+        ("AbntC1", Key::AbntC1),
+        ("AbntC2", Key::AbntC2),
+        ("Apostrophe", Key::Apostrophe),
+        ("Apps", Key::Apps),
+        ("Asterisk", Key::Asterisk),
+        ("At", Key::At),
+        ("Ax", Key::Ax),
+        ("Backslash", Key::Backslash),
+        ("Calculator", Key::Calculator),
+        ("Capital", Key::Capital),
+        ("Colon", Key::Colon),
+        ("Comma", Key::Comma),
+        ("Convert", Key::Convert),
+        ("Equals", Key::Equals),
+        ("Grave", Key::Grave),
+        ("Kana", Key::Kana),
+        ("Kanji", Key::Kanji),
+        ("LAlt", Key::LAlt),
+        ("LBracket", Key::LBracket),
+        ("LControl", Key::LControl),
+        ("LShift", Key::LShift),
+        ("LWin", Key::LWin),
+        ("Mail", Key::Mail),
+        ("MediaSelect", Key::MediaSelect),
+        ("MediaStop", Key::MediaStop),
+        ("Minus", Key::Minus),
+        ("Mute", Key::Mute),
+        ("MyComputer", Key::MyComputer),
+        ("NavigateForward", Key::NavigateForward),
+        ("NavigateBackward", Key::NavigateBackward),
+        ("NextTrack", Key::NextTrack),
+        ("NoConvert", Key::NoConvert),
+        ("OEM102", Key::OEM102),
+        ("Period", Key::Period),
+        ("PlayPause", Key::PlayPause),
+        ("Plus", Key::Plus),
+        ("Power", Key::Power),
+        ("PrevTrack", Key::PrevTrack),
+        ("RAlt", Key::RAlt),
+        ("RBracket", Key::RBracket),
+        ("RControl", Key::RControl),
+        ("RShift", Key::RShift),
+        ("RWin", Key::RWin),
+        ("Semicolon", Key::Semicolon),
+        ("Slash", Key::Slash),
+        ("Sleep", Key::Sleep),
+        ("Stop", Key::Stop),
+        ("Sysrq", Key::Sysrq),
+        ("Tab", Key::Tab),
+        ("Underline", Key::Underline),
+        ("Unlabeled", Key::Unlabeled),
+        ("VolumeDown", Key::VolumeDown),
+        ("VolumeUp", Key::VolumeUp),
+        ("Wake", Key::Wake),
+        ("WebBack", Key::WebBack),
+        ("WebFavorites", Key::WebFavorites),
+        ("WebForward", Key::WebForward),
+        ("WebHome", Key::WebHome),
+        ("WebRefresh", Key::WebRefresh),
+        ("WebSearch", Key::WebSearch),
+        ("WebStop", Key::WebStop),
+        ("Yen", Key::Yen),
+        ("Copy", Key::Copy),
+        ("Paste", Key::Paste),
+        ("Cut", Key::Cut),
+    ]
+    .iter()
+    .map(|(n, c)| (n.to_string(), *c))
+    .collect();
+
+    keys
+}
+
 fn received_char_to_egui_text(c: char) -> egui::Event {
     egui::Event::Text(c.into())
 }
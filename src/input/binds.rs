@@ -19,8 +19,104 @@ impl<T> InputPayload for T where
 }
 
 /// Trait for app subsystem inputs that can be bound to keys and other user input
-pub trait BindableInput: InputPayload {
+pub trait BindableInput: InputPayload + std::fmt::Debug {
     fn default_binds() -> SystemInputBindings<Self>;
+
+    /// Every input of the subsystem, used when listing and parsing
+    /// the inputs by name
+    fn all_inputs() -> Vec<Self>;
+
+    fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::all_inputs().into_iter().find(|i| i.name() == name)
+    }
+}
+
+/// A key or mouse button press, with the modifiers held at the
+/// time, as captured when rebinding an input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CapturedInput {
+    Key(event::VirtualKeyCode, event::ModifiersState),
+    Mouse(event::MouseButton, event::ModifiersState),
+}
+
+pub fn modifiers_to_string(modifiers: event::ModifiersState) -> String {
+    let mut mods = Vec::new();
+
+    if modifiers.shift() {
+        mods.push("shift");
+    }
+    if modifiers.ctrl() {
+        mods.push("ctrl");
+    }
+    if modifiers.alt() {
+        mods.push("alt");
+    }
+    if modifiers.logo() {
+        mods.push("logo");
+    }
+
+    if mods.is_empty() {
+        "none".to_string()
+    } else {
+        mods.join("+")
+    }
+}
+
+pub fn parse_modifiers(mods: &str) -> Option<event::ModifiersState> {
+    let mut modifiers = event::ModifiersState::empty();
+
+    if mods == "none" {
+        return Some(modifiers);
+    }
+
+    for m in mods.split('+') {
+        match m {
+            "shift" => modifiers |= event::ModifiersState::SHIFT,
+            "ctrl" => modifiers |= event::ModifiersState::CTRL,
+            "alt" => modifiers |= event::ModifiersState::ALT,
+            "logo" => modifiers |= event::ModifiersState::LOGO,
+            _ => return None,
+        }
+    }
+
+    Some(modifiers)
+}
+
+pub fn mouse_button_to_string(button: event::MouseButton) -> String {
+    match button {
+        event::MouseButton::Left => "Left".to_string(),
+        event::MouseButton::Right => "Right".to_string(),
+        event::MouseButton::Middle => "Middle".to_string(),
+        event::MouseButton::Other(ix) => format!("Other{}", ix),
+    }
+}
+
+pub fn parse_mouse_button(button: &str) -> Option<event::MouseButton> {
+    match button {
+        "Left" => Some(event::MouseButton::Left),
+        "Right" => Some(event::MouseButton::Right),
+        "Middle" => Some(event::MouseButton::Middle),
+        other => {
+            let ix = other.strip_prefix("Other")?.parse::<u16>().ok()?;
+            Some(event::MouseButton::Other(ix))
+        }
+    }
+}
+
+/// Human-readable description of a binding, e.g. `shift+ctrl+A`
+pub fn binding_to_string(
+    modifiers: event::ModifiersState,
+    input: &str,
+) -> String {
+    if modifiers.is_empty() {
+        input.to_string()
+    } else {
+        format!("{}+{}", modifiers_to_string(modifiers), input)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -104,6 +200,22 @@ pub struct WheelBind<T: Copy + PartialEq> {
 }
 
 impl<T: Copy + PartialEq> WheelBind<T> {
+    pub fn invert(&self) -> bool {
+        self.invert
+    }
+
+    pub fn mult(&self) -> f32 {
+        self.mult
+    }
+
+    pub fn modifiers(&self) -> event::ModifiersState {
+        self.modifiers
+    }
+
+    pub fn payload(&self) -> T {
+        self.payload
+    }
+
     pub fn new(invert: bool, mult: f32, payload: T) -> Self {
         Self {
             invert,
@@ -228,6 +340,101 @@ impl<Inputs: InputPayload> SystemInputBindings<Inputs> {
         }
     }
 
+    /// All key bindings, as (key, modifiers, input) triples, sorted
+    /// by input
+    pub fn key_binds(
+        &self,
+    ) -> Vec<(event::VirtualKeyCode, event::ModifiersState, Inputs)> {
+        let mut binds = self
+            .key_binds
+            .iter()
+            .flat_map(|(key, binds)| {
+                binds.iter().map(move |b| (*key, b.modifiers, b.payload))
+            })
+            .collect::<Vec<_>>();
+        binds.sort_by_key(|(key, _, input)| (*input, *key as u32));
+        binds
+    }
+
+    /// All mouse button bindings, as (button, modifiers, input)
+    /// triples, sorted by input
+    pub fn mouse_binds(
+        &self,
+    ) -> Vec<(event::MouseButton, event::ModifiersState, Inputs)> {
+        let mut binds = self
+            .mouse_binds
+            .iter()
+            .flat_map(|(button, binds)| {
+                binds.iter().map(move |b| (*button, b.modifiers, b.payload))
+            })
+            .collect::<Vec<_>>();
+        binds.sort_by_key(|(_, _, input)| *input);
+        binds
+    }
+
+    pub fn wheel_bind(&self) -> Option<WheelBind<Inputs>> {
+        self.wheel_bind
+    }
+
+    pub fn set_wheel_bind(&mut self, wheel_bind: Option<WheelBind<Inputs>>) {
+        self.wheel_bind = wheel_bind;
+    }
+
+    /// Remove all key and mouse button bindings
+    pub fn clear_binds(&mut self) {
+        self.key_binds.clear();
+        self.mouse_binds.clear();
+    }
+
+    /// Remove all key and mouse button bindings to `input`
+    pub fn clear_input(&mut self, input: Inputs) {
+        for binds in self.key_binds.values_mut() {
+            binds.retain(|b| b.payload != input);
+        }
+        self.key_binds.retain(|_, binds| !binds.is_empty());
+
+        for binds in self.mouse_binds.values_mut() {
+            binds.retain(|b| b.payload != input);
+        }
+        self.mouse_binds.retain(|_, binds| !binds.is_empty());
+    }
+
+    pub fn bind_key(
+        &mut self,
+        key: event::VirtualKeyCode,
+        modifiers: event::ModifiersState,
+        input: Inputs,
+    ) {
+        let bind = KeyBind::with_modifiers(input, modifiers);
+        let binds = self.key_binds.entry(key).or_default();
+        if !binds.contains(&bind) {
+            binds.push(bind);
+        }
+    }
+
+    pub fn bind_mouse(
+        &mut self,
+        button: event::MouseButton,
+        modifiers: event::ModifiersState,
+        input: Inputs,
+    ) {
+        let bind = MouseButtonBind::with_modifiers(input, modifiers);
+        let binds = self.mouse_binds.entry(button).or_default();
+        if !binds.contains(&bind) {
+            binds.push(bind);
+        }
+    }
+
+    /// Bind `input` to a captured key or mouse button press
+    pub fn bind_captured(&mut self, captured: CapturedInput, input: Inputs) {
+        match captured {
+            CapturedInput::Key(key, mods) => self.bind_key(key, mods, input),
+            CapturedInput::Mouse(button, mods) => {
+                self.bind_mouse(button, mods, input)
+            }
+        }
+    }
+
     pub fn apply(
         &self,
         // input_state: &mut InputState<Inputs>,
@@ -6,9 +6,11 @@ pub mod geometry;
 pub mod vulkan;
 
 pub mod annotations;
+pub mod export;
 pub mod graph_query;
 pub mod gui;
 pub mod overlays;
+pub mod session;
 
 pub mod gfa;
 pub mod quad_tree;
@@ -20,12 +20,19 @@ use winit::event_loop::ControlFlow;
 #[allow(unused_imports)]
 use winit::window::{Window, WindowBuilder};
 
-use gfaestus::app::{mainview::*, Args, OverlayCreatorMsg, Select};
+use gfaestus::app::{
+    mainview::*,
+    tour::{TourFrame, TourPlayer, TourStep},
+    Args, ExportMsg, OverlayCreatorMsg, OverlayState, Select, SessionMsg,
+    TourMsg,
+};
 use gfaestus::app::{App, AppMsg};
+use gfaestus::export::{export_png, export_svg};
 use gfaestus::geometry::*;
 use gfaestus::graph_query::*;
 use gfaestus::input::*;
 use gfaestus::overlays::*;
+use gfaestus::session::{apply_session, save_session, Session};
 use gfaestus::universe::*;
 use gfaestus::view::View;
 use gfaestus::vulkan::render_pass::Framebuffers;
@@ -117,8 +124,28 @@ fn main() {
 
     log::debug!("Logger initalized");
 
-    let gfa_file = &args.gfa;
-    let layout_file = &args.layout;
+    let session = args.session.as_ref().map(|path| match Session::load(path) {
+        Ok(session) => session,
+        Err(err) => {
+            error!("Error loading session file {:?}", path);
+            error!("{:?}", err.root_cause());
+            std::process::exit(1);
+        }
+    });
+
+    let (gfa_file, layout_file) = match (&args.gfa, &args.layout, &session) {
+        (Some(gfa), Some(layout), _) => (gfa.to_owned(), layout.to_owned()),
+        (None, None, Some(session)) => (
+            session.gfa.to_string_lossy().to_string(),
+            session.layout.to_string_lossy().to_string(),
+        ),
+        _ => {
+            error!(
+                "A GFA and a layout file, or a session file, must be provided"
+            );
+            std::process::exit(1);
+        }
+    };
     log::debug!("using {} and {}", gfa_file, layout_file);
 
     let (mut gfaestus, event_loop, window) = match GfaestusVk::new(&args) {
@@ -169,10 +196,14 @@ fn main() {
     info!("Loading GFA");
     let t = std::time::Instant::now();
 
-    let graph_query = Arc::new(GraphQuery::load_gfa(gfa_file).unwrap());
+    let graph_query = Arc::new(GraphQuery::load_gfa(&gfa_file).unwrap());
 
     let mut app = App::new((100.0, 100.0)).expect("error when creating App");
 
+    app.shared_state()
+        .frame_capture_supported
+        .store(gfaestus.supports_frame_capture());
+
     let mut reactor = gfaestus::reactor::Reactor::init(
         thread_pool.clone(),
         rayon_pool,
@@ -184,7 +215,7 @@ fn main() {
         GraphQueryWorker::new(graph_query.clone(), thread_pool.clone());
 
     let (mut universe, stats) =
-        universe_from_gfa_layout(&graph_query, layout_file).unwrap();
+        universe_from_gfa_layout(&graph_query, &layout_file).unwrap();
 
     let (top_left, bottom_right) = universe.layout().bounding_box();
 
@@ -236,6 +267,19 @@ fn main() {
         println!("i'm a bound command!");
     });
 
+    if let Some(config_path) = gfaestus::app::config::config_file_path() {
+        if config_path.exists() {
+            info!("Loading config from {:?}", config_path);
+            if let Err(err) = gfaestus::app::config::load_config(
+                &config_path,
+                &app.settings,
+                &input_manager.bindings(),
+            ) {
+                error!("Error loading config: {:?}", err);
+            }
+        }
+    }
+
     let app_rx = input_manager.clone_app_rx();
     let main_view_rx = input_manager.clone_main_view_rx();
     let gui_rx = input_manager.clone_gui_rx();
@@ -269,10 +313,18 @@ fn main() {
         app.shared_state().clone(),
         app.channels(),
         app.settings.clone(),
+        input_manager.bindings(),
         &graph_query,
     )
     .unwrap();
 
+    gui.set_minimap_layout(universe.layout());
+
+    gui.validate_graph(
+        &graph_query_worker,
+        universe.layout().missing_nodes().to_vec(),
+    );
+
     // create default overlays
     {
         let node_seq_script = "
@@ -303,6 +355,7 @@ fn node_color(id) {
         create_overlay(
             &gfaestus,
             &mut main_view,
+            app.shared_state().overlay_state(),
             &reactor,
             "Node Seq Hash",
             node_seq_script,
@@ -312,6 +365,7 @@ fn node_color(id) {
         create_overlay(
             &gfaestus,
             &mut main_view,
+            app.shared_state().overlay_state(),
             &reactor,
             "Node Step Count",
             step_count_script,
@@ -326,6 +380,23 @@ fn node_color(id) {
     let mut initial_view: Option<View> = None;
     let mut initialized_view = false;
 
+    // the session is applied once the initial view has been set, so
+    // that the session's view isn't overwritten
+    let mut pending_session = session;
+    let session_rx = app.channels().session_rx.clone();
+
+    let mut tour_player: Option<TourPlayer> = None;
+    let tour_rx = app.channels().tour_rx.clone();
+
+    let export_rx = app.channels().export_rx.clone();
+
+    // rendered once the initial view and any session have been
+    // applied, after which gfaestus exits
+    let mut render_png = args
+        .render_png
+        .clone()
+        .map(|path| (path, args.render_width, args.render_height));
+
     let new_overlay_rx = app.channels().new_overlay_rx.clone();
 
     let mut modal_handler =
@@ -472,6 +543,9 @@ fn node_color(id) {
                 warn!("executing script {}", script_file);
                 gui.console.eval_line(&mut reactor, true, script).unwrap();
             }
+        } else if args.watch_script {
+            warn!("watching script file {}", script_file);
+            gui.console.watch_file(&mut reactor, script_file).unwrap();
         } else {
             warn!("executing script file {}", script_file);
             gui.console
@@ -492,6 +566,30 @@ fn node_color(id) {
         }
     }
 
+    {
+        let new_overlay_tx = app.channels().new_overlay_tx.clone();
+
+        for overlay_path in &args.overlay_files {
+            match load_overlay_file(overlay_path, graph_query.graph()) {
+                Ok((name, data, legend)) => {
+                    info!("Loaded overlay {} from {:?}", name, overlay_path);
+                    let msg = OverlayCreatorMsg::NewOverlay {
+                        name,
+                        data,
+                        legend,
+                    };
+                    new_overlay_tx.send(msg).unwrap();
+                }
+                Err(err) => {
+                    error!(
+                        "Error loading overlay file {:?}: {:?}",
+                        overlay_path, err
+                    );
+                }
+            }
+        }
+    }
+
     event_loop.run(move |event, _, control_flow| {
 
         *control_flow = ControlFlow::Poll;
@@ -544,6 +642,186 @@ fn node_color(id) {
                     initialized_view = true;
                 }
 
+                if initialized_view {
+                    if let Some(session) = pending_session.take() {
+                        if let Some(view) = session.view {
+                            initial_view = Some(view);
+                        }
+
+                        let overlay_state =
+                            app.shared_state().overlay_state();
+
+                        for msg in session.load_overlays(graph_query.graph())
+                        {
+                            if let Err(err) = handle_new_overlay(
+                                &gfaestus,
+                                &mut main_view,
+                                overlay_state,
+                                graph_query.node_count(),
+                                msg,
+                            ) {
+                                error!("Error creating overlay: {:?}", err);
+                            }
+                        }
+
+                        if let Err(err) = apply_session(
+                            session,
+                            &app,
+                            &main_view,
+                            &mut gui,
+                            &reactor,
+                            &gfa_file,
+                        ) {
+                            error!("Error applying session: {:?}", err);
+                        }
+                    }
+                }
+
+                while let Ok(session_msg) = session_rx.try_recv() {
+                    match session_msg {
+                        SessionMsg::Save(path) => {
+                            let result = save_session(
+                                &path,
+                                &gfa_file,
+                                &layout_file,
+                                &app,
+                                &main_view,
+                                &gui,
+                                graph_query.graph(),
+                            );
+
+                            let output = match result {
+                                Ok(_) => format!("Saved session to {:?}", path),
+                                Err(err) => {
+                                    error!("Error saving session: {:?}", err);
+                                    format!("Error saving session: {:?}", err)
+                                }
+                            };
+                            gui.console.append_output(&output);
+                        }
+                        SessionMsg::Load(path) => match Session::load(&path) {
+                            Ok(session) => {
+                                info!("Loading session {:?}", path);
+                                pending_session = Some(session);
+                            }
+                            Err(err) => {
+                                error!("Error loading session: {:?}", err);
+                                gui.console.append_output(&format!(
+                                    "Error loading session: {:?}",
+                                    err
+                                ));
+                            }
+                        },
+                    }
+                }
+
+                while let Ok(export_msg) = export_rx.try_recv() {
+                    match export_msg {
+                        ExportMsg::Svg { path, whole_graph } => {
+                            let result = export_svg(
+                                &path,
+                                whole_graph,
+                                &app,
+                                &main_view,
+                                universe.layout(),
+                                graph_query.graph(),
+                            );
+
+                            let output = match result {
+                                Ok(_) => format!("Exported SVG to {:?}", path),
+                                Err(err) => {
+                                    error!("Error exporting SVG: {:?}", err);
+                                    format!("Error exporting SVG: {:?}", err)
+                                }
+                            };
+                            gui.console.append_output(&output);
+                        }
+                        ExportMsg::Png {
+                            path,
+                            width,
+                            height,
+                        } => {
+                            let result = export_png(
+                                &path,
+                                width,
+                                height,
+                                &gfaestus,
+                                &app,
+                                &mut main_view,
+                                &mut edge_renderer,
+                                &mut selection_edge,
+                                &mut selection_blur,
+                                &gradients,
+                            );
+
+                            let output = match result {
+                                Ok(_) => format!("Exported PNG to {:?}", path),
+                                Err(err) => {
+                                    error!("Error exporting PNG: {:?}", err);
+                                    format!("Error exporting PNG: {:?}", err)
+                                }
+                            };
+                            gui.console.append_output(&output);
+                        }
+                    }
+                }
+
+                if initial_view.is_some() && pending_session.is_none() {
+                    if let Some((path, width, height)) = render_png.take() {
+                        let width =
+                            width.unwrap_or(screen_dims.width as u32);
+
+                        match export_png(
+                            &path,
+                            width,
+                            height,
+                            &gfaestus,
+                            &app,
+                            &mut main_view,
+                            &mut edge_renderer,
+                            &mut selection_edge,
+                            &mut selection_blur,
+                            &gradients,
+                        ) {
+                            Ok(_) => info!("Rendered PNG to {:?}", path),
+                            Err(err) => {
+                                error!("Error rendering PNG: {:?}", err)
+                            }
+                        }
+
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+
+                while let Ok(tour_msg) = tour_rx.try_recv() {
+                    match tour_msg {
+                        TourMsg::Play { tour, frame_dir } => {
+                            let player = if frame_dir.is_some()
+                                && !gfaestus.supports_frame_capture()
+                            {
+                                Err(anyhow::anyhow!(
+                                    "Frame capture is not supported"
+                                ))
+                            } else {
+                                let view = main_view.view();
+                                TourPlayer::new(tour, view, frame_dir)
+                            };
+
+                            match player {
+                                Ok(player) => tour_player = Some(player),
+                                Err(err) => {
+                                    error!("Error starting tour: {:?}", err);
+                                    gui.console.append_output(&format!(
+                                        "Error starting tour: {:?}",
+                                        err
+                                    ));
+                                }
+                            }
+                        }
+                        TourMsg::Stop => tour_player = None,
+                    }
+                }
+
                 // hacky -- this should take place after mouse pos is updated
                 // in egui but before input is sent to mainview
                 input_manager.handle_events(&mut reactor, &gui_msg_tx);
@@ -660,6 +938,7 @@ fn node_color(id) {
                     if let Ok(_) = handle_new_overlay(
                         &gfaestus,
                         &mut main_view,
+                        app.shared_state().overlay_state(),
                         graph_query.node_count(),
                         new_overlay
                     ) {
@@ -746,6 +1025,27 @@ fn node_color(id) {
 
                 let frame_t = std::time::Instant::now();
 
+                let mut frame_capture_path = None;
+
+                if let Some(player) = tour_player.as_mut() {
+                    let tour_frame = player.advance();
+
+                    frame_capture_path = apply_tour_frame(tour_frame, &app, &main_view);
+
+                    if frame_capture_path.is_some() {
+                        if let Err(err) = gfaestus.request_frame_capture() {
+                            error!("Error capturing tour frame: {:?}", err);
+                            frame_capture_path = None;
+                            player.stop_exporting();
+                        }
+                    }
+
+                    if player.is_done() {
+                        info!("Tour finished");
+                        tour_player = None;
+                    }
+                }
+
                 if dirty_swapchain {
                     let size = window.inner_size();
                     log::trace!("Dirty swapchain, reconstructing");
@@ -755,6 +1055,10 @@ fn node_color(id) {
                             .recreate_swapchain(Some([size.width, size.height]))
                             .unwrap();
 
+                        app.shared_state()
+                            .frame_capture_supported
+                            .store(gfaestus.supports_frame_capture());
+
                         selection_edge.write_descriptor_set(
                             gfaestus.vk_context().device(),
                             gfaestus.node_attachments.mask_resolve,
@@ -827,6 +1131,17 @@ fn node_color(id) {
                     // cluster_tree.draw_clusters(&gui.ctx, view);
                 }
 
+                gui.draw_ruler(
+                    &graph_query,
+                    universe.layout().nodes(),
+                    universe.bp_per_world_unit(),
+                );
+
+                gui.draw_path_highlights(
+                    &graph_query,
+                    universe.layout().nodes(),
+                );
+
 
                 /*
                 let annotations = app.annotations();
@@ -1234,6 +1549,18 @@ fn node_color(id) {
                 let size = window.inner_size();
                 dirty_swapchain = gfaestus.draw_frame_from([size.width, size.height], draw).unwrap();
 
+                if let Some(path) = frame_capture_path {
+                    if let Some(captured) = gfaestus.take_captured_frame() {
+                        reactor.spawn_forget(async move {
+                            if let Err(err) = captured.write_png(&path) {
+                                error!("Error writing frame {:?}: {:?}", path, err);
+                            }
+                        }).unwrap();
+                    } else {
+                        warn!("Frame {:?} could not be captured", path);
+                    }
+                }
+
                 if !dirty_swapchain {
                     let screen_dims = app.dims();
 
@@ -1304,50 +1631,89 @@ fn node_color(id) {
 fn handle_new_overlay(
     app: &GfaestusVk,
     main_view: &mut MainView,
+    overlay_state: &OverlayState,
     node_count: usize,
     msg: OverlayCreatorMsg,
 ) -> Result<()> {
-    let OverlayCreatorMsg::NewOverlay { name, data } = msg;
-
-    let overlay = match data {
-        OverlayData::RGB(data) => {
-            let mut overlay =
-                Overlay::new_empty_rgb(&name, app, node_count).unwrap();
-
-            overlay
-                .update_rgb_overlay(
-                    data.iter()
-                        .enumerate()
-                        .map(|(ix, col)| (NodeId::from((ix as u64) + 1), *col)),
-                )
-                .unwrap();
-
-            overlay
+    let (name, data, legend, replace) = match msg {
+        OverlayCreatorMsg::NewOverlay { name, data, legend } => {
+            (name, data, legend, false)
         }
-        OverlayData::Value(data) => {
-            let mut overlay =
-                Overlay::new_empty_value(&name, &app, node_count).unwrap();
-
-            overlay
-                .update_value_overlay(
-                    data.iter()
-                        .enumerate()
-                        .map(|(ix, v)| (NodeId::from((ix as u64) + 1), *v)),
-                )
-                .unwrap();
+        OverlayCreatorMsg::ReplaceOverlay { name, data, legend } => {
+            (name, data, legend, true)
+        }
+        OverlayCreatorMsg::SetNormalization {
+            overlay_id,
+            normalization,
+        } => {
+            overlay_state.set_overlay_normalization(overlay_id, normalization);
+            return upload_normalized_overlay(
+                main_view,
+                overlay_state,
+                overlay_id,
+            );
+        }
+    };
 
-            overlay
+    let pipelines = &mut main_view.node_draw_system.pipelines;
+
+    let existing = if replace {
+        pipelines.overlay_id(&name)
+    } else {
+        None
+    };
+
+    let overlay_id = if let Some(overlay_id) = existing {
+        if pipelines.overlay_kind(overlay_id) == Some(data.kind()) {
+            let overlay = pipelines.overlay_mut(overlay_id).unwrap();
+            overlay.update_from_data(&data)?;
+        } else {
+            let overlay =
+                Overlay::new_with_data(&name, app, node_count, &data)?;
+            pipelines.replace_overlay(app, overlay_id, overlay)?;
         }
+        overlay_id
+    } else {
+        let overlay = Overlay::new_with_data(&name, app, node_count, &data)?;
+        pipelines.create_overlay(overlay)
     };
 
-    main_view.node_draw_system.pipelines.create_overlay(overlay);
+    overlay_state.set_overlay_data(overlay_id, Arc::new(data));
+    overlay_state.set_overlay_legend(overlay_id, legend);
+
+    // a replaced overlay keeps its normalization
+    if overlay_state.overlay_normalization(overlay_id)
+        != OverlayNormalization::Linear
+    {
+        upload_normalized_overlay(main_view, overlay_state, overlay_id)?;
+    }
 
     Ok(())
 }
 
+/// Uploads the data of an overlay with its normalization applied
+fn upload_normalized_overlay(
+    main_view: &mut MainView,
+    overlay_state: &OverlayState,
+    overlay_id: usize,
+) -> Result<()> {
+    let data = overlay_state
+        .normalized_overlay_data(overlay_id)
+        .ok_or_else(|| anyhow::anyhow!("Overlay {} has no data", overlay_id))?;
+
+    let overlay = main_view
+        .node_draw_system
+        .pipelines
+        .overlay_mut(overlay_id)
+        .ok_or_else(|| anyhow::anyhow!("Overlay {} not found", overlay_id))?;
+
+    overlay.update_from_data(&data)
+}
+
 fn create_overlay(
     app: &GfaestusVk,
     main_view: &mut MainView,
+    overlay_state: &OverlayState,
     reactor: &Reactor,
     name: &str,
     script: &str,
@@ -1359,7 +1725,7 @@ fn create_overlay(
         target: gfaestus::script::ScriptTarget::Nodes,
     };
 
-    if let Ok(data) = gfaestus::script::overlay_colors_tgt(
+    if let Ok((data, legend)) = gfaestus::script::overlay_colors_tgt(
         &reactor.rayon_pool,
         &script_config,
         &reactor.graph_query,
@@ -1368,8 +1734,9 @@ fn create_overlay(
         let msg = OverlayCreatorMsg::NewOverlay {
             name: name.to_string(),
             data,
+            legend,
         };
-        handle_new_overlay(app, main_view, node_count, msg)?;
+        handle_new_overlay(app, main_view, overlay_state, node_count, msg)?;
     }
 
     Ok(())
@@ -1405,3 +1772,56 @@ where
         gfaestus::gui::text::draw_rect_world(ctx, view, rect, Some(color));
     }
 }
+
+/// Apply the view and actions of a tour frame, returning the path
+/// the frame should be written to, if any
+fn apply_tour_frame(
+    tour_frame: TourFrame,
+    app: &App,
+    main_view: &MainView,
+) -> Option<std::path::PathBuf> {
+    if let Some(view) = tour_frame.view {
+        main_view.set_view(view);
+    }
+
+    let app_tx = &app.channels().app_tx;
+
+    for action in tour_frame.actions {
+        match action {
+            TourStep::Overlay(name) => {
+                let pipelines = &main_view.node_draw_system.pipelines;
+                if let Some(id) = pipelines.overlay_id(&name) {
+                    app.shared_state()
+                        .overlay_state()
+                        .set_current_overlay(Some(id));
+                } else {
+                    warn!("Tour overlay `{}` not found", name);
+                }
+            }
+            TourStep::Select(nodes) => {
+                let nodes = nodes.into_iter().collect();
+                app_tx
+                    .send(AppMsg::Selection(Select::Many {
+                        nodes,
+                        clear: true,
+                    }))
+                    .unwrap();
+            }
+            TourStep::ClearSelection => {
+                app_tx.send(AppMsg::Selection(Select::Clear)).unwrap();
+            }
+            TourStep::Labels { name, visible } => {
+                if let Some(label_set) =
+                    app.annotations().label_sets().get(&name)
+                {
+                    label_set.set_visibility(visible);
+                } else {
+                    warn!("Tour label set `{}` not found", name);
+                }
+            }
+            TourStep::View { .. } | TourStep::Wait(_) => (),
+        }
+    }
+
+    tour_frame.frame_path
+}
@@ -1,3 +1,17 @@
+use std::{
+    borrow::Cow,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+
+use handlegraph::{handlegraph::*, packedgraph::PackedGraph};
+
+pub mod builtin;
+
+pub use builtin::BuiltinOverlay;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// Defines the type of mapping from node ID to colors used by an
 /// overlay script
@@ -10,11 +24,257 @@ pub enum OverlayKind {
     Value,
 }
 
+#[derive(Debug, Clone)]
 pub enum OverlayData {
     RGB(Vec<rgb::RGBA<f32>>),
     Value(Vec<f32>),
 }
 
+/// Maps the colors of an overlay back to the values they were
+/// computed from, so that they can be shown in a legend
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayLegend {
+    /// A value overlay whose values were normalized to 0..1 from
+    /// `min..max`
+    Range { min: f32, max: f32, units: String },
+    /// An RGB overlay where each color stands for a value, e.g. the
+    /// hashed values of an annotation column
+    Categories(Vec<(rgb::RGBA<f32>, String)>),
+}
+
+impl OverlayLegend {
+    pub fn range(min: f32, max: f32) -> Self {
+        OverlayLegend::Range {
+            min,
+            max,
+            units: String::new(),
+        }
+    }
+
+    /// Builds a categorical legend from color/value pairs, with one
+    /// entry per distinct value, sorted by value
+    pub fn categories(
+        entries: impl IntoIterator<Item = (rgb::RGBA<f32>, String)>,
+    ) -> Self {
+        let categories = entries
+            .into_iter()
+            .map(|(color, value)| (value, color))
+            .collect::<std::collections::BTreeMap<_, _>>();
+
+        OverlayLegend::Categories(
+            categories
+                .into_iter()
+                .map(|(value, color)| (color, value))
+                .collect(),
+        )
+    }
+
+    /// Formats a value of a range legend, with its units if any
+    pub fn format_value(value: f32, units: &str) -> String {
+        let value = if value.fract() == 0.0 && value.abs() < 1.0e9 {
+            format!("{}", value as i64)
+        } else {
+            format!("{:.3}", value)
+        };
+
+        if units.is_empty() {
+            value
+        } else {
+            format!("{} {}", value, units)
+        }
+    }
+
+    /// The legend of the overlay after renormalizing it, with the
+    /// range covered by the gradient
+    pub fn normalized(&self, normalization: OverlayNormalization) -> Self {
+        match self {
+            OverlayLegend::Range { min, max, units } => {
+                let at = |t: f32| normalization.value_at(t, *min, *max);
+                OverlayLegend::Range {
+                    min: at(0.0).unwrap_or(*min),
+                    max: at(1.0).unwrap_or(*max),
+                    units: units.clone(),
+                }
+            }
+            OverlayLegend::Categories(_) => self.clone(),
+        }
+    }
+
+    /// The value that a normalized overlay value `t` corresponds to,
+    /// for range legends
+    pub fn value_at(&self, t: f32) -> Option<f32> {
+        match self {
+            OverlayLegend::Range { min, max, .. } => {
+                Some(min + t * (max - min))
+            }
+            OverlayLegend::Categories(_) => None,
+        }
+    }
+}
+
+/// How the values of a value overlay are mapped to 0..1, and from
+/// there to the colors of the gradient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlayNormalization {
+    /// `(x - min) / (max - min)`, as done when the overlay is created
+    Linear,
+    /// Linear on `ln(1 + x - min)`, for values spanning several orders
+    /// of magnitude, such as coverage
+    Log,
+    /// Linear from `min` to `max`, with values outside the range
+    /// clamped to it
+    Clamp { min: f32, max: f32 },
+    /// The rank of each value, so that every color of the gradient is
+    /// used by the same number of nodes
+    Quantile,
+    /// `mid` maps to the middle of the gradient, and the values on
+    /// either side are scaled by the same factor
+    Diverging { mid: f32 },
+}
+
+impl std::default::Default for OverlayNormalization {
+    fn default() -> Self {
+        OverlayNormalization::Linear
+    }
+}
+
+impl OverlayNormalization {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OverlayNormalization::Linear => "Linear",
+            OverlayNormalization::Log => "Log",
+            OverlayNormalization::Clamp { .. } => "Clamp",
+            OverlayNormalization::Quantile => "Quantile",
+            OverlayNormalization::Diverging { .. } => "Diverging",
+        }
+    }
+
+    /// Maps `values` to 0..1. If all values are equal, they're mapped
+    /// to 0, or 0.5 when diverging
+    pub fn normalize(&self, values: &[f32]) -> Vec<f32> {
+        let (min, max) = value_range(values);
+
+        let scale = |x: f32, lo: f32, hi: f32| {
+            if hi > lo {
+                ((x - lo) / (hi - lo)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+
+        match *self {
+            OverlayNormalization::Linear => {
+                values.iter().map(|&x| scale(x, min, max)).collect()
+            }
+            OverlayNormalization::Log => {
+                let hi = (max - min).ln_1p();
+                values
+                    .iter()
+                    .map(|&x| scale((x - min).ln_1p(), 0.0, hi))
+                    .collect()
+            }
+            OverlayNormalization::Clamp { min, max } => {
+                values.iter().map(|&x| scale(x, min, max)).collect()
+            }
+            OverlayNormalization::Quantile => {
+                let mut order = (0..values.len()).collect::<Vec<_>>();
+                order.sort_by(|&a, &b| {
+                    values[a]
+                        .partial_cmp(&values[b])
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+
+                let last = (values.len().max(2) - 1) as f32;
+
+                let mut result = vec![0.0; values.len()];
+
+                // equal values get the same rank, and thus color
+                let mut rank = 0;
+                for (i, &ix) in order.iter().enumerate() {
+                    if i > 0 && values[ix] != values[order[i - 1]] {
+                        rank = i;
+                    }
+                    result[ix] = rank as f32 / last;
+                }
+
+                result
+            }
+            OverlayNormalization::Diverging { mid } => {
+                let dist = (max - mid).abs().max((mid - min).abs());
+                values
+                    .iter()
+                    .map(|&x| {
+                        if dist > 0.0 {
+                            (0.5 + 0.5 * (x - mid) / dist).clamp(0.0, 1.0)
+                        } else {
+                            0.5
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The value that maps to `t` in 0..1, given the original range
+    /// of the values. Quantiles can only be found for the ends of
+    /// the range without the values themselves
+    pub fn value_at(&self, t: f32, min: f32, max: f32) -> Option<f32> {
+        match *self {
+            OverlayNormalization::Linear => Some(min + t * (max - min)),
+            OverlayNormalization::Log => {
+                Some(min + ((max - min).ln_1p() * t).exp_m1())
+            }
+            OverlayNormalization::Clamp { min, max } => {
+                Some(min + t * (max - min))
+            }
+            OverlayNormalization::Quantile => {
+                if t <= 0.0 {
+                    Some(min)
+                } else if t >= 1.0 {
+                    Some(max)
+                } else {
+                    None
+                }
+            }
+            OverlayNormalization::Diverging { mid } => {
+                let dist = (max - mid).abs().max((mid - min).abs());
+                Some(mid + (2.0 * t - 1.0) * dist)
+            }
+        }
+    }
+
+    /// Renormalizes the data of a value overlay, whose values were
+    /// normalized linearly from the range in `legend` (or 0..1 if
+    /// there is no legend)
+    pub fn apply<'a>(
+        &self,
+        data: &'a OverlayData,
+        legend: Option<&OverlayLegend>,
+    ) -> Cow<'a, OverlayData> {
+        match (self, data) {
+            (OverlayNormalization::Linear, _) | (_, OverlayData::RGB(_)) => {
+                Cow::Borrowed(data)
+            }
+            (_, OverlayData::Value(values)) => {
+                let original = values
+                    .iter()
+                    .map(|&t| legend.and_then(|l| l.value_at(t)).unwrap_or(t))
+                    .collect::<Vec<_>>();
+
+                Cow::Owned(OverlayData::Value(self.normalize(&original)))
+            }
+        }
+    }
+}
+
+fn value_range(values: &[f32]) -> (f32, f32) {
+    values
+        .iter()
+        .fold((std::f32::MAX, std::f32::MIN), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        })
+}
+
 pub fn hash_node_color(hash: u64) -> (f32, f32, f32) {
     let r_u16 = ((hash >> 32) & 0xFFFFFFFF) as u16;
     let g_u16 = ((hash >> 16) & 0xFFFFFFFF) as u16;
@@ -26,3 +286,456 @@ pub fn hash_node_color(hash: u64) -> (f32, f32, f32) {
     let b = (b_u16 as f32) / max;
     (r, g, b)
 }
+
+impl OverlayData {
+    pub fn kind(&self) -> OverlayKind {
+        match self {
+            OverlayData::RGB(_) => OverlayKind::RGB,
+            OverlayData::Value(_) => OverlayKind::Value,
+        }
+    }
+
+    /// The number of nodes the overlay covers; the value at index `i`
+    /// belongs to node `i + 1`
+    pub fn len(&self) -> usize {
+        match self {
+            OverlayData::RGB(colors) => colors.len(),
+            OverlayData::Value(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the overlay as a TSV, with one line per node: the node
+    /// ID followed by either the value, or the RGBA components
+    pub fn write_tsv<W: Write>(&self, mut out: W) -> std::io::Result<()> {
+        match self {
+            OverlayData::RGB(colors) => {
+                for (ix, color) in colors.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}\t{}\t{}",
+                        ix + 1,
+                        color.r,
+                        color.g,
+                        color.b,
+                        color.a
+                    )?;
+                }
+            }
+            OverlayData::Value(values) => {
+                for (ix, value) in values.iter().enumerate() {
+                    writeln!(out, "{}\t{}", ix + 1, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The file extension used for saved overlays
+pub const OVERLAY_FILE_EXT: &str = "gfo";
+
+const OVERLAY_FILE_MAGIC: &[u8; 8] = b"GFAESOVL";
+const OVERLAY_FILE_VERSION: u32 = 1;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// 64-bit FNV-1a, which unlike the hashers in `std` and `rustc_hash`
+/// is fully specified, so its output can be stored on disk
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        for byte in value.to_le_bytes().iter() {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hash of the node IDs and sequence lengths of a graph, used to make
+/// sure an overlay file is only loaded for the graph it was computed
+/// from.
+///
+/// This is FNV-1a over the node count, and then the ID and length of
+/// each node in ID order, all as little-endian `u64`s, so the result
+/// is the same across builds and platforms.
+pub fn graph_checksum(graph: &PackedGraph) -> u64 {
+    let mut handles = graph.handles().collect::<Vec<_>>();
+    handles.sort();
+
+    let mut hasher = Fnv1a::new();
+    hasher.write_u64(handles.len() as u64);
+
+    for handle in handles {
+        hasher.write_u64(handle.id().0);
+        hasher.write_u64(graph.node_len(handle) as u64);
+    }
+
+    hasher.finish()
+}
+
+fn write_str<W: Write>(out: &mut W, s: &str) -> std::io::Result<()> {
+    out.write_all(&(s.len() as u32).to_le_bytes())?;
+    out.write_all(s.as_bytes())
+}
+
+/// Reads a length or count from the file, failing if it's larger
+/// than `max`, which should be bounded by the size of the file, so a
+/// corrupt file can't make us allocate gigabytes
+fn read_len<R: Read>(input: &mut R, max: u64) -> Result<usize> {
+    let mut u32_buf = [0u8; 4];
+    input.read_exact(&mut u32_buf)?;
+    let len = u32::from_le_bytes(u32_buf);
+
+    if len as u64 > max {
+        bail!("Overlay file is truncated or corrupt");
+    }
+
+    Ok(len as usize)
+}
+
+fn read_str<R: Read>(input: &mut R, file_len: u64) -> Result<String> {
+    let len = read_len(input, file_len)?;
+
+    let mut bytes = vec![0u8; len];
+    input.read_exact(&mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn read_f32<R: Read>(input: &mut R) -> Result<f32> {
+    let mut u32_buf = [0u8; 4];
+    input.read_exact(&mut u32_buf)?;
+    Ok(f32::from_le_bytes(u32_buf))
+}
+
+/// Writes a legend as a tag byte -- 0 for no legend, 1 for a range,
+/// 2 for categories -- followed by the min, max, and units of a
+/// range, or the number of categories and the RGBA color and value
+/// of each category
+fn write_legend<W: Write>(
+    out: &mut W,
+    legend: Option<&OverlayLegend>,
+) -> std::io::Result<()> {
+    match legend {
+        None => out.write_all(&[0]),
+        Some(OverlayLegend::Range { min, max, units }) => {
+            out.write_all(&[1])?;
+            out.write_all(&min.to_le_bytes())?;
+            out.write_all(&max.to_le_bytes())?;
+            write_str(out, units)
+        }
+        Some(OverlayLegend::Categories(categories)) => {
+            out.write_all(&[2])?;
+            out.write_all(&(categories.len() as u32).to_le_bytes())?;
+            for (color, value) in categories {
+                for v in [color.r, color.g, color.b, color.a].iter() {
+                    out.write_all(&v.to_le_bytes())?;
+                }
+                write_str(out, value)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn read_legend<R: Read>(
+    input: &mut R,
+    file_len: u64,
+) -> Result<Option<OverlayLegend>> {
+    let mut tag = [0u8; 1];
+    input.read_exact(&mut tag)?;
+
+    match tag[0] {
+        0 => Ok(None),
+        1 => {
+            let min = read_f32(input)?;
+            let max = read_f32(input)?;
+            let units = read_str(input, file_len)?;
+            Ok(Some(OverlayLegend::Range { min, max, units }))
+        }
+        2 => {
+            // each category takes at least 20 bytes, the color and
+            // the length of the value
+            let count = read_len(input, file_len / 20)?;
+
+            let mut categories = Vec::with_capacity(count);
+            for _ in 0..count {
+                let r = read_f32(input)?;
+                let g = read_f32(input)?;
+                let b = read_f32(input)?;
+                let a = read_f32(input)?;
+                let value = read_str(input, file_len)?;
+                categories.push((rgb::RGBA::new(r, g, b, a), value));
+            }
+            Ok(Some(OverlayLegend::Categories(categories)))
+        }
+        t => bail!("Unknown overlay legend type {}", t),
+    }
+}
+
+/// Writes an overlay to `path` in the binary overlay format.
+///
+/// The file consists of a header containing a magic string, format
+/// version, overlay kind, node count, and graph checksum, followed by
+/// the overlay name, the legend, and then the per-node data as
+/// little-endian `f32`s -- one per node for value overlays, four
+/// (RGBA) per node for color overlays.
+pub fn save_overlay_file<P: AsRef<Path>>(
+    path: P,
+    graph: &PackedGraph,
+    name: &str,
+    data: &OverlayData,
+    legend: Option<&OverlayLegend>,
+) -> Result<()> {
+    let node_count = graph.node_count();
+
+    if data.len() != node_count {
+        bail!(
+            "Overlay has {} entries, but the graph has {} nodes",
+            data.len(),
+            node_count
+        );
+    }
+
+    let file = std::fs::File::create(path)?;
+    let mut out = BufWriter::new(file);
+
+    out.write_all(OVERLAY_FILE_MAGIC)?;
+    out.write_all(&OVERLAY_FILE_VERSION.to_le_bytes())?;
+
+    let kind: u8 = match data.kind() {
+        OverlayKind::RGB => 0,
+        OverlayKind::Value => 1,
+    };
+    out.write_all(&[kind])?;
+
+    out.write_all(&(node_count as u64).to_le_bytes())?;
+    out.write_all(&graph_checksum(graph).to_le_bytes())?;
+
+    write_str(&mut out, name)?;
+    write_legend(&mut out, legend)?;
+
+    match data {
+        OverlayData::RGB(colors) => {
+            for color in colors {
+                for v in [color.r, color.g, color.b, color.a].iter() {
+                    out.write_all(&v.to_le_bytes())?;
+                }
+            }
+        }
+        OverlayData::Value(values) => {
+            for v in values {
+                out.write_all(&v.to_le_bytes())?;
+            }
+        }
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+/// Loads an overlay saved with `save_overlay_file`, returning the
+/// overlay name, data, and legend. Fails if the file was created for a graph
+/// with a different node count or checksum.
+pub fn load_overlay_file<P: AsRef<Path>>(
+    path: P,
+    graph: &PackedGraph,
+) -> Result<(String, OverlayData, Option<OverlayLegend>)> {
+    let file = std::fs::File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut input = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    input.read_exact(&mut magic)?;
+    if &magic != OVERLAY_FILE_MAGIC {
+        bail!("Not a gfaestus overlay file");
+    }
+
+    let mut u32_buf = [0u8; 4];
+    let mut u64_buf = [0u8; 8];
+
+    input.read_exact(&mut u32_buf)?;
+    let version = u32::from_le_bytes(u32_buf);
+    if version != OVERLAY_FILE_VERSION {
+        bail!("Unsupported overlay file version {}", version);
+    }
+
+    let mut kind = [0u8; 1];
+    input.read_exact(&mut kind)?;
+    let kind = match kind[0] {
+        0 => OverlayKind::RGB,
+        1 => OverlayKind::Value,
+        k => bail!("Unknown overlay kind {}", k),
+    };
+
+    input.read_exact(&mut u64_buf)?;
+    let node_count = u64::from_le_bytes(u64_buf) as usize;
+    if node_count != graph.node_count() {
+        bail!(
+            "Overlay file has {} nodes, but the graph has {}",
+            node_count,
+            graph.node_count()
+        );
+    }
+
+    input.read_exact(&mut u64_buf)?;
+    let checksum = u64::from_le_bytes(u64_buf);
+    if checksum != graph_checksum(graph) {
+        bail!("Overlay file was created for a different graph");
+    }
+
+    let name = read_str(&mut input, file_len)?;
+    let legend = read_legend(&mut input, file_len)?;
+
+    let data = match kind {
+        OverlayKind::RGB => {
+            let mut colors = Vec::with_capacity(node_count);
+            for _ in 0..node_count {
+                let r = read_f32(&mut input)?;
+                let g = read_f32(&mut input)?;
+                let b = read_f32(&mut input)?;
+                let a = read_f32(&mut input)?;
+                colors.push(rgb::RGBA::new(r, g, b, a));
+            }
+            OverlayData::RGB(colors)
+        }
+        OverlayKind::Value => {
+            let mut values = Vec::with_capacity(node_count);
+            for _ in 0..node_count {
+                values.push(read_f32(&mut input)?);
+            }
+            OverlayData::Value(values)
+        }
+    };
+
+    Ok((name, data, legend))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn legends_roundtrip() {
+        let legends = vec![
+            None,
+            Some(OverlayLegend::Range {
+                min: -1.5,
+                max: 20.0,
+                units: "bp".to_string(),
+            }),
+            Some(OverlayLegend::Categories(vec![
+                (rgb::RGBA::new(1.0, 0.0, 0.0, 1.0), "gene".to_string()),
+                (rgb::RGBA::new(0.0, 0.5, 1.0, 1.0), "exon".to_string()),
+            ])),
+        ];
+
+        for legend in legends {
+            let mut bytes = Vec::new();
+            write_legend(&mut bytes, legend.as_ref()).unwrap();
+
+            let file_len = bytes.len() as u64;
+            let read = read_legend(&mut Cursor::new(bytes), file_len).unwrap();
+
+            assert_eq!(read, legend);
+        }
+    }
+
+    #[test]
+    fn lengths_past_the_end_of_the_file_are_errors() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        bytes.extend_from_slice(b"short");
+
+        let file_len = bytes.len() as u64;
+        assert!(read_str(&mut Cursor::new(bytes), file_len).is_err());
+
+        let mut bytes = vec![2u8];
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+        let file_len = bytes.len() as u64;
+        assert!(read_legend(&mut Cursor::new(bytes), file_len).is_err());
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1.0e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn normalize_log() {
+        let e = std::f32::consts::E;
+
+        let values = [0.0, e - 1.0, e * e - 1.0];
+        assert_close(
+            &OverlayNormalization::Log.normalize(&values),
+            &[0.0, 0.5, 1.0],
+        );
+
+        // the values are shifted so the smallest is at 0
+        let values = [10.0, 10.0 + e - 1.0];
+        assert_close(
+            &OverlayNormalization::Log.normalize(&values),
+            &[0.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn normalize_quantile() {
+        let values = [5.0, 1.0, 3.0, 3.0];
+        assert_close(
+            &OverlayNormalization::Quantile.normalize(&values),
+            &[1.0, 0.0, 1.0 / 3.0, 1.0 / 3.0],
+        );
+
+        assert_close(&OverlayNormalization::Quantile.normalize(&[7.0]), &[0.0]);
+    }
+
+    #[test]
+    fn normalize_diverging() {
+        let norm = OverlayNormalization::Diverging { mid: 0.0 };
+        assert_close(&norm.normalize(&[-2.0, 0.0, 1.0]), &[0.0, 0.5, 0.75]);
+
+        // the midpoint can be outside the range of the values
+        let norm = OverlayNormalization::Diverging { mid: 10.0 };
+        assert_close(&norm.normalize(&[0.0, 5.0]), &[0.0, 0.25]);
+    }
+
+    #[test]
+    fn normalize_equal_values() {
+        let values = [3.0; 4];
+
+        let norms = [
+            (OverlayNormalization::Linear, 0.0),
+            (OverlayNormalization::Log, 0.0),
+            (OverlayNormalization::Clamp { min: 3.0, max: 3.0 }, 0.0),
+            (OverlayNormalization::Quantile, 0.0),
+            (OverlayNormalization::Diverging { mid: 3.0 }, 0.5),
+        ];
+
+        for (norm, expected) in norms.iter() {
+            let normalized = norm.normalize(&values);
+            assert!(normalized.iter().all(|v| v.is_finite()));
+            assert_close(&normalized, &[*expected; 4]);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use handlegraph::{
+    handle::{Direction, Handle},
+    handlegraph::*,
+    packedgraph::PackedGraph,
+    pathhandlegraph::*,
+};
+
+use bstr::ByteSlice;
+use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::{
+    hash_node_color, value_range, OverlayData, OverlayLegend,
+    OverlayNormalization,
+};
+
+/// The color of nodes that aren't marked by a marker overlay, the
+/// same as the default color of path overlay scripts
+const UNMARKED_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+    r: 0.3,
+    g: 0.3,
+    b: 0.3,
+    a: 0.3,
+};
+
+const MARKED_COLOR: rgb::RGBA<f32> = rgb::RGBA {
+    r: 0.9,
+    g: 0.1,
+    b: 0.1,
+    a: 1.0,
+};
+
+/// The number of path names listed for each path set in the legend
+/// of the path set overlay
+const PATH_SET_LEGEND_NAMES: usize = 4;
+
+/// The number of path sets, by node count, that get their own color
+/// in the path set overlay; the nodes in the other sets are colored
+/// as unmarked
+const PATH_SET_LEGEND_ENTRIES: usize = 24;
+
+/// Overlays that are computed directly from the graph, in parallel,
+/// rather than by evaluating a Rhai script for each node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuiltinOverlay {
+    /// The number of path steps on each node
+    PathDepth,
+    /// The number of distinct paths that visit each node
+    UniquePaths,
+    NodeLength,
+    GcContent,
+    /// The sum of the left and right degrees
+    Degree,
+    /// Marks nodes with an edge to themselves
+    SelfLoops,
+    /// Marks nodes that are traversed in reverse by any path
+    Inversions,
+    /// Colors nodes by a hash of the set of paths that visit them
+    PathSet,
+}
+
+impl BuiltinOverlay {
+    pub const ALL: [BuiltinOverlay; 8] = [
+        BuiltinOverlay::PathDepth,
+        BuiltinOverlay::UniquePaths,
+        BuiltinOverlay::NodeLength,
+        BuiltinOverlay::GcContent,
+        BuiltinOverlay::Degree,
+        BuiltinOverlay::SelfLoops,
+        BuiltinOverlay::Inversions,
+        BuiltinOverlay::PathSet,
+    ];
+
+    /// The name used for the overlay, and to find it from the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuiltinOverlay::PathDepth => "Path depth",
+            BuiltinOverlay::UniquePaths => "Unique paths",
+            BuiltinOverlay::NodeLength => "Node length",
+            BuiltinOverlay::GcContent => "GC content",
+            BuiltinOverlay::Degree => "Degree",
+            BuiltinOverlay::SelfLoops => "Self-loops",
+            BuiltinOverlay::Inversions => "Inversions",
+            BuiltinOverlay::PathSet => "Path set",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            BuiltinOverlay::PathDepth => "Number of path steps on each node",
+            BuiltinOverlay::UniquePaths => {
+                "Number of distinct paths visiting each node"
+            }
+            BuiltinOverlay::NodeLength => "Sequence length of each node",
+            BuiltinOverlay::GcContent => "Percentage of G and C bases",
+            BuiltinOverlay::Degree => "Number of edges on each node",
+            BuiltinOverlay::SelfLoops => "Nodes with an edge to themselves",
+            BuiltinOverlay::Inversions => {
+                "Nodes traversed in reverse by a path"
+            }
+            BuiltinOverlay::PathSet => {
+                "Colors nodes by the set of paths visiting them"
+            }
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|overlay| overlay.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Computes the overlay for every node of `graph`, using the
+    /// threads of `rayon_pool`
+    pub fn compute(
+        &self,
+        rayon_pool: &rayon::ThreadPool,
+        graph: &PackedGraph,
+    ) -> (OverlayData, Option<OverlayLegend>) {
+        let mut handles = graph.handles().collect::<Vec<_>>();
+        handles.sort();
+
+        rayon_pool.install(|| match self {
+            BuiltinOverlay::PathDepth => {
+                value_overlay(&handles, "steps", |handle| {
+                    graph
+                        .steps_on_handle(handle)
+                        .map(|steps| steps.count())
+                        .unwrap_or(0) as f32
+                })
+            }
+            BuiltinOverlay::UniquePaths => {
+                value_overlay(&handles, "paths", |handle| {
+                    node_paths(graph, handle).len() as f32
+                })
+            }
+            BuiltinOverlay::NodeLength => {
+                value_overlay(&handles, "bp", |handle| {
+                    graph.node_len(handle) as f32
+                })
+            }
+            BuiltinOverlay::GcContent => {
+                value_overlay(&handles, "%", |handle| {
+                    let seq = graph.sequence_vec(handle);
+                    let gc = seq
+                        .iter()
+                        .filter(|&&b| matches!(b, b'G' | b'C' | b'g' | b'c'))
+                        .count();
+                    100.0 * gc as f32 / seq.len().max(1) as f32
+                })
+            }
+            BuiltinOverlay::Degree => {
+                value_overlay(&handles, "edges", |handle| {
+                    let left = graph.degree(handle, Direction::Left);
+                    let right = graph.degree(handle, Direction::Right);
+                    (left + right) as f32
+                })
+            }
+            BuiltinOverlay::SelfLoops => {
+                marker_overlay(&handles, "Self-loop", |handle| {
+                    let id = handle.id();
+                    graph
+                        .neighbors(handle, Direction::Left)
+                        .chain(graph.neighbors(handle, Direction::Right))
+                        .any(|other| other.id() == id)
+                })
+            }
+            BuiltinOverlay::Inversions => {
+                marker_overlay(&handles, "Inverted", |handle| {
+                    graph
+                        .steps_on_handle(handle)
+                        .map(|mut steps| {
+                            steps.any(|(path, step)| {
+                                graph
+                                    .path_handle_at_step(path, step)
+                                    .map(|h| h.is_reverse())
+                                    .unwrap_or(false)
+                            })
+                        })
+                        .unwrap_or(false)
+                })
+            }
+            BuiltinOverlay::PathSet => path_set_overlay(&handles, graph),
+        })
+    }
+}
+
+/// The sorted IDs of the distinct paths on a node
+fn node_paths(graph: &PackedGraph, handle: Handle) -> Vec<PathId> {
+    let mut paths = graph
+        .steps_on_handle(handle)
+        .map(|steps| steps.map(|(path, _)| path).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    paths.sort_by_key(|path| path.0);
+    paths.dedup();
+    paths
+}
+
+fn value_overlay<F>(
+    handles: &[Handle],
+    units: &str,
+    value: F,
+) -> (OverlayData, Option<OverlayLegend>)
+where
+    F: Fn(Handle) -> f32 + Send + Sync,
+{
+    let values = handles
+        .par_iter()
+        .map(|&handle| value(handle))
+        .collect::<Vec<_>>();
+
+    let (min, max) = value_range(&values);
+
+    let legend = OverlayLegend::Range {
+        min,
+        max,
+        units: units.to_string(),
+    };
+
+    let values = OverlayNormalization::Linear.normalize(&values);
+
+    (OverlayData::Value(values), Some(legend))
+}
+
+fn marker_overlay<F>(
+    handles: &[Handle],
+    marked_label: &str,
+    marked: F,
+) -> (OverlayData, Option<OverlayLegend>)
+where
+    F: Fn(Handle) -> bool + Send + Sync,
+{
+    let colors = handles
+        .par_iter()
+        .map(|&handle| {
+            if marked(handle) {
+                MARKED_COLOR
+            } else {
+                UNMARKED_COLOR
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let legend = OverlayLegend::Categories(vec![
+        (MARKED_COLOR, marked_label.to_string()),
+        (UNMARKED_COLOR, "Other".to_string()),
+    ]);
+
+    (OverlayData::RGB(colors), Some(legend))
+}
+
+fn path_set_overlay(
+    handles: &[Handle],
+    graph: &PackedGraph,
+) -> (OverlayData, Option<OverlayLegend>) {
+    let node_sets = handles
+        .par_iter()
+        .map(|&handle| {
+            let paths = node_paths(graph, handle);
+
+            let mut hasher = DefaultHasher::default();
+            for path in paths.iter() {
+                path.hash(&mut hasher);
+            }
+
+            (hasher.finish(), paths)
+        })
+        .collect::<Vec<_>>();
+
+    // the paths and node count of each distinct path set
+    let mut path_sets: FxHashMap<u64, (&[PathId], usize)> =
+        FxHashMap::default();
+
+    for (hash, paths) in node_sets.iter() {
+        path_sets.entry(*hash).or_insert((paths.as_slice(), 0)).1 += 1;
+    }
+
+    // only the sets on the most nodes get their own color and legend
+    // entry, the rest are colored and listed together
+    let mut path_sets = path_sets.into_iter().collect::<Vec<_>>();
+    path_sets
+        .sort_by(|(h0, (_, c0)), (h1, (_, c1))| c1.cmp(c0).then(h0.cmp(h1)));
+
+    let other_sets =
+        path_sets.split_off(path_sets.len().min(PATH_SET_LEGEND_ENTRIES));
+
+    let shown = path_sets
+        .iter()
+        .map(|(hash, _)| *hash)
+        .collect::<FxHashSet<_>>();
+
+    let colors = node_sets
+        .iter()
+        .map(|(hash, _)| {
+            if shown.contains(hash) {
+                path_set_color(*hash)
+            } else {
+                UNMARKED_COLOR
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut legend = path_sets
+        .iter()
+        .map(|(hash, (paths, _))| {
+            (path_set_color(*hash), path_set_label(graph, paths))
+        })
+        .collect::<Vec<_>>();
+
+    if !other_sets.is_empty() {
+        legend.push((
+            UNMARKED_COLOR,
+            format!("{} other path sets", other_sets.len()),
+        ));
+    }
+
+    (
+        OverlayData::RGB(colors),
+        Some(OverlayLegend::Categories(legend)),
+    )
+}
+
+fn path_set_color(hash: u64) -> rgb::RGBA<f32> {
+    let (r, g, b) = hash_node_color(hash);
+    rgb::RGBA::new(r, g, b, 1.0)
+}
+
+fn path_set_label(graph: &PackedGraph, paths: &[PathId]) -> String {
+    if paths.is_empty() {
+        return "No paths".to_string();
+    }
+
+    let mut names = paths
+        .iter()
+        .take(PATH_SET_LEGEND_NAMES)
+        .filter_map(|&path| {
+            let name = graph.get_path_name_vec(path)?;
+            Some(name.to_str_lossy().into_owned())
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if paths.len() > PATH_SET_LEGEND_NAMES {
+        names.push_str(&format!(
+            " and {} more",
+            paths.len() - PATH_SET_LEGEND_NAMES
+        ));
+    }
+
+    names
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use rhai::{Engine, EvalAltResult};
 
 use rayon::prelude::*;
@@ -10,11 +12,18 @@ use handlegraph::{
 
 use rustc_hash::FxHashMap;
 
-use crate::overlays::{OverlayData, OverlayKind};
+use bstr::ByteSlice;
+
+use crate::annotations::{
+    AnnotationRecord, BedColumn, BedRecord, BedRecords, ColumnKey, Gff3Column,
+    Gff3Record, Gff3Records,
+};
+use crate::overlays::{OverlayData, OverlayKind, OverlayLegend};
 use crate::{app::selection::NodeSelection, graph_query::GraphQuery};
 
 use rhai::plugin::*;
 
+pub mod headless;
 pub mod plugins;
 
 pub fn create_engine() -> Engine {
@@ -123,6 +132,116 @@ pub fn create_engine() -> Engine {
     engine
 }
 
+/// Registers the functions for accessing annotation records and
+/// their columns, which don't depend on the rest of the app state
+pub fn add_annotation_record_fns(engine: &mut Engine) {
+    engine.register_result_fn(
+        "get_record",
+        move |coll: &mut Arc<Gff3Records>, ix: i64| {
+            if let Some(record) = coll.records().get(ix as usize).cloned() {
+                Ok(record)
+            } else {
+                Err(Box::new(EvalAltResult::ErrorArrayBounds(
+                    coll.records().len(),
+                    ix as i64,
+                    rhai::Position::NONE,
+                )))
+            }
+        },
+    );
+
+    engine.register_result_fn(
+        "get_record",
+        move |coll: &mut Arc<BedRecords>, ix: i64| {
+            if let Some(record) = coll.records().get(ix as usize).cloned() {
+                Ok(record)
+            } else {
+                Err(Box::new(EvalAltResult::ErrorArrayBounds(
+                    coll.records().len(),
+                    ix as i64,
+                    rhai::Position::NONE,
+                )))
+            }
+        },
+    );
+
+    engine.register_fn("len", move |coll: &mut Arc<Gff3Records>| {
+        coll.len() as i64
+    });
+
+    engine.register_fn("len", move |coll: &mut Arc<BedRecords>| {
+        coll.len() as i64
+    });
+
+    engine.register_fn("gff3_column", |key: &str| match key {
+        "SeqId" => Gff3Column::SeqId,
+        "Source" => Gff3Column::Source,
+        "Type" => Gff3Column::Type,
+        "Start" => Gff3Column::Start,
+        "End" => Gff3Column::End,
+        "Score" => Gff3Column::Score,
+        "Strand" => Gff3Column::Strand,
+        "Frame" => Gff3Column::Frame,
+        attr => Gff3Column::Attribute(attr.as_bytes().to_owned()),
+    });
+
+    engine.register_fn("bed_column", |ix: i64| BedColumn::Index(ix as usize));
+    engine.register_result_fn(
+        "bed_column",
+        |coll: &mut Arc<BedRecords>, header: &str| {
+            if let Some(col) = coll.header_to_column(header.as_bytes()) {
+                Ok(col)
+            } else {
+                Err("Header not found in provided BED file".into())
+            }
+        },
+    );
+    engine.register_result_fn("bed_column", |key: &str| match key {
+        "Chr" => Ok(BedColumn::Chr),
+        "Start" => Ok(BedColumn::Start),
+        "End" => Ok(BedColumn::End),
+        "Name" => Ok(BedColumn::Name),
+        _ => Err("Only headers \"name\", \"start\", \"end\", and \"name\" can be referred to without a BED record context".into()),
+    });
+
+    fn get_impl<R, K>(record: &mut R, column: K) -> rhai::Dynamic
+    where
+        R: AnnotationRecord<ColumnKey = K>,
+        K: ColumnKey,
+    {
+        if column == K::seq_id() {
+            let seq_id = record.seq_id();
+            rhai::Dynamic::from(seq_id.to_str().unwrap().to_string())
+        } else if column == K::start() {
+            rhai::Dynamic::from(record.start())
+        } else if column == K::end() {
+            rhai::Dynamic::from(record.end())
+        } else {
+            let fields = record.get_all(&column);
+            let dyn_fields = fields
+                .into_iter()
+                .map(|val| rhai::Dynamic::from(format!("{}", val.as_bstr())))
+                .collect::<Vec<_>>();
+
+            rhai::Dynamic::from(dyn_fields)
+        }
+    }
+
+    engine.register_fn(
+        "get",
+        move |record: &mut Gff3Record, column: Gff3Column| {
+            get_impl(record, column)
+        },
+    );
+
+    engine.register_fn(
+        "get",
+        move |record: &mut BedRecord, column: BedColumn| {
+            get_impl(record, column)
+        },
+    );
+}
+
 #[derive(Debug, Clone)]
 pub enum ScriptTarget {
     Nodes,
@@ -145,8 +264,14 @@ pub fn check_overlay_kind(data: rhai::Dynamic) -> Option<OverlayKind> {
     }
 }
 
+/// The data produced by an overlay script, and its legend, if any
+pub type ScriptOverlay = (OverlayData, Option<OverlayLegend>);
+
 // pub fn check_overlay_kind(data: &Vec<rhai::Dynamic>)
-pub fn cast_overlay_data(data: Vec<rhai::Dynamic>) -> Option<OverlayData> {
+/// Casts the values produced by an overlay script to overlay data.
+/// Values are normalized to 0..1, and their original range returned
+/// as the overlay legend
+pub fn cast_overlay_data(data: Vec<rhai::Dynamic>) -> Option<ScriptOverlay> {
     let first = data.first()?.clone();
 
     if let Some(_rgb) = first.clone().try_cast::<rgb::RGBA<f32>>() {
@@ -156,7 +281,7 @@ pub fn cast_overlay_data(data: Vec<rhai::Dynamic>) -> Option<OverlayData> {
             .map(|v| v.try_cast::<rgb::RGBA<f32>>().unwrap())
             .collect::<Vec<_>>();
 
-        return Some(OverlayData::RGB(data));
+        return Some((OverlayData::RGB(data), None));
     } else if let Some(_val) = first.try_cast::<f32>() {
         let mut min = std::f32::MAX;
         let mut max = std::f32::MIN;
@@ -174,13 +299,20 @@ pub fn cast_overlay_data(data: Vec<rhai::Dynamic>) -> Option<OverlayData> {
 
         let range = max - min;
 
+        // if all values are equal there's nothing to scale by
         for val in data.iter_mut() {
-            *val = (*val - min) / range;
+            *val = if range > 0.0 {
+                (*val - min) / range
+            } else {
+                0.0
+            };
         }
 
         log::debug!("Overlay values, min: {}, max: {}", min, max);
 
-        return Some(OverlayData::Value(data));
+        let legend = OverlayLegend::range(min, max);
+
+        return Some((OverlayData::Value(data), Some(legend)));
     }
 
     None
@@ -193,7 +325,7 @@ pub fn overlay_colors_tgt_ast(
     engine: &rhai::Engine,
     scope: rhai::Scope<'_>,
     node_color_ast: rhai::AST,
-) -> std::result::Result<OverlayData, Box<EvalAltResult>> {
+) -> std::result::Result<ScriptOverlay, Box<EvalAltResult>> {
     match config.target.clone() {
         ScriptTarget::Nodes => {
             let mut node_ids =
@@ -265,7 +397,7 @@ pub fn overlay_colors_tgt_ast(
             let (nodes, values): (Vec<_>, Vec<_>) =
                 node_value_map.into_iter().unzip();
 
-            let data = cast_overlay_data(values)
+            let (data, legend) = cast_overlay_data(values)
                 .ok_or("Couldn't process overlay data")?;
 
             let mut node_ids =
@@ -291,7 +423,7 @@ pub fn overlay_colors_tgt_ast(
                         })
                         .collect();
 
-                    Ok(OverlayData::RGB(data))
+                    Ok((OverlayData::RGB(data), legend))
                 }
                 OverlayData::Value(val) => {
                     let node_val_map: FxHashMap<NodeId, f32> = nodes
@@ -309,7 +441,7 @@ pub fn overlay_colors_tgt_ast(
                         })
                         .collect();
 
-                    Ok(OverlayData::Value(data))
+                    Ok((OverlayData::Value(data), legend))
                 }
             }
         }
@@ -321,7 +453,7 @@ pub fn overlay_colors_tgt(
     config: &ScriptConfig,
     graph: &GraphQuery,
     script: &str,
-) -> std::result::Result<OverlayData, Box<EvalAltResult>> {
+) -> std::result::Result<ScriptOverlay, Box<EvalAltResult>> {
     use rhai::Scope;
 
     let mut scope = Scope::new();
@@ -408,7 +540,7 @@ pub fn overlay_colors_tgt(
             let (nodes, values): (Vec<_>, Vec<_>) =
                 node_value_map.into_iter().unzip();
 
-            let data = cast_overlay_data(values)
+            let (data, legend) = cast_overlay_data(values)
                 .ok_or("Couldn't process overlay data")?;
 
             let mut node_ids =
@@ -434,7 +566,7 @@ pub fn overlay_colors_tgt(
                         })
                         .collect();
 
-                    Ok(OverlayData::RGB(data))
+                    Ok((OverlayData::RGB(data), legend))
                 }
                 OverlayData::Value(val) => {
                     let node_val_map: FxHashMap<NodeId, f32> = nodes
@@ -452,7 +584,7 @@ pub fn overlay_colors_tgt(
                         })
                         .collect();
 
-                    Ok(OverlayData::Value(data))
+                    Ok((OverlayData::Value(data), legend))
                 }
             }
         }
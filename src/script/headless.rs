@@ -0,0 +1,456 @@
+//! Running overlay and annotation scripts without a window or GPU,
+//! writing the results to files instead of sending them to the app.
+//!
+//! Scripts have access to the same graph API as in the console (see
+//! `script::plugins`), plus the functions registered in
+//! `HeadlessRunner::create_engine`.
+
+use std::{
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use handlegraph::{handle::NodeId, handlegraph::*, pathhandlegraph::*};
+
+use anyhow::Result;
+use bstr::ByteSlice;
+use parking_lot::Mutex;
+use rhai::EvalAltResult;
+
+use crate::{
+    annotations::{
+        AnnotationCollection, AnnotationFileType, AnnotationLabelSet,
+        Annotations, BedColumn, BedRecords, ColumnKey, Gff3Column, Gff3Records,
+    },
+    app::selection::NodeSelection,
+    geometry::Point,
+    graph_query::GraphQuery,
+    overlays::{save_overlay_file, OverlayData, OVERLAY_FILE_EXT},
+    universe::{FlatLayout, GraphLayout, Universe},
+};
+
+use super::{overlay_colors_tgt_ast, ScriptConfig, ScriptTarget};
+
+type EvalResult<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+pub struct HeadlessRunner {
+    graph: Arc<GraphQuery>,
+    universe: Option<Arc<Universe<FlatLayout>>>,
+    rayon_pool: Arc<rayon::ThreadPool>,
+
+    out_dir: PathBuf,
+
+    annotations: Arc<Mutex<Annotations>>,
+
+    // (overlay name, node color function name); overlays are
+    // computed after the script has been evaluated, as the
+    // functions they use are defined in the script itself
+    overlay_requests: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl HeadlessRunner {
+    pub fn new(
+        graph: Arc<GraphQuery>,
+        universe: Option<Universe<FlatLayout>>,
+        rayon_pool: Arc<rayon::ThreadPool>,
+        out_dir: PathBuf,
+    ) -> Self {
+        Self {
+            graph,
+            universe: universe.map(Arc::new),
+            rayon_pool,
+
+            out_dir,
+
+            annotations: Arc::new(Mutex::new(Annotations::default())),
+
+            overlay_requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn out_dir(&self) -> &Path {
+        &self.out_dir
+    }
+
+    fn output_path(out_dir: &Path, name: &str, ext: &str) -> PathBuf {
+        out_dir.join(format!("{}.{}", name, ext))
+    }
+
+    pub fn create_scope(&self) -> rhai::Scope<'static> {
+        let mut scope = rhai::Scope::new();
+        scope
+            .push("graph", self.graph.graph.clone())
+            .push("path_pos", self.graph.path_positions.clone());
+        scope
+    }
+
+    pub fn create_engine(&self) -> rhai::Engine {
+        let mut engine = super::create_engine();
+
+        engine.register_type::<Point>();
+        engine.register_fn("Point", |x: f32, y: f32| Point::new(x, y));
+        engine.register_get_set(
+            "x",
+            |p: &mut Point| p.x,
+            |p: &mut Point, x| p.x = x,
+        );
+        engine.register_get_set(
+            "y",
+            |p: &mut Point| p.y,
+            |p: &mut Point, y| p.y = y,
+        );
+
+        engine.register_fn("log", |v: rhai::Dynamic| {
+            if let Some(string) = v.clone().try_cast::<String>() {
+                println!("{}", string);
+            } else {
+                println!("{:?}", v);
+            }
+        });
+
+        let graph = self.graph.clone();
+        engine.register_fn("get_graph", move || graph.graph.clone());
+
+        let graph = self.graph.clone();
+        engine.register_fn("get_path_positions", move || {
+            graph.path_positions.clone()
+        });
+
+        self.add_layout_fns(&mut engine);
+        self.add_selection_fns(&mut engine);
+        self.add_annotation_fns(&mut engine);
+
+        // same signature as in the console, but the overlay is
+        // computed and written once the script has finished
+        let overlay_requests = self.overlay_requests.clone();
+        engine.register_result_fn(
+            "create_overlay_from_fn",
+            move |name: &str, fn_name: rhai::Dynamic| {
+                if let Some(fn_name) = fn_name.try_cast::<String>() {
+                    overlay_requests.lock().push((name.to_string(), fn_name));
+                    Ok(())
+                } else {
+                    Err("The function name must be a string".into())
+                }
+            },
+        );
+
+        engine
+    }
+
+    fn add_layout_fns(&self, engine: &mut rhai::Engine) {
+        let universe = if let Some(universe) = self.universe.clone() {
+            universe
+        } else {
+            engine.register_result_fn(
+                "node_center",
+                |_: NodeId| -> EvalResult<Point> {
+                    Err("No layout was provided".into())
+                },
+            );
+            return;
+        };
+
+        let universe_ = universe.clone();
+        engine.register_result_fn(
+            "node_center",
+            move |node: NodeId| -> EvalResult<Point> {
+                let nodes = universe_.layout().nodes();
+                let ix = (node.0 as usize).checked_sub(1);
+                ix.and_then(|ix| nodes.get(ix))
+                    .map(|n| n.center())
+                    .ok_or("Node not found in layout".into())
+            },
+        );
+
+        engine.register_fn("selection_center", move |sel: NodeSelection| {
+            let rect = sel.bounding_box(universe.layout().nodes());
+            rect.center()
+        });
+    }
+
+    fn add_selection_fns(&self, engine: &mut rhai::Engine) {
+        let graph = self.graph.graph.clone();
+        engine.register_result_fn("path_selection", move |path_name: &str| {
+            if let Some(path) = graph.get_path_id(path_name.as_bytes()) {
+                let mut selection = NodeSelection::default();
+                if let Some(steps) = graph.path_steps(path) {
+                    for step in steps {
+                        let id = step.handle().id();
+                        selection.add_one(false, id);
+                    }
+                }
+                Ok(selection)
+            } else {
+                Err("The provided path does not exist".into())
+            }
+        });
+
+        let out_dir = self.out_dir.clone();
+        engine.register_result_fn(
+            "save_selection",
+            move |name: &str, selection: NodeSelection| {
+                let path = Self::output_path(&out_dir, name, "selection.txt");
+                write_selection(&path, &selection)
+                    .map_err(|err| format!("{}", err).into())
+            },
+        );
+    }
+
+    fn add_annotation_fns(&self, engine: &mut rhai::Engine) {
+        super::add_annotation_record_fns(engine);
+
+        let annotations = self.annotations.clone();
+        engine.register_result_fn(
+            "load_collection",
+            move |path: &str| -> EvalResult<String> {
+                let file = PathBuf::from(path);
+
+                let ext = file
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .ok_or("Missing file extension")?;
+
+                if ext == "gff3" {
+                    let records = Gff3Records::parse_gff3_file(&file)
+                        .map_err(|_| "Error parsing GFF3 file")?;
+                    let name = records.file_name().to_string();
+                    annotations.lock().insert_gff3(&name, records);
+                    Ok(name)
+                } else if ext == "bed" {
+                    let records = BedRecords::parse_bed_file(&file)
+                        .map_err(|_| "Error parsing BED file")?;
+                    let name = records.file_name().to_string();
+                    annotations.lock().insert_bed(&name, records);
+                    Ok(name)
+                } else {
+                    Err("Invalid file extension".into())
+                }
+            },
+        );
+
+        let annotations = self.annotations.clone();
+        engine.register_fn("list_collections", move || {
+            let annotations = annotations.lock();
+            annotations
+                .annot_names()
+                .iter()
+                .map(|(name, _)| rhai::Dynamic::from(name.to_string()))
+                .collect::<Vec<_>>()
+        });
+
+        let annotations = self.annotations.clone();
+        engine.register_result_fn(
+            "get_collection",
+            move |name: &str| -> EvalResult<rhai::Dynamic> {
+                let annotations = annotations.lock();
+                let file_type = annotations
+                    .annot_names()
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, t)| *t)
+                    .ok_or("Annotation collection not found")?;
+
+                let result = match file_type {
+                    AnnotationFileType::Gff3 => annotations
+                        .get_gff3(name)
+                        .map(|r| rhai::Dynamic::from(r.clone())),
+                    AnnotationFileType::Bed => annotations
+                        .get_bed(name)
+                        .map(|r| rhai::Dynamic::from(r.clone())),
+                };
+
+                result.ok_or("Error retrieving data".into())
+            },
+        );
+
+        fn create_label_set_impl<C, K>(
+            graph: &GraphQuery,
+            out_dir: &Path,
+            annots: &mut Arc<C>,
+            record_indices: Vec<rhai::Dynamic>,
+            path_id: PathId,
+            column: K,
+            label_set_name: &str,
+        ) -> EvalResult<()>
+        where
+            C: AnnotationCollection<ColumnKey = K> + Send + Sync + 'static,
+            K: ColumnKey,
+        {
+            let record_indices = record_indices
+                .into_iter()
+                .filter_map(|i| Some(i.as_int().ok()? as usize))
+                .collect::<Vec<_>>();
+
+            let path_name = graph
+                .graph
+                .get_path_name_vec(path_id)
+                .ok_or("The provided path does not exist")?;
+            let path_name = path_name.to_str().unwrap();
+
+            let label_set = crate::annotations::calculate_annotation_set(
+                graph,
+                annots.as_ref(),
+                &record_indices,
+                path_id,
+                path_name,
+                &column,
+                label_set_name,
+            )
+            .ok_or("Error calculating the label set")?;
+
+            let path = HeadlessRunner::output_path(
+                out_dir,
+                label_set_name,
+                "labels.tsv",
+            );
+            write_label_set(&path, &label_set)
+                .map_err(|err| format!("{}", err).into())
+        }
+
+        let graph = self.graph.clone();
+        let out_dir = self.out_dir.clone();
+        engine.register_result_fn(
+            "create_label_set",
+            move |annots: &mut Arc<Gff3Records>,
+                  record_indices: Vec<rhai::Dynamic>,
+                  path_id: PathId,
+                  column: Gff3Column,
+                  label_set_name: &str| {
+                create_label_set_impl(
+                    &graph,
+                    &out_dir,
+                    annots,
+                    record_indices,
+                    path_id,
+                    column,
+                    label_set_name,
+                )
+            },
+        );
+
+        let graph = self.graph.clone();
+        let out_dir = self.out_dir.clone();
+        engine.register_result_fn(
+            "create_label_set",
+            move |annots: &mut Arc<BedRecords>,
+                  record_indices: Vec<rhai::Dynamic>,
+                  path_id: PathId,
+                  column: BedColumn,
+                  label_set_name: &str| {
+                create_label_set_impl(
+                    &graph,
+                    &out_dir,
+                    annots,
+                    record_indices,
+                    path_id,
+                    column,
+                    label_set_name,
+                )
+            },
+        );
+    }
+
+    /// Evaluates the script at `path`, then computes and writes any
+    /// overlays it requested with `create_overlay_from_fn`
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let engine = self.create_engine();
+        let mut scope = self.create_scope();
+
+        let script = std::fs::read_to_string(path.as_ref())?;
+
+        let ast = engine
+            .compile(&script)
+            .map_err(|err| anyhow::anyhow!("Parse error: {}", err))?;
+
+        engine
+            .eval_ast_with_scope::<rhai::Dynamic>(&mut scope, &ast)
+            .map_err(|err| anyhow::anyhow!("Script error: {}", err))?;
+
+        let requests = std::mem::take(&mut *self.overlay_requests.lock());
+
+        let functions = ast.clone_functions_only();
+
+        let config = ScriptConfig {
+            default_color: rgb::RGBA::new(0.3, 0.3, 0.3, 0.3),
+            target: ScriptTarget::Nodes,
+        };
+
+        for (name, fn_name) in requests {
+            log::info!("computing overlay {} using {}", name, fn_name);
+
+            let wrapper = format!("\nfn node_color(i) {{\n{}(i)\n}}", fn_name);
+            let node_color_ast = engine
+                .compile(&wrapper)
+                .map_err(|err| anyhow::anyhow!("Parse error: {}", err))?;
+            let node_color_ast = functions.merge(&node_color_ast);
+
+            let (data, legend) = overlay_colors_tgt_ast(
+                &self.rayon_pool,
+                &config,
+                &self.graph,
+                &engine,
+                self.create_scope(),
+                node_color_ast,
+            )
+            .map_err(|err| anyhow::anyhow!("Overlay error: {}", err))?;
+
+            let path = Self::output_path(&self.out_dir, &name, "overlay.tsv");
+            write_overlay(&path, &data)?;
+            log::info!("wrote overlay {} to {}", name, path.display());
+
+            // also in the binary format, so it can be loaded in the GUI
+            let path =
+                Self::output_path(&self.out_dir, &name, OVERLAY_FILE_EXT);
+            save_overlay_file(
+                &path,
+                self.graph.graph(),
+                &name,
+                &data,
+                legend.as_ref(),
+            )?;
+            log::info!("wrote overlay {} to {}", name, path.display());
+        }
+
+        Ok(())
+    }
+}
+
+fn write_overlay(path: &Path, data: &OverlayData) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    data.write_tsv(BufWriter::new(file))
+}
+
+fn write_selection(
+    path: &Path,
+    selection: &NodeSelection,
+) -> std::io::Result<()> {
+    let mut nodes = selection.nodes.iter().copied().collect::<Vec<_>>();
+    nodes.sort();
+
+    let file = std::fs::File::create(path)?;
+    let mut out = BufWriter::new(file);
+    for node in nodes {
+        writeln!(out, "{}", node.0)?;
+    }
+    Ok(())
+}
+
+fn write_label_set(
+    path: &Path,
+    label_set: &AnnotationLabelSet,
+) -> std::io::Result<()> {
+    let strings = label_set.label_strings();
+    let mut labels = label_set.labels().iter().collect::<Vec<_>>();
+    labels.sort_by_key(|(node, _)| **node);
+
+    let file = std::fs::File::create(path)?;
+    let mut out = BufWriter::new(file);
+    for (node, indices) in labels {
+        for &ix in indices {
+            writeln!(out, "{}\t{}", node.0, strings[ix])?;
+        }
+    }
+    Ok(())
+}
@@ -211,6 +211,18 @@ pub mod paths_plugin {
         path_len(graph, PathId(path as u64))
     }
 
+    // unlike `path_len`, these return an INT, which scripts can do
+    // arithmetic on
+    #[rhai_fn(pure)]
+    pub fn path_step_count(graph: &mut Arc<PackedGraph>, path: PathId) -> i64 {
+        graph.path_len(path).unwrap_or(0) as i64
+    }
+
+    #[rhai_fn(pure)]
+    pub fn path_base_count(graph: &mut Arc<PackedGraph>, path: PathId) -> i64 {
+        graph.path_bases_len(path).unwrap_or(0) as i64
+    }
+
     #[rhai_fn(pure, return_raw)]
     pub fn path_first_step(
         graph: &mut Arc<PackedGraph>,
@@ -296,6 +308,12 @@ pub mod graph_plugin {
         graph.path_count()
     }
 
+    // the IDs of all paths in the graph, for iterating over them
+    #[rhai_fn(pure)]
+    pub fn path_ids(graph: &mut Arc<PackedGraph>) -> Vec<rhai::Dynamic> {
+        graph.path_ids().map(rhai::Dynamic::from).collect()
+    }
+
     #[rhai_fn(pure)]
     pub fn total_length(graph: &mut Arc<PackedGraph>) -> usize {
         graph.total_length()
@@ -0,0 +1,1035 @@
+//! Session files store the state of a workspace -- the graph and
+//! layout, view, settings, annotations, label sets, overlays,
+//! selection, and bookmarks -- so that it can be restored later.
+//!
+//! A session is a tab-separated text file, with one entry per line;
+//! the first field of each line is the kind of entry. Overlays are
+//! stored as overlay files (see `crate::overlays`) in a directory
+//! next to the session file.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use bstr::ByteSlice;
+use crossbeam::channel::Sender;
+
+use handlegraph::{
+    handle::NodeId, packedgraph::PackedGraph, pathhandlegraph::*,
+};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::annotations::{
+    AnnotationCollection, AnnotationColumn, AnnotationFileType,
+    AnnotationLabelSet, BedRecords, Gff3Records,
+};
+use crate::app::{
+    mainview::MainView, App, AppMsg, Bookmark, OverlayCreatorMsg, Select,
+};
+use crate::geometry::Point;
+use crate::gui::{Gui, GuiMsg};
+use crate::overlays::{OverlayData, OverlayLegend, OVERLAY_FILE_EXT};
+use crate::reactor::Reactor;
+use crate::view::View;
+
+pub const SESSION_FILE_EXT: &str = "gfs";
+
+const SESSION_HEADER: &str = "gfaestus-session";
+const SESSION_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct SessionFilter {
+    pub file_type: AnnotationFileType,
+    pub file_name: String,
+    pub column: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionLabelSet {
+    pub name: String,
+    pub annotation_name: String,
+    pub column: String,
+    pub path_name: String,
+    pub visible: bool,
+    pub labels: Vec<(NodeId, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionOverlay {
+    pub name: String,
+    /// Path to the overlay file; relative paths are relative to the
+    /// directory containing the session file
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Session {
+    pub gfa: PathBuf,
+    pub layout: PathBuf,
+
+    pub view: Option<View>,
+    pub dark_mode: bool,
+
+    /// Key-value pairs as produced by `AppSettings::to_key_values`
+    pub settings: Vec<(String, String)>,
+
+    pub annotation_files: Vec<PathBuf>,
+    /// Annotation file names and the names of their reference paths
+    pub ref_paths: Vec<(String, String)>,
+    pub filters: Vec<SessionFilter>,
+    pub label_sets: Vec<SessionLabelSet>,
+
+    pub overlays: Vec<SessionOverlay>,
+    pub current_overlay: Option<String>,
+
+    pub selection: Vec<NodeId>,
+
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// Tabs and newlines would break the line format
+fn clean(field: &str) -> String {
+    field.replace(|c| c == '\t' || c == '\n' || c == '\r', " ")
+}
+
+/// Bookmark notes can span several lines, so they're escaped rather
+/// than cleaned
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+fn unescape(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut chars = field.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => result.push('\t'),
+                Some('n') => result.push('\n'),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn file_type_str(file_type: AnnotationFileType) -> &'static str {
+    match file_type {
+        AnnotationFileType::Gff3 => "gff3",
+        AnnotationFileType::Bed => "bed",
+    }
+}
+
+fn parse_file_type(file_type: &str) -> Option<AnnotationFileType> {
+    match file_type {
+        "gff3" => Some(AnnotationFileType::Gff3),
+        "bed" => Some(AnnotationFileType::Bed),
+        _ => None,
+    }
+}
+
+impl Session {
+    pub fn new<P: AsRef<Path>, Q: AsRef<Path>>(gfa: P, layout: Q) -> Self {
+        let canonical =
+            |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_owned());
+
+        Self {
+            gfa: canonical(gfa.as_ref()),
+            layout: canonical(layout.as_ref()),
+            ..Self::default()
+        }
+    }
+
+    /// Directory the overlay files for the session at `path` are
+    /// stored in
+    fn overlay_dir(path: &Path) -> PathBuf {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("session");
+        path.with_file_name(format!("{}.overlays", stem))
+    }
+
+    /// Write each overlay to the overlay directory of the session at
+    /// `path`, adding them to the session
+    pub fn write_overlays(
+        &mut self,
+        path: &Path,
+        graph: &PackedGraph,
+        overlays: &[(String, Arc<OverlayData>, Option<OverlayLegend>)],
+    ) -> Result<()> {
+        if overlays.is_empty() {
+            return Ok(());
+        }
+
+        let dir = Self::overlay_dir(path);
+        std::fs::create_dir_all(&dir)?;
+
+        let dir_name = PathBuf::from(dir.file_name().unwrap());
+
+        for (ix, (name, data, legend)) in overlays.iter().enumerate() {
+            let file_name = format!("{}.{}", ix, OVERLAY_FILE_EXT);
+            crate::overlays::save_overlay_file(
+                dir.join(&file_name),
+                graph,
+                name,
+                data,
+                legend.as_ref(),
+            )?;
+
+            self.overlays.push(SessionOverlay {
+                name: name.to_string(),
+                file: dir_name.join(file_name),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Loads the session's overlay files, as messages that replace
+    /// any existing overlays with the same names. Overlays that fail
+    /// to load are logged and skipped
+    pub fn load_overlays(&self, graph: &PackedGraph) -> Vec<OverlayCreatorMsg> {
+        let mut msgs = Vec::with_capacity(self.overlays.len());
+
+        for overlay in self.overlays.iter() {
+            match crate::overlays::load_overlay_file(&overlay.file, graph) {
+                Ok((_, data, legend)) => {
+                    msgs.push(OverlayCreatorMsg::ReplaceOverlay {
+                        name: overlay.name.clone(),
+                        data,
+                        legend,
+                    });
+                }
+                Err(err) => {
+                    error!(
+                        "Error loading overlay file {:?}: {:?}",
+                        overlay.file, err
+                    );
+                }
+            }
+        }
+
+        msgs
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut out = BufWriter::new(file);
+
+        self.write_to(&mut out)?;
+        out.flush()?;
+
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, mut out: W) -> Result<()> {
+        writeln!(out, "{}\t{}", SESSION_HEADER, SESSION_VERSION)?;
+
+        writeln!(out, "gfa\t{}", self.gfa.display())?;
+        writeln!(out, "layout\t{}", self.layout.display())?;
+
+        if let Some(view) = self.view {
+            writeln!(
+                out,
+                "view\t{}\t{}\t{}",
+                view.center.x, view.center.y, view.scale
+            )?;
+        }
+
+        writeln!(out, "dark_mode\t{}", self.dark_mode)?;
+
+        for (key, value) in self.settings.iter() {
+            writeln!(out, "setting\t{}\t{}", key, clean(value))?;
+        }
+
+        for annot_path in self.annotation_files.iter() {
+            writeln!(out, "annotation\t{}", annot_path.display())?;
+        }
+
+        for (annot, path_name) in self.ref_paths.iter() {
+            writeln!(out, "ref_path\t{}\t{}", clean(annot), clean(path_name))?;
+        }
+
+        for filter in self.filters.iter() {
+            let fields = filter
+                .fields
+                .iter()
+                .map(|f| clean(f))
+                .collect::<Vec<_>>()
+                .join("\t");
+
+            writeln!(
+                out,
+                "filter\t{}\t{}\t{}\t{}",
+                file_type_str(filter.file_type),
+                clean(&filter.file_name),
+                clean(&filter.column),
+                fields
+            )?;
+        }
+
+        for label_set in self.label_sets.iter() {
+            let name = clean(&label_set.name);
+
+            writeln!(
+                out,
+                "label_set\t{}\t{}\t{}\t{}\t{}",
+                name,
+                clean(&label_set.annotation_name),
+                clean(&label_set.column),
+                clean(&label_set.path_name),
+                label_set.visible
+            )?;
+
+            for (node, text) in label_set.labels.iter() {
+                writeln!(out, "label\t{}\t{}\t{}", name, node.0, clean(text))?;
+            }
+        }
+
+        for overlay in self.overlays.iter() {
+            writeln!(
+                out,
+                "overlay\t{}\t{}",
+                clean(&overlay.name),
+                overlay.file.display()
+            )?;
+        }
+
+        if let Some(current) = &self.current_overlay {
+            writeln!(out, "current_overlay\t{}", clean(current))?;
+        }
+
+        if !self.selection.is_empty() {
+            let nodes = self
+                .selection
+                .iter()
+                .map(|n| n.0.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, "selection\t{}", nodes)?;
+        }
+
+        for bookmark in self.bookmarks.iter() {
+            let view = bookmark.view;
+            writeln!(
+                out,
+                "bookmark\t{}\t{}\t{}\t{}\t{}",
+                clean(&bookmark.name),
+                view.center.x,
+                view.center.y,
+                view.scale,
+                escape(&bookmark.note)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let reader = BufReader::new(file);
+
+        let session_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        Self::read_from(reader, session_dir)
+            .with_context(|| format!("Error reading session {:?}", path))
+    }
+
+    /// Parses a session, with relative overlay paths resolved
+    /// against `session_dir`
+    fn read_from<R: BufRead>(reader: R, session_dir: &Path) -> Result<Self> {
+        let mut lines = reader.lines();
+
+        match lines.next() {
+            Some(header) => {
+                let header = header?;
+                let mut fields = header.split('\t');
+                if fields.next() != Some(SESSION_HEADER) {
+                    bail!("Not a gfaestus session file");
+                }
+                let version = fields.next().and_then(|v| v.parse::<u32>().ok());
+                if version != Some(SESSION_VERSION) {
+                    bail!("Unsupported session file version: {:?}", version);
+                }
+            }
+            None => bail!("Empty session file"),
+        }
+
+        let mut session = Session::default();
+
+        let mut label_set_ixs: HashMap<String, usize> = HashMap::new();
+
+        for (line_ix, line) in lines.enumerate() {
+            let line = line?;
+            // the header is line 1
+            let line_no = line_ix + 2;
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields = line.split('\t').collect::<Vec<_>>();
+
+            macro_rules! field {
+                ($ix:expr) => {
+                    match fields.get($ix) {
+                        Some(field) => *field,
+                        None => bail!(
+                            "Missing field {} on line {} of session file",
+                            $ix,
+                            line_no
+                        ),
+                    }
+                };
+            }
+
+            macro_rules! parse {
+                ($ix:expr, $t:ty) => {
+                    match field!($ix).parse::<$t>() {
+                        Ok(v) => v,
+                        Err(_) => bail!(
+                            "Error parsing field {} on line {} of session file",
+                            $ix,
+                            line_no
+                        ),
+                    }
+                };
+            }
+
+            match fields[0] {
+                "gfa" => session.gfa = PathBuf::from(field!(1)),
+                "layout" => session.layout = PathBuf::from(field!(1)),
+                "view" => {
+                    let center = Point::new(parse!(1, f32), parse!(2, f32));
+                    let scale = parse!(3, f32);
+                    session.view = Some(View { center, scale });
+                }
+                "dark_mode" => session.dark_mode = parse!(1, bool),
+                "setting" => session
+                    .settings
+                    .push((field!(1).to_string(), field!(2).to_string())),
+                "annotation" => {
+                    session.annotation_files.push(PathBuf::from(field!(1)))
+                }
+                "ref_path" => session
+                    .ref_paths
+                    .push((field!(1).to_string(), field!(2).to_string())),
+                "filter" => {
+                    let file_type = match parse_file_type(field!(1)) {
+                        Some(t) => t,
+                        None => bail!(
+                            "Unknown annotation type on line {} of session file",
+                            line_no
+                        ),
+                    };
+
+                    session.filters.push(SessionFilter {
+                        file_type,
+                        file_name: field!(2).to_string(),
+                        column: field!(3).to_string(),
+                        fields: fields[4..]
+                            .iter()
+                            .map(|f| f.to_string())
+                            .collect(),
+                    });
+                }
+                "label_set" => {
+                    let name = field!(1).to_string();
+                    label_set_ixs
+                        .insert(name.clone(), session.label_sets.len());
+
+                    session.label_sets.push(SessionLabelSet {
+                        name,
+                        annotation_name: field!(2).to_string(),
+                        column: field!(3).to_string(),
+                        path_name: field!(4).to_string(),
+                        visible: parse!(5, bool),
+                        labels: Vec::new(),
+                    });
+                }
+                "label" => {
+                    let node = NodeId::from(parse!(2, u64));
+                    let text = field!(3).to_string();
+
+                    match label_set_ixs.get(field!(1)) {
+                        Some(&ix) => {
+                            session.label_sets[ix].labels.push((node, text))
+                        }
+                        None => bail!(
+                            "Label before its label set on line {} of session file",
+                            line_no
+                        ),
+                    }
+                }
+                "overlay" => {
+                    let file = PathBuf::from(field!(2));
+                    let file = if file.is_relative() {
+                        session_dir.join(file)
+                    } else {
+                        file
+                    };
+
+                    session.overlays.push(SessionOverlay {
+                        name: field!(1).to_string(),
+                        file,
+                    });
+                }
+                "current_overlay" => {
+                    session.current_overlay = Some(field!(1).to_string())
+                }
+                "selection" => {
+                    for node in field!(1).split_whitespace() {
+                        match node.parse::<u64>() {
+                            Ok(id) => session.selection.push(NodeId::from(id)),
+                            Err(_) => bail!(
+                                "Error parsing selection on line {} of session file",
+                                line_no
+                            ),
+                        }
+                    }
+                }
+                "bookmark" => {
+                    let center = Point::new(parse!(2, f32), parse!(3, f32));
+                    let scale = parse!(4, f32);
+                    let note = fields.get(5).map(|n| unescape(n));
+
+                    session.bookmarks.push(Bookmark::new(
+                        field!(1),
+                        note.as_deref().unwrap_or_default(),
+                        View { center, scale },
+                    ));
+                }
+                other => {
+                    warn!(
+                        "Ignoring unknown session entry `{}` on line {}",
+                        other, line_no
+                    );
+                }
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Load the session's annotation files, and restore the
+    /// reference paths and label sets that use them.
+    ///
+    /// The records, reference paths, and label sets are sent to the
+    /// App in that order, so this can be run on a separate thread.
+    /// Files that can't be loaded are logged and skipped, along with
+    /// the label sets that use them.
+    pub fn load_annotations(
+        &self,
+        graph: &PackedGraph,
+        app_tx: &Sender<AppMsg>,
+    ) {
+        let mut columns: HashMap<String, Vec<AnnotationColumn>> =
+            HashMap::new();
+
+        for annot_path in self.annotation_files.iter() {
+            let ext = annot_path.extension().and_then(|ext| ext.to_str());
+
+            match ext {
+                Some("gff3") => {
+                    let records = match Gff3Records::parse_gff3_file(annot_path)
+                    {
+                        Ok(records) => records,
+                        Err(err) => {
+                            error!(
+                                "Error loading annotation file {:?}: {:?}",
+                                annot_path, err
+                            );
+                            continue;
+                        }
+                    };
+                    let cols = records
+                        .all_columns()
+                        .into_iter()
+                        .map(AnnotationColumn::Gff3)
+                        .collect();
+                    columns.insert(records.file_name().to_string(), cols);
+                    app_tx.send(AppMsg::AddGff3Records(records)).unwrap();
+                }
+                Some("bed") => {
+                    let records = match BedRecords::parse_bed_file(annot_path) {
+                        Ok(records) => records,
+                        Err(err) => {
+                            error!(
+                                "Error loading annotation file {:?}: {:?}",
+                                annot_path, err
+                            );
+                            continue;
+                        }
+                    };
+                    let cols = records
+                        .all_columns()
+                        .into_iter()
+                        .map(AnnotationColumn::Bed)
+                        .collect();
+                    columns.insert(records.file_name().to_string(), cols);
+                    app_tx.send(AppMsg::AddBedRecords(records)).unwrap();
+                }
+                _ => {
+                    warn!("Unknown annotation file type: {:?}", annot_path);
+                }
+            }
+        }
+
+        for (annot, path_name) in self.ref_paths.iter() {
+            if let Some(path) = graph.get_path_id(path_name.as_bytes()) {
+                app_tx
+                    .send(AppMsg::SetData {
+                        key: "annotation_ref_path".to_string(),
+                        index: annot.to_string(),
+                        value: rhai::Dynamic::from(path),
+                    })
+                    .unwrap();
+            } else {
+                warn!("Reference path {} not found in graph", path_name);
+            }
+        }
+
+        for label_set in self.label_sets.iter() {
+            let column = columns
+                .get(&label_set.annotation_name)
+                .and_then(|cols| {
+                    cols.iter().find(|c| {
+                        let name = match c {
+                            AnnotationColumn::Gff3(c) => c.to_string(),
+                            AnnotationColumn::Bed(c) => c.to_string(),
+                        };
+                        name == label_set.column
+                    })
+                })
+                .cloned();
+
+            let path = graph.get_path_id(label_set.path_name.as_bytes());
+
+            if let (Some(column), Some(path)) = (column, path) {
+                let new_label_set = AnnotationLabelSet::from_labels(
+                    &label_set.annotation_name,
+                    &label_set.name,
+                    column,
+                    path,
+                    &label_set.path_name,
+                    &label_set.labels,
+                );
+                new_label_set.set_visibility(label_set.visible);
+
+                app_tx
+                    .send(AppMsg::NewNodeLabels {
+                        name: label_set.name.clone(),
+                        label_set: new_label_set,
+                    })
+                    .unwrap();
+            } else {
+                warn!(
+                    "Could not restore label set {}: column or path not found",
+                    label_set.name
+                );
+            }
+        }
+    }
+
+    /// Add the label sets from the annotations to the session
+    pub fn add_label_sets<'a>(
+        &mut self,
+        label_sets: impl Iterator<Item = &'a Arc<AnnotationLabelSet>>,
+    ) {
+        for label_set in label_sets {
+            let mut labels = Vec::new();
+
+            let label_strings = label_set.label_strings();
+
+            for (node, label_ixs) in label_set.labels().iter() {
+                for &ix in label_ixs.iter() {
+                    labels.push((*node, label_strings[ix].to_string()));
+                }
+            }
+
+            labels.sort();
+
+            self.label_sets.push(SessionLabelSet {
+                name: label_set.name().to_string(),
+                annotation_name: label_set.annotation_name.clone(),
+                column: label_set.column_str.clone(),
+                path_name: label_set.path_name.clone(),
+                visible: label_set.is_visible(),
+                labels,
+            });
+        }
+
+        self.label_sets.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// The name of `path` in `graph`, used for storing reference paths
+    pub fn path_name(graph: &PackedGraph, path: PathId) -> Option<String> {
+        let name = graph.get_path_name_vec(path)?;
+        Some(format!("{}", name.as_bstr()))
+    }
+}
+
+/// Saves the current workspace to a session file at `path`, and its
+/// overlays next to it
+pub fn save_session(
+    path: &Path,
+    gfa_file: &str,
+    layout_file: &str,
+    app: &App,
+    main_view: &MainView,
+    gui: &Gui,
+    graph: &PackedGraph,
+) -> Result<()> {
+    let path = if path.extension().is_none() {
+        path.with_extension(SESSION_FILE_EXT)
+    } else {
+        path.to_owned()
+    };
+
+    let mut session = Session::new(gfa_file, layout_file);
+
+    session.view = Some(main_view.view());
+    session.dark_mode = app.shared_state().dark_mode().load();
+
+    session.settings = app
+        .settings
+        .to_key_values()
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+    let annotations = app.annotations();
+
+    for (name, file_type) in annotations.annot_names() {
+        let file_path = match file_type {
+            AnnotationFileType::Gff3 => {
+                annotations.get_gff3(name).map(|r| r.file_path().to_owned())
+            }
+            AnnotationFileType::Bed => {
+                annotations.get_bed(name).map(|r| r.file_path().to_owned())
+            }
+        };
+
+        if let Some(file_path) = file_path {
+            session.annotation_files.push(file_path);
+        }
+
+        let ref_path = annotations
+            .get_default_ref_path(name)
+            .and_then(|path| Session::path_name(graph, path));
+
+        if let Some(path_name) = ref_path {
+            session.ref_paths.push((name.to_string(), path_name));
+        }
+    }
+
+    session.add_label_sets(annotations.label_sets().values());
+
+    session.filters = gui
+        .annotation_filter_fields()
+        .into_iter()
+        .map(|(file_type, file_name, column, fields)| SessionFilter {
+            file_type,
+            file_name,
+            column,
+            fields,
+        })
+        .collect();
+
+    let overlay_state = app.shared_state().overlay_state();
+    let pipelines = &main_view.node_draw_system.pipelines;
+
+    let mut overlays = Vec::new();
+
+    for (id, _kind, name) in pipelines.overlay_names() {
+        if let Some(data) = overlay_state.overlay_data(id) {
+            let legend = overlay_state.overlay_legend(id);
+            overlays.push((name.to_string(), data, legend));
+        } else {
+            warn!("No data found for overlay {}, not saving it", name);
+        }
+    }
+
+    session.write_overlays(&path, graph, &overlays)?;
+
+    session.current_overlay = overlay_state.current_overlay().and_then(|id| {
+        pipelines
+            .overlay_names()
+            .into_iter()
+            .find(|(i, _, _)| *i == id)
+            .map(|(_, _, name)| name.to_string())
+    });
+
+    if let Some((_, selection)) = app.selected_nodes_() {
+        let mut nodes = selection.iter().copied().collect::<Vec<_>>();
+        nodes.sort();
+        session.selection = nodes;
+    }
+
+    session.bookmarks = app.shared_state().bookmarks().lock().clone();
+
+    session.write(&path)?;
+
+    info!("Saved session to {:?}", path);
+
+    Ok(())
+}
+
+/// Restores the workspace from a session, except for the overlays,
+/// which must already have been created from `Session::load_overlays`
+/// so that the current overlay can be found by name
+pub fn apply_session(
+    session: Session,
+    app: &App,
+    main_view: &MainView,
+    gui: &mut Gui,
+    reactor: &Reactor,
+    gfa_file: &str,
+) -> Result<()> {
+    if Path::new(gfa_file).canonicalize().ok().as_ref() != Some(&session.gfa) {
+        warn!(
+            "Session was saved for {:?}, but {} is loaded",
+            session.gfa, gfa_file
+        );
+    }
+
+    for (key, value) in session.settings.iter() {
+        if let Err(err) = app.settings.set_from_str(key, value) {
+            warn!("Error restoring setting {}: {:?}", key, err);
+        }
+    }
+
+    let gui_msg_tx = gui.clone_gui_msg_tx();
+
+    app.shared_state().dark_mode().store(session.dark_mode);
+    if session.dark_mode {
+        gui_msg_tx.send(GuiMsg::SetDarkMode).unwrap();
+    } else {
+        gui_msg_tx.send(GuiMsg::SetLightMode).unwrap();
+    }
+
+    if let Some(view) = session.view {
+        main_view.set_view(view);
+    }
+
+    let graph_query = reactor.graph_query.clone();
+    let overlay_state = app.shared_state().overlay_state();
+
+    let pipelines = &main_view.node_draw_system.pipelines;
+
+    gui.populate_overlay_list(pipelines.overlay_names().into_iter());
+
+    if let Some(id) = session
+        .current_overlay
+        .as_ref()
+        .and_then(|name| pipelines.overlay_id(name))
+    {
+        overlay_state.set_current_overlay(Some(id));
+    }
+
+    // restoring a filter replaces the whole filter for that file, so
+    // the columns have to be grouped first
+    let mut filters: HashMap<_, Vec<_>> = HashMap::new();
+
+    for filter in session.filters.iter() {
+        filters
+            .entry((filter.file_type, filter.file_name.clone()))
+            .or_default()
+            .push((filter.column.clone(), filter.fields.clone()));
+    }
+
+    for ((file_type, file_name), columns) in filters {
+        gui.restore_annotation_filter(file_type, &file_name, columns);
+    }
+
+    *app.shared_state().bookmarks().lock() = session.bookmarks.clone();
+
+    let app_tx = app.channels().app_tx.clone();
+
+    if !session.selection.is_empty() {
+        let nodes = session.selection.iter().copied().collect();
+        app_tx
+            .send(AppMsg::Selection(Select::Many { nodes, clear: true }))
+            .unwrap();
+    }
+
+    reactor.spawn_forget(async move {
+        session.load_annotations(graph_query.graph(), &app_tx);
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(session: &Session) -> Session {
+        let mut bytes = Vec::new();
+        session.write_to(&mut bytes).unwrap();
+        Session::read_from(bytes.as_slice(), Path::new("/sessions")).unwrap()
+    }
+
+    #[test]
+    fn escaped_fields_roundtrip() {
+        for text in &[
+            "plain",
+            "tab\there",
+            "two\nlines",
+            "back\\slash",
+            "\\t is not a tab",
+            "trailing\\",
+        ] {
+            assert_eq!(unescape(&escape(text)), *text);
+            assert!(!escape(text).contains(|c| c == '\t' || c == '\n'));
+        }
+
+        assert_eq!(escape("a\r\nb"), "a\\nb");
+    }
+
+    #[test]
+    fn cleaned_fields_have_no_separators() {
+        assert_eq!(clean("a\tb\nc\rd"), "a b c d");
+        assert_eq!(clean("back\\slash"), "back\\slash");
+    }
+
+    #[test]
+    fn session_roundtrip() {
+        let mut session = Session::default();
+
+        session.gfa = PathBuf::from("/data/graph.gfa");
+        session.layout = PathBuf::from("/data/graph.lay");
+        session.view = Some(View {
+            center: Point::new(10.5, -3.0),
+            scale: 2.5,
+        });
+        session.dark_mode = true;
+
+        session.settings = vec![
+            ("node_width".to_string(), "3.5".to_string()),
+            ("label".to_string(), "tab\tand\nnewline".to_string()),
+        ];
+
+        session.annotation_files = vec![PathBuf::from("/data/genes.gff3")];
+        session.ref_paths =
+            vec![("genes.gff3".to_string(), "chr\\1".to_string())];
+
+        session.filters = vec![SessionFilter {
+            file_type: AnnotationFileType::Bed,
+            file_name: "peaks.bed".to_string(),
+            column: "name".to_string(),
+            fields: vec!["a\tb".to_string(), "c\\d".to_string()],
+        }];
+
+        session.label_sets = vec![SessionLabelSet {
+            name: "genes\tset".to_string(),
+            annotation_name: "genes.gff3".to_string(),
+            column: "Name".to_string(),
+            path_name: "chr1".to_string(),
+            visible: false,
+            labels: vec![
+                (NodeId::from(3), "BRCA\n2".to_string()),
+                (NodeId::from(7), "C:\\gene".to_string()),
+            ],
+        }];
+
+        session.overlays = vec![SessionOverlay {
+            name: "depth".to_string(),
+            file: PathBuf::from("work.overlays/0.gfo"),
+        }];
+        session.current_overlay = Some("depth".to_string());
+
+        session.selection = vec![NodeId::from(1), NodeId::from(5)];
+
+        let view = View {
+            center: Point::new(1.0, 2.0),
+            scale: 0.5,
+        };
+        session.bookmarks = vec![
+            Bookmark::new("start", "", view),
+            Bookmark::new("tab\there", "line 1\nline\t2 \\ end\\", view),
+        ];
+
+        let loaded = roundtrip(&session);
+
+        assert_eq!(loaded.gfa, session.gfa);
+        assert_eq!(loaded.layout, session.layout);
+        assert_eq!(loaded.view, session.view);
+        assert!(loaded.dark_mode);
+
+        assert_eq!(
+            loaded.settings,
+            vec![
+                ("node_width".to_string(), "3.5".to_string()),
+                ("label".to_string(), "tab and newline".to_string()),
+            ]
+        );
+
+        assert_eq!(loaded.annotation_files, session.annotation_files);
+        assert_eq!(loaded.ref_paths, session.ref_paths);
+
+        let filter = &loaded.filters[0];
+        assert_eq!(filter.file_type, AnnotationFileType::Bed);
+        assert_eq!(filter.file_name, "peaks.bed");
+        assert_eq!(filter.fields, vec!["a b", "c\\d"]);
+
+        let label_set = &loaded.label_sets[0];
+        assert_eq!(label_set.name, "genes set");
+        assert!(!label_set.visible);
+        assert_eq!(
+            label_set.labels,
+            vec![
+                (NodeId::from(3), "BRCA 2".to_string()),
+                (NodeId::from(7), "C:\\gene".to_string()),
+            ]
+        );
+
+        // relative overlay paths are resolved against the session
+        assert_eq!(loaded.overlays[0].name, "depth");
+        assert_eq!(
+            loaded.overlays[0].file,
+            PathBuf::from("/sessions/work.overlays/0.gfo")
+        );
+        assert_eq!(loaded.current_overlay, session.current_overlay);
+
+        assert_eq!(loaded.selection, session.selection);
+
+        // bookmark notes are escaped, so they roundtrip exactly
+        assert_eq!(loaded.bookmarks[0], session.bookmarks[0]);
+        assert_eq!(loaded.bookmarks[1].name, "tab here");
+        assert_eq!(loaded.bookmarks[1].note, "line 1\nline\t2 \\ end\\");
+    }
+
+    #[test]
+    fn invalid_sessions_are_errors() {
+        let read =
+            |text: &str| Session::read_from(text.as_bytes(), Path::new("."));
+
+        assert!(read("").is_err());
+        assert!(read("not-a-session\t1\n").is_err());
+        assert!(read("gfaestus-session\t99\n").is_err());
+        assert!(read("gfaestus-session\t1\nview\t1.0\n").is_err());
+        assert!(read("gfaestus-session\t1\nlabel\tmissing\t1\tx\n").is_err());
+
+        let session = read("gfaestus-session\t1\nunknown\tentry\n").unwrap();
+        assert!(session.bookmarks.is_empty());
+    }
+}
@@ -61,8 +61,8 @@ pub trait GraphLayout {
 
 #[derive(Debug, Clone)]
 pub struct Universe<G: GraphLayout> {
-    // TODO bp_per_world_unit isn't used yet; and it should probably
-    // be a 2D vector allowing nonuniform scaling
+    // TODO bp_per_world_unit should probably be a 2D vector allowing
+    // nonuniform scaling
     bp_per_world_unit: f32,
     // grid: grid::Grid<NodeId>,
     graph_layout: G,
@@ -82,6 +82,12 @@ impl<G: GraphLayout> Universe<G> {
     pub fn layout_mut(&mut self) -> &mut G {
         &mut self.graph_layout
     }
+
+    /// The average number of base pairs per world unit of node
+    /// length, used to convert distances on screen to base pairs
+    pub fn bp_per_world_unit(&self) -> f32 {
+        self.bp_per_world_unit
+    }
 }
 
 impl Universe<FlatLayout> {
@@ -89,12 +95,24 @@ impl Universe<FlatLayout> {
         graph: &PackedGraph,
         layout_path: &str,
     ) -> Result<Self> {
-        let bp_per_world_unit = 1.0;
         let offset = Point::new(0.0, 0.0);
         let angle = 0.0;
 
         let graph_layout = FlatLayout::from_laid_out_graph(graph, layout_path)?;
 
+        let total_bp = graph.total_length() as f32;
+        let world_length = graph_layout
+            .nodes()
+            .iter()
+            .map(|node| node.p0.dist(node.p1))
+            .sum::<f32>();
+
+        let bp_per_world_unit = if total_bp > 0.0 && world_length > 0.0 {
+            total_bp / world_length
+        } else {
+            1.0
+        };
+
         Ok(Self {
             bp_per_world_unit,
             graph_layout,
@@ -190,6 +208,9 @@ pub struct FlatLayout {
     pub component_offsets: Vec<usize>,
     top_left: Point,
     bottom_right: Point,
+    // nodes that had no rows in the layout file, placed in a column
+    // to the right of the rest of the layout
+    missing_nodes: Vec<NodeId>,
 }
 
 impl GraphLayout for FlatLayout {
@@ -207,6 +228,11 @@ impl GraphLayout for FlatLayout {
 }
 
 impl FlatLayout {
+    /// The nodes that were missing from the layout file
+    pub fn missing_nodes(&self) -> &[NodeId] {
+        &self.missing_nodes
+    }
+
     pub fn node_component(&self, node_id: NodeId) -> usize {
         let offset =
             self.component_offsets.iter().enumerate().find(|(_, o)| {
@@ -311,10 +337,25 @@ impl FlatLayout {
         let mut min_y = std::f32::MAX;
         let mut max_y = std::f32::MIN;
 
+        let mut missing_nodes = Vec::new();
+        let mut missing_indices = Vec::new();
+
         for handle in handles {
             let id = handle.id();
 
-            let (p0, p1) = *layout_map.get(&id).unwrap();
+            let (p0, p1) = if let Some(points) = layout_map.get(&id) {
+                *points
+            } else {
+                // placed once the bounding box of the rest is known
+                missing_nodes.push(id);
+                missing_indices.push(nodes.len());
+                node_ids.push(id);
+                nodes.push(Node {
+                    p0: Point::new(0.0, 0.0),
+                    p1: Point::new(0.0, 0.0),
+                });
+                continue;
+            };
 
             let comp = component_map.get(&id).copied().unwrap_or(0);
 
@@ -334,6 +375,31 @@ impl FlatLayout {
             nodes.push(Node { p0, p1 });
         }
 
+        if !missing_nodes.is_empty() {
+            warn!("{} nodes are missing from the layout", missing_nodes.len());
+
+            if missing_nodes.len() == nodes.len() {
+                min_x = 0.0;
+                max_x = 0.0;
+                min_y = 0.0;
+                max_y = 0.0;
+            }
+
+            let spacing = 20.0;
+            let x = max_x + 10.0 * spacing;
+
+            for (row, &ix) in missing_indices.iter().enumerate() {
+                let p0 = Point::new(x, min_y + row as f32 * spacing);
+                let p1 = Point::new(x + spacing, p0.y);
+
+                max_y = max_y.max(p0.y);
+
+                nodes[ix] = Node { p0, p1 };
+            }
+
+            max_x = x + spacing;
+        }
+
         let top_left = Point::new(min_x, min_y);
         let bottom_right = Point::new(max_x, max_y);
 
@@ -343,6 +409,7 @@ impl FlatLayout {
             component_offsets: components,
             top_left,
             bottom_right,
+            missing_nodes,
         })
     }
 }
@@ -1,12 +1,15 @@
+pub mod capture;
 pub mod compute;
 pub mod context;
 pub mod debug;
 pub mod draw_system;
+pub mod offscreen;
 pub mod render_pass;
 pub mod texture;
 
 mod init;
 
+use capture::*;
 use context::*;
 use init::*;
 use render_pass::*;
@@ -52,6 +55,9 @@ pub struct GfaestusVk {
 
     swapchain_images: Vec<vk::Image>,
     swapchain_image_views: Vec<vk::ImageView>,
+    // whether the swapchain images were created with `TRANSFER_SRC`
+    // usage, which frame capture requires
+    swapchain_transfer_src: bool,
 
     pub render_passes: RenderPasses,
     pub node_attachments: NodeAttachments,
@@ -62,6 +68,8 @@ pub struct GfaestusVk {
     pub transient_command_pool: vk::CommandPool,
     in_flight_frames: InFlightFrames,
 
+    frame_capture: FrameCapture,
+
     pub vk_context: VkContext,
     // dimensions: ScreenDims,
     // pub supported_features: SupportedFeatures,
@@ -165,13 +173,18 @@ impl GfaestusVk {
         let width = 800u32;
         let height = 600u32;
 
-        let (swapchain, swapchain_khr, swapchain_props, images) =
-            create_swapchain_and_images(
-                &vk_context,
-                graphics_ix,
-                present_ix,
-                [width, height],
-            )?;
+        let (
+            swapchain,
+            swapchain_khr,
+            swapchain_props,
+            images,
+            swapchain_transfer_src,
+        ) = create_swapchain_and_images(
+            &vk_context,
+            graphics_ix,
+            present_ix,
+            [width, height],
+        )?;
         let swapchain_image_views = create_swapchain_image_views(
             vk_context.device(),
             &images,
@@ -250,6 +263,7 @@ impl GfaestusVk {
 
             swapchain_images: images,
             swapchain_image_views,
+            swapchain_transfer_src,
 
             render_passes,
             node_attachments,
@@ -260,6 +274,8 @@ impl GfaestusVk {
             transient_command_pool,
 
             in_flight_frames,
+
+            frame_capture: FrameCapture::default(),
         };
 
         result.render_passes.set_vk_debug_names(&result)?;
@@ -291,6 +307,29 @@ impl GfaestusVk {
         &self.vk_context
     }
 
+    /// Whether frames can be captured, which requires a surface that
+    /// supports copying from the swapchain images
+    pub fn supports_frame_capture(&self) -> bool {
+        self.swapchain_transfer_src
+    }
+
+    /// Copy the next frame drawn by `draw_frame_from` to host memory,
+    /// after which it can be retrieved with `take_captured_frame`
+    pub fn request_frame_capture(&mut self) -> Result<()> {
+        if !self.swapchain_transfer_src {
+            anyhow::bail!(
+                "Frame capture is not supported: the swapchain images \
+                 can't be copied from on this device"
+            );
+        }
+        self.frame_capture.request();
+        Ok(())
+    }
+
+    pub fn take_captured_frame(&mut self) -> Option<CapturedFrame> {
+        self.frame_capture.take_captured()
+    }
+
     pub fn draw_frame_from<F>(
         &mut self,
         window_size: [u32; 2],
@@ -339,14 +378,34 @@ impl GfaestusVk {
 
         unsafe { self.vk_context.device().reset_fences(&wait_fences) }?;
 
-        let device = self.vk_context.device();
-
         let wait_semaphores = [img_available];
         let signal_semaphores = [render_finished];
         let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
 
         let queue = self.graphics_queue;
 
+        let capture_extent = self.swapchain_props.extent;
+
+        let capture = if self.frame_capture.is_requested() {
+            let mut frame_capture = std::mem::take(&mut self.frame_capture);
+            let buffer = frame_capture.prepare(self, capture_extent);
+            self.frame_capture = frame_capture;
+
+            match buffer {
+                Ok(buffer) => {
+                    Some((self.swapchain_images[img_index as usize], buffer))
+                }
+                Err(err) => {
+                    error!("Error preparing frame capture: {:?}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let device = self.vk_context.device();
+
         let framebuffers = &self.framebuffers[img_index as usize];
 
         let cmd_buf = self.execute_one_time_commands_semaphores(
@@ -359,6 +418,17 @@ impl GfaestusVk {
             in_flight_fence,
             |cmd_buf| {
                 commands(device, cmd_buf, framebuffers);
+
+                if let Some((image, buffer)) = capture {
+                    FrameCapture::record_copy(
+                        device,
+                        cmd_buf,
+                        image,
+                        vk::ImageLayout::PRESENT_SRC_KHR,
+                        buffer,
+                        capture_extent,
+                    );
+                }
             },
         )?;
 
@@ -394,6 +464,13 @@ impl GfaestusVk {
             device.free_command_buffers(self.command_pool, &[cmd_buf]);
         };
 
+        if capture.is_some() {
+            let format = self.swapchain_props.format.format;
+            if let Err(err) = self.frame_capture.read_back(device, format) {
+                error!("Error reading captured frame: {:?}", err);
+            }
+        }
+
         Ok(false)
     }
 
@@ -1240,13 +1317,18 @@ impl GfaestusVk {
             self.swapchain_props.extent.height,
         ]);
 
-        let (swapchain, swapchain_khr, swapchain_props, images) =
-            create_swapchain_and_images(
-                &self.vk_context,
-                self.graphics_family_index,
-                self.present_family_index,
-                dimensions,
-            )?;
+        let (
+            swapchain,
+            swapchain_khr,
+            swapchain_props,
+            images,
+            swapchain_transfer_src,
+        ) = create_swapchain_and_images(
+            &self.vk_context,
+            self.graphics_family_index,
+            self.present_family_index,
+            dimensions,
+        )?;
 
         let swapchain_image_views =
             create_swapchain_image_views(device, &images, swapchain_props)?;
@@ -1310,6 +1392,7 @@ impl GfaestusVk {
 
         self.swapchain_images = images;
         self.swapchain_image_views = swapchain_image_views;
+        self.swapchain_transfer_src = swapchain_transfer_src;
 
         self.render_passes = render_passes;
         self.node_attachments = node_attachments;
@@ -1425,6 +1508,7 @@ impl Drop for GfaestusVk {
         self.cleanup_swapchain();
 
         let device = self.vk_context.device();
+        self.frame_capture.destroy(device);
         self.in_flight_frames.destroy(device);
 
         unsafe {
@@ -0,0 +1,292 @@
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk, Device};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use super::GfaestusVk;
+
+/// An 8-bit RGBA image read back from the swapchain
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// A transparent black frame of the given size
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixels = vec![0u8; (width * height * 4) as usize];
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Copy `frame` into this frame with its top left corner at
+    /// `(x, y)`, cropping the parts that fall outside
+    pub fn copy_from(&mut self, frame: &CapturedFrame, x: u32, y: u32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let width = frame.width.min(self.width - x) as usize;
+        let height = frame.height.min(self.height - y) as usize;
+
+        for row in 0..height {
+            let src_start = row * frame.width as usize * 4;
+            let src = &frame.pixels[src_start..src_start + width * 4];
+
+            let dst_start =
+                ((y as usize + row) * self.width as usize + x as usize) * 4;
+            self.pixels[dst_start..dst_start + width * 4].copy_from_slice(src);
+        }
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let writer = BufWriter::new(file);
+
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+}
+
+/// Host-visible buffer that the swapchain image is copied into when
+/// a frame capture has been requested
+pub struct FrameCapture {
+    requested: bool,
+
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: vk::DeviceSize,
+    extent: vk::Extent2D,
+
+    captured: Option<CapturedFrame>,
+}
+
+impl std::default::Default for FrameCapture {
+    fn default() -> Self {
+        Self {
+            requested: false,
+
+            buffer: vk::Buffer::null(),
+            memory: vk::DeviceMemory::null(),
+            size: 0,
+            extent: vk::Extent2D::default(),
+
+            captured: None,
+        }
+    }
+}
+
+impl FrameCapture {
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn take_captured(&mut self) -> Option<CapturedFrame> {
+        self.captured.take()
+    }
+
+    /// Make sure the buffer can hold an image of size `extent`,
+    /// returning the buffer to copy the swapchain image into
+    pub(super) fn prepare(
+        &mut self,
+        app: &GfaestusVk,
+        extent: vk::Extent2D,
+    ) -> Result<vk::Buffer> {
+        if self.buffer != vk::Buffer::null() && self.extent == extent {
+            return Ok(self.buffer);
+        }
+
+        self.destroy(app.vk_context().device());
+
+        let img_size = (extent.width * extent.height * 4) as vk::DeviceSize;
+
+        let usage = vk::BufferUsageFlags::TRANSFER_DST;
+
+        let mem_props = vk::MemoryPropertyFlags::HOST_VISIBLE
+            | vk::MemoryPropertyFlags::HOST_COHERENT;
+
+        let (buffer, memory, size) =
+            app.create_buffer(img_size, usage, mem_props)?;
+
+        app.set_debug_object_name(buffer, "Frame Capture Buffer")?;
+
+        self.buffer = buffer;
+        self.memory = memory;
+        self.size = size;
+        self.extent = extent;
+
+        Ok(buffer)
+    }
+
+    /// Records the commands copying `image` to the capture buffer;
+    /// the image is transitioned from `layout` and back again
+    pub(super) fn record_copy(
+        device: &Device,
+        cmd_buf: vk::CommandBuffer,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        buffer: vk::Buffer,
+        extent: vk::Extent2D,
+    ) {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(1)
+            .build();
+
+        let to_transfer = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .old_layout(layout)
+            .new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let to_present = vk::ImageMemoryBarrier::builder()
+            .src_access_mask(vk::AccessFlags::TRANSFER_READ)
+            .dst_access_mask(vk::AccessFlags::MEMORY_READ)
+            .old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .new_layout(layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource_range)
+            .build();
+
+        let region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            })
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .build();
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::BY_REGION,
+                &[],
+                &[],
+                &[to_transfer],
+            );
+
+            device.cmd_copy_image_to_buffer(
+                cmd_buf,
+                image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                buffer,
+                &[region],
+            );
+
+            device.cmd_pipeline_barrier(
+                cmd_buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::BY_REGION,
+                &[],
+                &[],
+                &[to_present],
+            );
+        }
+    }
+
+    /// Reads the copied image from the capture buffer; must only be
+    /// called once the copy commands have finished executing
+    pub(super) fn read_back(
+        &mut self,
+        device: &Device,
+        format: vk::Format,
+    ) -> Result<()> {
+        self.requested = false;
+
+        let bgra = match format {
+            vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM => true,
+            vk::Format::R8G8B8A8_SRGB | vk::Format::R8G8B8A8_UNORM => false,
+            _ => {
+                bail!("Unsupported swapchain format for capture: {:?}", format)
+            }
+        };
+
+        let len = (self.extent.width * self.extent.height * 4) as usize;
+
+        let mut pixels = vec![0u8; len];
+
+        unsafe {
+            let data_ptr = device.map_memory(
+                self.memory,
+                0,
+                self.size,
+                vk::MemoryMapFlags::empty(),
+            )?;
+
+            let slice = std::slice::from_raw_parts(data_ptr as *const u8, len);
+            pixels.copy_from_slice(slice);
+
+            device.unmap_memory(self.memory);
+        }
+
+        for pixel in pixels.chunks_exact_mut(4) {
+            if bgra {
+                pixel.swap(0, 2);
+            }
+            pixel[3] = 255;
+        }
+
+        self.captured = Some(CapturedFrame {
+            width: self.extent.width,
+            height: self.extent.height,
+            pixels,
+        });
+
+        Ok(())
+    }
+
+    pub fn destroy(&mut self, device: &Device) {
+        unsafe {
+            device.destroy_buffer(self.buffer, None);
+            device.free_memory(self.memory, None);
+        }
+
+        self.buffer = vk::Buffer::null();
+        self.memory = vk::DeviceMemory::null();
+        self.size = 0;
+        self.extent = vk::Extent2D::default();
+    }
+}
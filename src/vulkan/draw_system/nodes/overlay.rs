@@ -6,7 +6,12 @@ use anyhow::*;
 
 use crate::vulkan::context::NodeRendererType;
 use crate::vulkan::texture::GradientTexture;
-use crate::{overlays::OverlayKind, vulkan::GfaestusVk};
+use crate::{
+    overlays::{OverlayData, OverlayKind},
+    vulkan::GfaestusVk,
+};
+
+use handlegraph::handle::NodeId;
 
 use super::NodePipelineConfig;
 
@@ -183,6 +188,53 @@ impl OverlayPipelines {
     fn update_overlay(&mut self, overlay_id: usize, overlay: Overlay) {
         self.overlays.insert(overlay_id, overlay);
     }
+
+    /// Returns the ID of the first overlay with the provided name
+    pub fn overlay_id(&self, name: &str) -> Option<usize> {
+        self.overlay_names()
+            .into_iter()
+            .find(|(_, _, n)| *n == name)
+            .map(|(id, _, _)| id)
+    }
+
+    pub fn overlay_kind(&self, overlay_id: usize) -> Option<OverlayKind> {
+        self.overlays.get(&overlay_id).map(|o| o.kind)
+    }
+
+    pub fn overlay_mut(&mut self, overlay_id: usize) -> Option<&mut Overlay> {
+        self.overlays.get_mut(&overlay_id)
+    }
+
+    /// Replaces the overlay at `overlay_id`, freeing the old
+    /// overlay's buffer.
+    ///
+    /// Waits for the GPU to be idle, as the old buffer may be in use.
+    pub fn replace_overlay(
+        &mut self,
+        app: &GfaestusVk,
+        overlay_id: usize,
+        overlay: Overlay,
+    ) -> Result<()> {
+        app.wait_gpu_idle()?;
+
+        if let Some(old) = self.overlays.remove(&overlay_id) {
+            if let Some(buffer_view) = old.buffer_view {
+                unsafe {
+                    self.device.destroy_buffer_view(buffer_view, None);
+                }
+            }
+            app.allocator.destroy_buffer(old.buffer, &old.alloc)?;
+        }
+
+        // make sure the descriptor set is rewritten with the new buffer
+        if self.overlay_set_id == Some(overlay_id) {
+            self.overlay_set_id = None;
+        }
+
+        self.update_overlay(overlay_id, overlay);
+
+        Ok(())
+    }
 }
 
 pub struct OverlayPipelineRGB {
@@ -611,6 +663,43 @@ impl Overlay {
         })
     }
 
+    /// Create a new host-visible overlay of the same kind as `data`,
+    /// and fill it with the data
+    pub fn new_with_data(
+        name: &str,
+        app: &GfaestusVk,
+        node_count: usize,
+        data: &OverlayData,
+    ) -> Result<Self> {
+        let mut overlay = match data.kind() {
+            OverlayKind::RGB => Self::new_empty_rgb(name, app, node_count)?,
+            OverlayKind::Value => Self::new_empty_value(name, app, node_count)?,
+        };
+
+        overlay.update_from_data(data)?;
+
+        Ok(overlay)
+    }
+
+    /// Overwrite the entire contents of a host-visible overlay; the
+    /// value at index `i` is used for node `i + 1`
+    pub fn update_from_data(&mut self, data: &OverlayData) -> Result<()> {
+        match data {
+            OverlayData::RGB(colors) => self.update_rgb_overlay(
+                colors
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, col)| (NodeId::from((ix as u64) + 1), *col)),
+            ),
+            OverlayData::Value(values) => self.update_value_overlay(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, v)| (NodeId::from((ix as u64) + 1), *v)),
+            ),
+        }
+    }
+
     /// Update the colors for a host-visible overlay by providing a
     /// set of node IDs and new values
     pub fn update_value_overlay<I>(
@@ -318,6 +318,8 @@ pub(super) fn choose_physical_device(
     ))
 }
 
+/// Also returns whether the swapchain images can be copied from,
+/// i.e. whether they were created with `TRANSFER_SRC` usage
 pub(super) fn create_swapchain_and_images(
     vk_context: &VkContext,
     graphics_ix: u32,
@@ -328,6 +330,7 @@ pub(super) fn create_swapchain_and_images(
     vk::SwapchainKHR,
     SwapchainProperties,
     Vec<vk::Image>,
+    bool,
 )> {
     let details = SwapchainSupportDetails::new(
         vk_context.physical_device(),
@@ -359,6 +362,19 @@ pub(super) fn create_swapchain_and_images(
 
     let family_indices = [graphics_ix, present_ix];
 
+    // the swapchain images are copied from when capturing frames
+    let transfer_src = details
+        .capabilities
+        .supported_usage_flags
+        .contains(vk::ImageUsageFlags::TRANSFER_SRC);
+
+    let image_usage = if transfer_src {
+        vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | vk::ImageUsageFlags::TRANSFER_SRC
+    } else {
+        vk::ImageUsageFlags::COLOR_ATTACHMENT
+    };
+
     let create_info = {
         let mut builder = vk::SwapchainCreateInfoKHR::builder()
             .surface(vk_context.surface_khr())
@@ -367,7 +383,7 @@ pub(super) fn create_swapchain_and_images(
             .image_color_space(props.format.color_space)
             .image_extent(props.extent)
             .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT);
+            .image_usage(image_usage);
 
         builder = if graphics_ix != present_ix {
             builder
@@ -390,7 +406,7 @@ pub(super) fn create_swapchain_and_images(
         unsafe { swapchain.create_swapchain(&create_info, None) }?;
     let images = unsafe { swapchain.get_swapchain_images(swapchain_khr) }?;
 
-    Ok((swapchain, swapchain_khr, props, images))
+    Ok((swapchain, swapchain_khr, props, images, transfer_src))
 }
 
 pub(super) fn create_swapchain_image_views(
@@ -0,0 +1,197 @@
+use ash::{version::InstanceV1_0, vk, Device};
+
+use anyhow::{anyhow, Result};
+
+#[allow(unused_imports)]
+use log::{debug, error, info, trace, warn};
+
+use crate::view::ScreenDims;
+
+use super::capture::{CapturedFrame, FrameCapture};
+use super::render_pass::{Framebuffers, NodeAttachments, OffscreenAttachment};
+use super::texture::Texture;
+use super::{GfaestusVk, SwapchainProperties};
+
+/// The largest offscreen render target that will be used, as the
+/// node attachments are multisampled and grow quickly
+pub const MAX_TILE_SIZE: u32 = 2048;
+
+/// Attachments and framebuffers with the same layout as those used
+/// to draw to the swapchain, but with a color image of any size (up
+/// to device limits) taking the place of the swapchain image, so
+/// that the main view can be rendered at a resolution independent
+/// of the window
+pub struct OffscreenTarget {
+    extent: vk::Extent2D,
+    format: vk::Format,
+
+    pub node_attachments: NodeAttachments,
+    pub offscreen_attachment: OffscreenAttachment,
+
+    color: Texture,
+
+    pub framebuffers: Framebuffers,
+
+    capture: FrameCapture,
+}
+
+impl OffscreenTarget {
+    pub fn new(app: &GfaestusVk, width: u32, height: u32) -> Result<Self> {
+        let extent = vk::Extent2D { width, height };
+
+        let props = SwapchainProperties {
+            extent,
+            ..app.swapchain_props
+        };
+
+        let format = props.format.format;
+
+        let vk_context = app.vk_context();
+        let command_pool = app.transient_command_pool;
+        let queue = app.graphics_queue;
+
+        let node_attachments = NodeAttachments::new(
+            vk_context,
+            command_pool,
+            queue,
+            props,
+            app.msaa_samples,
+            app.render_passes.id_format,
+        )?;
+
+        let offscreen_attachment =
+            OffscreenAttachment::new(vk_context, command_pool, queue, props)?;
+
+        let color = Texture::create_attachment_image(
+            vk_context,
+            command_pool,
+            queue,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSFER_SRC,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            extent,
+            format,
+            None,
+        )?;
+
+        app.set_debug_object_name(color.image, "Offscreen Render Target")?;
+
+        let framebuffers = app.render_passes.framebuffers(
+            vk_context.device(),
+            &node_attachments,
+            &offscreen_attachment,
+            color.view,
+            props,
+        )?;
+
+        Ok(Self {
+            extent,
+            format,
+
+            node_attachments,
+            offscreen_attachment,
+
+            color,
+
+            framebuffers,
+
+            capture: FrameCapture::default(),
+        })
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    pub fn dims(&self) -> ScreenDims {
+        ScreenDims {
+            width: self.extent.width as f32,
+            height: self.extent.height as f32,
+        }
+    }
+
+    pub fn destroy(&mut self, device: &Device) {
+        self.framebuffers.destroy(device);
+        self.node_attachments.destroy(device);
+        self.offscreen_attachment.destroy(device);
+        self.color.destroy(device);
+        self.capture.destroy(device);
+    }
+}
+
+impl GfaestusVk {
+    /// The largest width and height of an `OffscreenTarget`
+    pub fn max_offscreen_size(&self) -> u32 {
+        let props = unsafe {
+            self.vk_context.instance().get_physical_device_properties(
+                self.vk_context.physical_device(),
+            )
+        };
+
+        let limits = props.limits;
+
+        limits
+            .max_image_dimension2_d
+            .min(limits.max_framebuffer_width)
+            .min(limits.max_framebuffer_height)
+            .min(MAX_TILE_SIZE)
+    }
+
+    /// Record `commands` drawing to `target`, then wait for them to
+    /// finish and read the rendered image back to host memory.
+    ///
+    /// `commands` must leave the target color image in the
+    /// `COLOR_ATTACHMENT_OPTIMAL` layout, i.e. not use the GUI render
+    /// pass, which transitions it for presentation. If `commands`
+    /// returns an error, the commands recorded so far are still
+    /// submitted, but nothing is read back
+    pub fn draw_offscreen<F>(
+        &self,
+        target: &mut OffscreenTarget,
+        commands: F,
+    ) -> Result<CapturedFrame>
+    where
+        F: FnOnce(&Device, vk::CommandBuffer, &Framebuffers) -> Result<()>,
+    {
+        let extent = target.extent;
+        let buffer = target.capture.prepare(self, extent)?;
+
+        let device = self.vk_context.device();
+
+        let image = target.color.image;
+        let framebuffers = &target.framebuffers;
+
+        let mut recorded = Ok(());
+
+        Self::execute_one_time_commands(
+            device,
+            self.transient_command_pool,
+            self.graphics_queue,
+            |cmd_buf| {
+                recorded = commands(device, cmd_buf, framebuffers);
+
+                if recorded.is_err() {
+                    return;
+                }
+
+                FrameCapture::record_copy(
+                    device,
+                    cmd_buf,
+                    image,
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    buffer,
+                    extent,
+                );
+            },
+        )?;
+
+        recorded?;
+
+        target.capture.read_back(device, target.format)?;
+
+        target
+            .capture
+            .take_captured()
+            .ok_or(anyhow!("Offscreen render could not be read back"))
+    }
+}